@@ -0,0 +1,63 @@
+use ariadne::Report;
+
+use crate::syntax::Span;
+
+/// Collects the errors and warnings of the compiler's passes,
+/// so that a single run reports as many of them as possible.
+#[derive(Default)]
+pub struct Diagnostics {
+    diagnostics: Vec<Diagnostic>,
+}
+
+struct Diagnostic {
+    /// The code the report is about, which orders the reports
+    span: Span,
+    report: Report<'static>,
+    is_error: bool,
+}
+
+impl Diagnostics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an error about the code at `span`, which prevents the file from being translated.
+    pub fn error(&mut self, span: Span, report: Report<'static>) {
+        self.diagnostics.push(Diagnostic {
+            span,
+            report,
+            is_error: true,
+        });
+    }
+
+    /// Adds a warning about the code at `span`.
+    pub fn warning(&mut self, span: Span, report: Report<'static>) {
+        self.diagnostics.push(Diagnostic {
+            span,
+            report,
+            is_error: false,
+        });
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic.is_error)
+    }
+
+    /// Adds the diagnostics of a later pass.
+    pub fn extend(&mut self, other: Diagnostics) {
+        self.diagnostics.extend(other.diagnostics);
+    }
+
+    /// Returns the reports in the order of the code they are about.
+    pub fn into_reports(mut self) -> Vec<Report<'static>> {
+        self.diagnostics
+            .sort_by_key(|diagnostic| (diagnostic.span.start, diagnostic.span.end));
+
+        self.diagnostics
+            .into_iter()
+            .map(|diagnostic| diagnostic.report)
+            .collect()
+    }
+}
@@ -1,15 +1,25 @@
 mod block;
+mod class;
 mod display;
 mod expr;
 mod file;
 mod r#fn;
+mod r#for;
+mod r#if;
 mod r#let;
+mod pattern;
 mod stmt;
+mod r#while;
 
 pub use block::Block;
-pub use display::Display;
-pub use expr::Expr;
+pub use class::{Class, Method};
+pub use display::{is_identifier, is_identifier_char, Display};
+pub use expr::{Expr, Prop, TemplatePart};
 pub use file::File;
+pub use pattern::Pattern;
 pub use r#fn::Fn;
+pub use r#for::{For, ForOf};
+pub use r#if::{Else, If};
 pub use r#let::Let;
+pub use r#while::While;
 pub use stmt::Stmt;
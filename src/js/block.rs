@@ -2,7 +2,7 @@ use std::fmt;
 
 use crate::js::{self, Stmt};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Block<'a> {
     pub stmts: Vec<Stmt<'a>>,
 }
@@ -0,0 +1,60 @@
+use std::borrow::Cow;
+use std::fmt;
+
+use crate::js::display::PropName;
+use crate::js::{Block, Display, Pattern};
+
+#[derive(Debug, Clone)]
+pub struct Class<'a> {
+    pub name: Cow<'a, str>,
+    pub methods: Vec<Method<'a>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Method<'a> {
+    pub name: Cow<'a, str>,
+    pub args: Vec<Pattern<'a>>,
+    pub body: Block<'a>,
+    pub is_static: bool,
+}
+
+impl fmt::Display for Display<'_, Class<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_indent(f)?;
+        writeln!(f, "class {}", self.value.name)?;
+
+        self.write_indent(f)?;
+        writeln!(f, "{{")?;
+
+        for method in &self.value.methods {
+            self.with_indented(method).fmt(f)?;
+        }
+
+        self.write_indent(f)?;
+        writeln!(f, "}}")
+    }
+}
+
+impl fmt::Display for Display<'_, Method<'_>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_indent(f)?;
+
+        if self.value.is_static {
+            write!(f, "static ")?;
+        }
+
+        write!(f, "{}(", PropName(&self.value.name))?;
+
+        for (i, arg) in self.value.args.iter().enumerate() {
+            if i + 1 == self.value.args.len() {
+                write!(f, "{}", self.with(arg))?;
+            } else {
+                write!(f, "{}, ", self.with(arg))?;
+            }
+        }
+
+        writeln!(f, ")")?;
+
+        self.with(&self.value.body).fmt(f)
+    }
+}
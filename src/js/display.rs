@@ -1,6 +1,8 @@
 use std::fmt;
 
-use crate::js::Expr;
+use unicode_ident::{is_xid_continue, is_xid_start};
+
+use crate::js::{Expr, Prop, Stmt, TemplatePart};
 
 pub struct Display<'a, T: 'a> {
     pub(crate) indent: usize,
@@ -36,21 +38,229 @@ impl<'a, T: 'a> Display<'a, T> {
 }
 
 impl<'a> fmt::Display for Display<'a, Expr<'a>> {
+    // Expressions don't write their own indentation, since they are part of a line.
+    // The indentation is only passed on to nested blocks, like the bodies of arrow functions.
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        self.write_indent(f)?;
+        let precedence = self.value.precedence();
+        // Operands binding weaker than their operator need parentheses
+        let operand = |expr: &'a Expr<'a>| Operand(self.with(expr), precedence);
+        // Operators are left-associative, unless noted otherwise
+        let rhs_operand = |expr: &'a Expr<'a>| Operand(self.with(expr), precedence + 1);
 
         match &self.value {
             Expr::Number(n) => write!(f, "{n}"),
-            Expr::Parens(expr) => write!(f, "({expr})"),
+            Expr::BigInt(n) => write!(f, "{n}n"),
+            Expr::Bool(b) => write!(f, "{b}"),
+            Expr::String(s) => write!(f, "\"{}\"", escape(s, '"')),
+            Expr::Template(parts) => {
+                write!(f, "`")?;
+
+                for part in parts {
+                    match part {
+                        TemplatePart::Str(s) => write!(f, "{}", escape(s, '`'))?,
+                        TemplatePart::Expr(expr) => write!(f, "${{{}}}", self.with(expr))?,
+                    }
+                }
+
+                write!(f, "`")
+            }
+            Expr::Parens(expr) => write!(f, "({})", self.with(&**expr)),
             Expr::Var(name) => write!(f, "{name}"),
             Expr::Neg(expr) => match **expr {
-                Expr::Neg(_) => write!(f, "-({expr})"),
-                _ => write!(f, "-{expr}"),
+                Expr::Neg(_) => write!(f, "-({})", self.with(&**expr)),
+                _ => write!(f, "-{}", operand(expr)),
             },
-            Expr::Mul(lhs, rhs) => write!(f, "{lhs} * {rhs}"),
-            Expr::Div(lhs, rhs) => write!(f, "{lhs} / {rhs}"),
-            Expr::Add(lhs, rhs) => write!(f, "{lhs} + {rhs}"),
-            Expr::Sub(lhs, rhs) => write!(f, "{lhs} - {rhs}"),
+            Expr::Mul(lhs, rhs) => write!(f, "{} * {}", operand(lhs), rhs_operand(rhs)),
+            Expr::Div(lhs, rhs) => write!(f, "{} / {}", operand(lhs), rhs_operand(rhs)),
+            Expr::Add(lhs, rhs) => write!(f, "{} + {}", operand(lhs), rhs_operand(rhs)),
+            Expr::Sub(lhs, rhs) => write!(f, "{} - {}", operand(lhs), rhs_operand(rhs)),
+            Expr::StrictEq(lhs, rhs) => write!(f, "{} === {}", operand(lhs), rhs_operand(rhs)),
+            Expr::StrictNe(lhs, rhs) => write!(f, "{} !== {}", operand(lhs), rhs_operand(rhs)),
+            Expr::Lt(lhs, rhs) => write!(f, "{} < {}", operand(lhs), rhs_operand(rhs)),
+            Expr::Le(lhs, rhs) => write!(f, "{} <= {}", operand(lhs), rhs_operand(rhs)),
+            Expr::Gt(lhs, rhs) => write!(f, "{} > {}", operand(lhs), rhs_operand(rhs)),
+            Expr::Ge(lhs, rhs) => write!(f, "{} >= {}", operand(lhs), rhs_operand(rhs)),
+            Expr::Not(expr) => write!(f, "!{}", operand(expr)),
+            Expr::TypeOf(expr) => write!(f, "typeof {}", operand(expr)),
+            Expr::BitOr(lhs, rhs) => write!(f, "{} | {}", operand(lhs), rhs_operand(rhs)),
+            Expr::And(lhs, rhs) => write!(f, "{} && {}", operand(lhs), rhs_operand(rhs)),
+            Expr::Or(lhs, rhs) => write!(f, "{} || {}", operand(lhs), rhs_operand(rhs)),
+            // Right-associative
+            Expr::Ternary(cond, then, r#else) => write!(
+                f,
+                "{} ? {} : {}",
+                rhs_operand(cond),
+                operand(then),
+                operand(r#else),
+            ),
+            // Assignments are right-associative
+            Expr::Assign(lhs, rhs) => write!(f, "{} = {}", rhs_operand(lhs), operand(rhs)),
+            Expr::AddAssign(lhs, rhs) => write!(f, "{} += {}", rhs_operand(lhs), operand(rhs)),
+            Expr::SubAssign(lhs, rhs) => write!(f, "{} -= {}", rhs_operand(lhs), operand(rhs)),
+            Expr::MulAssign(lhs, rhs) => write!(f, "{} *= {}", rhs_operand(lhs), operand(rhs)),
+            Expr::DivAssign(lhs, rhs) => write!(f, "{} /= {}", rhs_operand(lhs), operand(rhs)),
+            Expr::New(callee, args) => {
+                write!(f, "new {}(", operand(callee))?;
+
+                for (i, arg) in args.iter().enumerate() {
+                    if i + 1 == args.len() {
+                        write!(f, "{}", self.with(arg))?;
+                    } else {
+                        write!(f, "{}, ", self.with(arg))?;
+                    }
+                }
+
+                write!(f, ")")
+            }
+            Expr::Member(expr, name) if is_identifier(name) => {
+                write!(f, "{}.{name}", operand(expr))
+            }
+            Expr::Member(expr, name) => write!(f, "{}[{}]", operand(expr), PropName(name)),
+            Expr::Index(expr, index) => write!(f, "{}[{}]", operand(expr), self.with(&**index)),
+            Expr::Array(elements) => {
+                write!(f, "[")?;
+
+                for (i, element) in elements.iter().enumerate() {
+                    if i + 1 == elements.len() {
+                        write!(f, "{}", self.with(element))?;
+                    } else {
+                        write!(f, "{}, ", self.with(element))?;
+                    }
+                }
+
+                write!(f, "]")
+            }
+            Expr::Object(props) if props.is_empty() => write!(f, "{{}}"),
+            Expr::Object(props) => {
+                write!(f, "{{ ")?;
+
+                for (i, prop) in props.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+
+                    // Values can't be comma expressions, which bind weaker than assignments
+                    match prop {
+                        Prop::KeyValue(key, value) => {
+                            write!(f, "{}: {}", PropName(key), Operand(self.with(value), 2))?
+                        }
+                        Prop::Spread(expr) => write!(f, "...{}", Operand(self.with(expr), 2))?,
+                    }
+                }
+
+                write!(f, " }}")
+            }
+            Expr::Call(callee, args) => {
+                write!(f, "{}(", operand(callee))?;
+
+                for (i, arg) in args.iter().enumerate() {
+                    if i + 1 == args.len() {
+                        write!(f, "{}", self.with(arg))?;
+                    } else {
+                        write!(f, "{}, ", self.with(arg))?;
+                    }
+                }
+
+                write!(f, ")")
+            }
+            Expr::Arrow(args, body) => {
+                write!(f, "(")?;
+
+                for (i, arg) in args.iter().enumerate() {
+                    if i + 1 == args.len() {
+                        write!(f, "{}", self.with(arg))?;
+                    } else {
+                        write!(f, "{}, ", self.with(arg))?;
+                    }
+                }
+
+                match body.stmts.as_slice() {
+                    // `(x) => { return y; }` can be written as `(x) => y`
+                    [Stmt::Return(Some(value))] if value.starts_with_object() => {
+                        write!(f, ") => ({})", self.with(value))
+                    }
+                    [Stmt::Return(Some(value))] => write!(f, ") => {}", operand(value)),
+                    _ => {
+                        writeln!(f, ") =>")?;
+                        // Blocks end with a newline, which has to be undone for the rest of the line
+                        let body = self.with(body).to_string();
+                        write!(f, "{}", body.trim_end())
+                    }
+                }
+            }
+            Expr::Undefined => write!(f, "undefined"),
+        }
+    }
+}
+
+/// Whether `name` can be written as a JS identifier.
+pub fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+
+    chars.next().is_some_and(|c| is_identifier_char(c, true))
+        && chars.all(|c| is_identifier_char(c, false))
+}
+
+/// Whether JS accepts `c` in an identifier, at its start if `is_start`.
+/// JS allows the `ID_Start` and `ID_Continue` characters, which include the `XID` ones checked here.
+pub fn is_identifier_char(c: char, is_start: bool) -> bool {
+    match c {
+        '$' | '_' => true,
+        c if is_start => is_xid_start(c),
+        c => is_xid_continue(c),
+    }
+}
+
+/// The name of a property in an object, class or pattern, which is quoted if it isn't an identifier.
+pub(crate) struct PropName<'a>(pub &'a str);
+
+impl fmt::Display for PropName<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if is_identifier(self.0) {
+            write!(f, "{}", self.0)
+        } else {
+            write!(f, "\"{}\"", escape(self.0, '"'))
+        }
+    }
+}
+
+/// Escapes the text of a JS string literal delimited by `quote`, which is `` ` `` for templates.
+/// Control characters are escaped too, so that the literal stays on one line.
+fn escape(s: &str, quote: char) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    let mut chars = s.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c == quote => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            // `${` would start a substitution in templates
+            '$' if quote == '`' && chars.peek() == Some(&'{') => escaped.push_str("\\$"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{{{:x}}}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+/// An operand that gets parenthesized if it binds weaker than the given precedence.
+struct Operand<'a>(Display<'a, Expr<'a>>, u8);
+
+impl fmt::Display for Operand<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Operand(expr, precedence) = self;
+
+        if expr.value.precedence() < *precedence {
+            write!(f, "({expr})")
+        } else {
+            write!(f, "{expr}")
         }
     }
 }
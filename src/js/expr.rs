@@ -1,11 +1,17 @@
 use std::borrow::Cow;
 use std::fmt;
 
-use crate::js;
+use crate::js::{self, Block};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Expr<'a> {
     Number(f64),
+    /// A `BigInt` literal like `123n`
+    BigInt(u64),
+    Bool(bool),
+    String(Cow<'a, str>),
+    /// A template literal like `` `x = ${x}` ``
+    Template(Vec<TemplatePart<'a>>),
     Parens(Box<Expr<'a>>),
     Var(Cow<'a, str>),
     Neg(Box<Expr<'a>>),
@@ -13,12 +19,124 @@ pub enum Expr<'a> {
     Div(Box<Expr<'a>>, Box<Expr<'a>>),
     Add(Box<Expr<'a>>, Box<Expr<'a>>),
     Sub(Box<Expr<'a>>, Box<Expr<'a>>),
+    StrictEq(Box<Expr<'a>>, Box<Expr<'a>>),
+    StrictNe(Box<Expr<'a>>, Box<Expr<'a>>),
+    Lt(Box<Expr<'a>>, Box<Expr<'a>>),
+    Le(Box<Expr<'a>>, Box<Expr<'a>>),
+    Gt(Box<Expr<'a>>, Box<Expr<'a>>),
+    Ge(Box<Expr<'a>>, Box<Expr<'a>>),
+    Not(Box<Expr<'a>>),
+    /// `typeof expr`
+    TypeOf(Box<Expr<'a>>),
+    BitOr(Box<Expr<'a>>, Box<Expr<'a>>),
+    And(Box<Expr<'a>>, Box<Expr<'a>>),
+    Or(Box<Expr<'a>>, Box<Expr<'a>>),
+    Ternary(Box<Expr<'a>>, Box<Expr<'a>>, Box<Expr<'a>>),
+    Assign(Box<Expr<'a>>, Box<Expr<'a>>),
+    AddAssign(Box<Expr<'a>>, Box<Expr<'a>>),
+    SubAssign(Box<Expr<'a>>, Box<Expr<'a>>),
+    MulAssign(Box<Expr<'a>>, Box<Expr<'a>>),
+    DivAssign(Box<Expr<'a>>, Box<Expr<'a>>),
+    Call(Box<Expr<'a>>, Vec<Expr<'a>>),
+    New(Box<Expr<'a>>, Vec<Expr<'a>>),
+    Member(Box<Expr<'a>>, Cow<'a, str>),
+    /// `array[index]`
+    Index(Box<Expr<'a>>, Box<Expr<'a>>),
+    Array(Vec<Expr<'a>>),
+    Object(Vec<Prop<'a>>),
+    /// An arrow function with its arguments and body
+    Arrow(Vec<js::Pattern<'a>>, Block<'a>),
+    Undefined,
+}
+
+/// A part of a template literal
+#[derive(Debug, Clone)]
+pub enum TemplatePart<'a> {
+    Str(Cow<'a, str>),
+    /// `${expr}`
+    Expr(Expr<'a>),
+}
+
+/// A property of an object literal
+#[derive(Debug, Clone)]
+pub enum Prop<'a> {
+    KeyValue(Cow<'a, str>, Expr<'a>),
+    /// `...expr`
+    Spread(Expr<'a>),
 }
 
 impl Expr<'_> {
     pub fn boxed(self) -> Box<Self> {
         Box::new(self)
     }
+
+    /// Whether the expression starts with an object literal,
+    /// which would be mistaken for a block at the start of a statement or arrow function body.
+    pub fn starts_with_object(&self) -> bool {
+        match self {
+            Expr::Object(_) => true,
+            Expr::Mul(lhs, _)
+            | Expr::Div(lhs, _)
+            | Expr::Add(lhs, _)
+            | Expr::Sub(lhs, _)
+            | Expr::StrictEq(lhs, _)
+            | Expr::StrictNe(lhs, _)
+            | Expr::Lt(lhs, _)
+            | Expr::Le(lhs, _)
+            | Expr::Gt(lhs, _)
+            | Expr::Ge(lhs, _)
+            | Expr::BitOr(lhs, _)
+            | Expr::And(lhs, _)
+            | Expr::Or(lhs, _)
+            | Expr::Ternary(lhs, _, _)
+            | Expr::Assign(lhs, _)
+            | Expr::AddAssign(lhs, _)
+            | Expr::SubAssign(lhs, _)
+            | Expr::MulAssign(lhs, _)
+            | Expr::DivAssign(lhs, _)
+            | Expr::Call(lhs, _)
+            | Expr::Member(lhs, _)
+            | Expr::Index(lhs, _) => lhs.starts_with_object(),
+            _ => false,
+        }
+    }
+
+    /// The precedence of the expression's operator.
+    ///
+    /// See <https://developer.mozilla.org/en-US/docs/Web/JavaScript/Reference/Operators/Operator_precedence>
+    pub fn precedence(&self) -> u8 {
+        match self {
+            Expr::Number(_)
+            | Expr::BigInt(_)
+            | Expr::Bool(_)
+            | Expr::String(_)
+            | Expr::Template(_)
+            | Expr::Parens(_)
+            | Expr::Var(_)
+            | Expr::Call(_, _)
+            | Expr::New(_, _)
+            | Expr::Member(_, _)
+            | Expr::Index(_, _)
+            | Expr::Array(_)
+            | Expr::Object(_)
+            | Expr::Undefined => 17,
+            Expr::Neg(_) | Expr::Not(_) | Expr::TypeOf(_) => 14,
+            Expr::Mul(_, _) | Expr::Div(_, _) => 12,
+            Expr::Add(_, _) | Expr::Sub(_, _) => 11,
+            Expr::Lt(_, _) | Expr::Le(_, _) | Expr::Gt(_, _) | Expr::Ge(_, _) => 9,
+            Expr::StrictEq(_, _) | Expr::StrictNe(_, _) => 8,
+            Expr::BitOr(_, _) => 5,
+            Expr::And(_, _) => 4,
+            Expr::Or(_, _) => 3,
+            Expr::Ternary(_, _, _)
+            | Expr::Assign(_, _)
+            | Expr::AddAssign(_, _)
+            | Expr::SubAssign(_, _)
+            | Expr::MulAssign(_, _)
+            | Expr::DivAssign(_, _)
+            | Expr::Arrow(_, _) => 2,
+        }
+    }
 }
 
 impl fmt::Display for Expr<'_> {
@@ -2,7 +2,7 @@ use std::fmt;
 
 use crate::js::{Display, Stmt};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct File<'a> {
     pub stmts: Vec<Stmt<'a>>,
 }
@@ -1,11 +1,12 @@
+use std::borrow::Cow;
 use std::fmt::{self, Debug};
 
-use crate::js::{Block, Display};
+use crate::js::{Block, Display, Pattern};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Fn<'a> {
-    pub name: &'a str,
-    pub args: Vec<&'a str>,
+    pub name: Cow<'a, str>,
+    pub args: Vec<Pattern<'a>>,
     pub body: Block<'a>,
 }
 
@@ -17,9 +18,9 @@ impl fmt::Display for Display<'_, Fn<'_>> {
 
         for (i, arg) in self.value.args.iter().enumerate() {
             if i + 1 == self.value.args.len() {
-                write!(f, "{}", arg)?;
+                write!(f, "{}", self.with(arg))?;
             } else {
-                write!(f, "{}, ", arg)?;
+                write!(f, "{}, ", self.with(arg))?;
             }
         }
 
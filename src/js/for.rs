@@ -0,0 +1,73 @@
+use std::borrow::Cow;
+use std::fmt;
+
+use crate::js::{Block, Display, Expr};
+
+/// A counted loop like `for (let i = start; i < end; i++)`
+#[derive(Debug, Clone)]
+pub struct For<'a> {
+    pub label: Option<Cow<'a, str>>,
+    pub var: Cow<'a, str>,
+    pub start: Box<Expr<'a>>,
+    pub end: Box<Expr<'a>>,
+    /// Compares with `<=` instead of `<`
+    pub inclusive: bool,
+    pub body: Block<'a>,
+}
+
+/// A loop over the elements of an iterable, like `for (const x of xs)`
+#[derive(Debug, Clone)]
+pub struct ForOf<'a> {
+    pub label: Option<Cow<'a, str>>,
+    pub var: Cow<'a, str>,
+    pub iter: Box<Expr<'a>>,
+    pub body: Block<'a>,
+}
+
+impl<'a> fmt::Display for Display<'a, For<'a>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let For {
+            label,
+            var,
+            start,
+            end,
+            inclusive,
+            body,
+        } = self.value;
+        let op = if *inclusive { "<=" } else { "<" };
+
+        self.write_indent(f)?;
+
+        if let Some(label) = label {
+            write!(f, "{label}: ")?;
+        }
+
+        writeln!(
+            f,
+            "for (let {var} = {}; {var} {op} {}; {var}++)",
+            self.with(&**start),
+            self.with(&**end),
+        )?;
+        self.with(body).fmt(f)
+    }
+}
+
+impl<'a> fmt::Display for Display<'a, ForOf<'a>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ForOf {
+            label,
+            var,
+            iter,
+            body,
+        } = self.value;
+
+        self.write_indent(f)?;
+
+        if let Some(label) = label {
+            write!(f, "{label}: ")?;
+        }
+
+        writeln!(f, "for (const {var} of {})", self.with(&**iter))?;
+        self.with(body).fmt(f)
+    }
+}
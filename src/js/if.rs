@@ -0,0 +1,48 @@
+use std::fmt;
+
+use crate::js::{Block, Display, Expr};
+
+#[derive(Debug, Clone)]
+pub struct If<'a> {
+    pub cond: Box<Expr<'a>>,
+    pub then: Block<'a>,
+    pub r#else: Option<Else<'a>>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Else<'a> {
+    If(Box<If<'a>>),
+    Block(Block<'a>),
+}
+
+impl<'a> fmt::Display for Display<'a, If<'a>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut r#if = self.value;
+
+        self.write_indent(f)?;
+
+        // Render `else if` chains flat instead of nesting them
+        loop {
+            let If { cond, then, r#else } = r#if;
+
+            writeln!(f, "if ({})", self.with(&**cond))?;
+            self.with(then).fmt(f)?;
+
+            match r#else {
+                Some(Else::If(else_if)) => {
+                    self.write_indent(f)?;
+                    write!(f, "else ")?;
+
+                    r#if = else_if;
+                }
+                Some(Else::Block(r#else)) => {
+                    self.write_indent(f)?;
+                    writeln!(f, "else")?;
+
+                    return self.with(r#else).fmt(f);
+                }
+                None => return Ok(()),
+            }
+        }
+    }
+}
@@ -1,20 +1,30 @@
-use std::borrow::Cow;
 use std::fmt;
 
-use crate::js::{Display, Expr};
+use crate::js::{Display, Expr, Pattern};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Let<'a> {
-    pub name: Cow<'a, str>,
-    pub rhs: Box<Expr<'a>>,
+    pub pattern: Pattern<'a>,
+    /// Declares the variable with `let` instead of `const`
+    pub mutable: bool,
+    pub rhs: Option<Box<Expr<'a>>>,
 }
 
 impl<'a> fmt::Display for Display<'a, Let<'a>> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         self.write_indent(f)?;
 
-        let Let { name, rhs } = self.value;
+        let Let {
+            pattern,
+            mutable,
+            rhs,
+        } = self.value;
+        let pattern = self.with(pattern);
+        let keyword = if *mutable { "let" } else { "const" };
 
-        writeln!(f, "let {name} = {rhs};")
+        match rhs {
+            Some(rhs) => writeln!(f, "{keyword} {pattern} = {};", self.with(&**rhs)),
+            None => writeln!(f, "{keyword} {pattern};"),
+        }
     }
 }
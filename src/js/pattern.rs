@@ -0,0 +1,56 @@
+use std::borrow::Cow;
+use std::fmt;
+
+use crate::js::display::PropName;
+use crate::js::Display;
+
+/// What a declaration or argument binds, which can destructure arrays and objects
+#[derive(Debug, Clone)]
+pub enum Pattern<'a> {
+    Var(Cow<'a, str>),
+    /// `[a, , b]`, where `None` skips an element
+    Array(Vec<Option<Pattern<'a>>>),
+    /// `{ x, y: b }`
+    Object(Vec<(Cow<'a, str>, Pattern<'a>)>),
+}
+
+impl<'a> fmt::Display for Display<'a, Pattern<'a>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.value {
+            Pattern::Var(name) => write!(f, "{name}"),
+            Pattern::Array(elements) => {
+                write!(f, "[")?;
+
+                for (i, element) in elements.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+
+                    if let Some(element) = element {
+                        write!(f, "{}", self.with(element))?;
+                    }
+                }
+
+                write!(f, "]")
+            }
+            Pattern::Object(props) if props.is_empty() => write!(f, "{{}}"),
+            Pattern::Object(props) => {
+                write!(f, "{{ ")?;
+
+                for (i, (key, value)) in props.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+
+                    match value {
+                        // `{ x }` is short for `{ x: x }`
+                        Pattern::Var(name) if name == key => write!(f, "{key}")?,
+                        value => write!(f, "{}: {}", PropName(key), self.with(value))?,
+                    }
+                }
+
+                write!(f, " }}")
+            }
+        }
+    }
+}
@@ -1,14 +1,27 @@
+use std::borrow::Cow;
 use std::fmt;
 
-use crate::js::{self, Block, Expr, Fn, Let};
+use crate::js::{self, Block, Class, Expr, Fn, For, ForOf, If, Let, While};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Stmt<'a> {
     Fn(Fn<'a>),
+    Class(Class<'a>),
     Let(Let<'a>),
-    // TODO: add `Option<Semi>` or similar
     Expr(Expr<'a>),
     Block(Block<'a>),
+    /// A block that can be left with `break label;`
+    Labeled(Cow<'a, str>, Block<'a>),
+    If(If<'a>),
+    While(While<'a>),
+    For(For<'a>),
+    ForOf(ForOf<'a>),
+    /// `break`, optionally with the label of the loop to break out of
+    Break(Option<Cow<'a, str>>),
+    /// `continue`, optionally with the label of the loop to continue
+    Continue(Option<Cow<'a, str>>),
+    Return(Option<Expr<'a>>),
+    Throw(Expr<'a>),
 }
 
 impl fmt::Display for Stmt<'_> {
@@ -21,9 +34,54 @@ impl<'a> fmt::Display for js::Display<'a, Stmt<'a>> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match &self.value {
             Stmt::Fn(r#fn) => self.with(r#fn).fmt(f),
+            Stmt::Class(class) => self.with(class).fmt(f),
             Stmt::Let(let_stmt) => self.with(let_stmt).fmt(f),
-            Stmt::Expr(expr) => writeln!(f, "{}", self.with(expr)),
+            Stmt::Expr(expr) if expr.starts_with_object() => {
+                self.write_indent(f)?;
+                writeln!(f, "({});", self.with(expr))
+            }
+            Stmt::Expr(expr) => {
+                self.write_indent(f)?;
+                writeln!(f, "{};", self.with(expr))
+            }
             Stmt::Block(block) => self.with(block).fmt(f),
+            Stmt::Labeled(label, block) => {
+                self.write_indent(f)?;
+                writeln!(f, "{label}:")?;
+                self.with(block).fmt(f)
+            }
+            Stmt::If(r#if) => self.with(r#if).fmt(f),
+            Stmt::While(r#while) => self.with(r#while).fmt(f),
+            Stmt::For(r#for) => self.with(r#for).fmt(f),
+            Stmt::ForOf(for_of) => self.with(for_of).fmt(f),
+            Stmt::Break(label) => {
+                self.write_indent(f)?;
+
+                match label {
+                    Some(label) => writeln!(f, "break {label};"),
+                    None => writeln!(f, "break;"),
+                }
+            }
+            Stmt::Continue(label) => {
+                self.write_indent(f)?;
+
+                match label {
+                    Some(label) => writeln!(f, "continue {label};"),
+                    None => writeln!(f, "continue;"),
+                }
+            }
+            Stmt::Return(value) => {
+                self.write_indent(f)?;
+
+                match value {
+                    Some(value) => writeln!(f, "return {};", self.with(value)),
+                    None => writeln!(f, "return;"),
+                }
+            }
+            Stmt::Throw(value) => {
+                self.write_indent(f)?;
+                writeln!(f, "throw {};", self.with(value))
+            }
         }
     }
 }
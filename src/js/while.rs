@@ -0,0 +1,26 @@
+use std::borrow::Cow;
+use std::fmt;
+
+use crate::js::{Block, Display, Expr};
+
+#[derive(Debug, Clone)]
+pub struct While<'a> {
+    pub label: Option<Cow<'a, str>>,
+    pub cond: Box<Expr<'a>>,
+    pub body: Block<'a>,
+}
+
+impl<'a> fmt::Display for Display<'a, While<'a>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let While { label, cond, body } = self.value;
+
+        self.write_indent(f)?;
+
+        if let Some(label) = label {
+            write!(f, "{label}: ")?;
+        }
+
+        writeln!(f, "while ({})", self.with(&**cond))?;
+        self.with(body).fmt(f)
+    }
+}
@@ -1,7 +1,10 @@
 #![allow(clippy::result_large_err)]
 
+pub mod diagnostics;
 pub mod js;
 pub mod parser;
+pub mod prelude;
 pub mod report;
 pub mod syntax;
 pub mod trans;
+pub mod typeck;
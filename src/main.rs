@@ -3,37 +3,58 @@ use std::{env, fs};
 use ariadne::Source;
 use chumsky::Parser;
 
+use jester_script::diagnostics::Diagnostics;
 use jester_script::js::Display;
-use jester_script::syntax::Span;
 use jester_script::trans::Trans;
+use jester_script::typeck::Typeck;
 use jester_script::{parser, report};
 
 fn main() {
-    let path = env::args().nth(1).unwrap();
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    let bounds_checks = args.iter().any(|arg| arg == "--bounds-checks");
+    let path = args.iter().find(|arg| !arg.starts_with("--")).unwrap();
     let input = fs::read_to_string(path).unwrap();
     let source = Source::from(&input);
 
-    let file = match parser::file().parse(&input).into_result() {
-        Ok(file) => file,
-        Err(errs) => {
-            for err in errs {
-                let report = report::parse_err(err);
-                report.print(source.clone());
-            }
-            return;
-        }
-    };
+    // The reports of all the passes are printed together, in the order of the code
+    let mut diagnostics = Diagnostics::new();
+    let js = compile(&input, bounds_checks, &mut diagnostics);
+    let has_errors = diagnostics.has_errors();
+
+    for report in diagnostics.into_reports() {
+        report.print(source.clone()).unwrap();
+    }
+
+    if let Some(js) = js.filter(|_| !has_errors) {
+        println!("{}", js);
+    }
+}
+
+/// Compiles the input to JS, which is only valid if there are no errors.
+fn compile(input: &str, bounds_checks: bool, diagnostics: &mut Diagnostics) -> Option<String> {
+    // The parser recovers from errors, so the rest of the file can still be checked
+    let (file, errs) = parser::file().parse(input).into_output_errors();
+
+    for err in errs {
+        diagnostics.error(*err.span(), report::parse_err(err));
+    }
+
+    let file = file?;
 
     println!("{:#?}", file);
 
-    let js = match Trans::new(Span::new(0, input.len())).trans_file(&file) {
-        Ok(js) => js,
-        Err(report) => {
-            report.print(source).unwrap();
-            return;
-        }
-    };
+    let (types, typeck_diagnostics) = Typeck::new().check_file(&file);
+    diagnostics.extend(typeck_diagnostics);
+
+    // The types of the expressions that failed to parse or check are missing
+    if diagnostics.has_errors() {
+        return None;
+    }
+
+    let js = Trans::new(types)
+        .with_bounds_checks(bounds_checks)
+        .trans_file(&file);
 
-    println!("{}", Display::new(&js));
     // println!("{:#?}", js);
+    Some(Display::new(&js).to_string())
 }
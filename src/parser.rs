@@ -1,81 +1,778 @@
+use std::borrow::Cow;
+use std::iter;
+
 use chumsky::container::OrderedSeq;
-use chumsky::extra::Err;
+use chumsky::error::Error;
+use chumsky::extra::Full;
 use chumsky::prelude::*;
 use chumsky::Parser as _;
+use unicode_ident::{is_xid_continue, is_xid_start};
+use unicode_normalization::{is_nfc, UnicodeNormalization};
 
+use crate::syntax::closure;
+use crate::syntax::format::FormatPart;
+use crate::syntax::pattern;
+use crate::syntax::pattern::PatternKind;
+use crate::syntax::r#enum;
 use crate::syntax::r#fn;
+use crate::syntax::r#match;
+use crate::syntax::r#struct;
+use crate::syntax::r#type::TypeKind;
 use crate::syntax::Block;
+use crate::syntax::Closure;
+use crate::syntax::Enum;
 use crate::syntax::Expr;
+use crate::syntax::ExprKind;
 use crate::syntax::File;
 use crate::syntax::Fn;
 use crate::syntax::Ident;
+use crate::syntax::Impl;
 use crate::syntax::Item;
 use crate::syntax::Let;
+use crate::syntax::Path;
+use crate::syntax::Pattern;
+use crate::syntax::Span;
 use crate::syntax::Stmt;
+use crate::syntax::Struct;
+use crate::syntax::Type;
+
+type Extra<'a> = Full<Rich<'a, char>, (), Context>;
 
-type Extra<'a> = Err<Rich<'a, char>>;
+#[derive(Clone, Copy, Default)]
+pub struct Context {
+    /// Set in conditions, where `if x { … }` would otherwise parse as a struct literal `x { … }`.
+    /// Parentheses and blocks allow struct literals again.
+    no_struct_literals: bool,
+}
 
-pub trait Parser<'a, O>: chumsky::Parser<'a, &'a str, O, Extra<'a>> + Clone {}
+pub trait Parser<'a, O>: chumsky::Parser<'a, &'a str, O, Extra<'a>> + Clone + 'a {}
 
-impl<'a, O, T> Parser<'a, O> for T where T: chumsky::Parser<'a, &'a str, O, Extra<'a>> + Clone {}
+impl<'a, O, T> Parser<'a, O> for T where T: chumsky::Parser<'a, &'a str, O, Extra<'a>> + Clone + 'a {}
 
 pub fn file<'a>() -> impl Parser<'a, File<'a>> {
-    item()
-        .repeated()
-        .collect::<Vec<_>>()
+    // After an error, the rest of the item is skipped until another item starts.
+    // `fn` also starts function types like `fn(i32)`, which can't be followed by a name.
+    let item_start = choice((
+        keyword("fn")
+            .then(whitespace())
+            .then(ident_chars())
+            .ignored(),
+        keyword("struct"),
+        keyword("enum"),
+        keyword("impl"),
+        just("///").ignored(),
+    ));
+    let recovery = skip_word_or_char()
+        .then(item_start.not().ignore_then(skip_word_or_char()).repeated())
+        .map(|_| None);
+    let item = item().map(Some).recover_with(via_parser(recovery));
+
+    doc_comments("//!")
+        .then(item.repeated().collect::<Vec<_>>())
+        .then_ignore(whitespace())
         .then_ignore(end())
-        .map(|items| File { items })
+        .map(|(doc, items)| File {
+            doc,
+            items: items.into_iter().flatten().collect(),
+        })
 }
 
 fn item<'a>() -> impl Parser<'a, Item<'a>> {
-    r#fn().map(Item::Fn)
+    choice((
+        r#fn().map(Item::Fn),
+        r#struct().map(Item::Struct),
+        r#enum().map(Item::Enum),
+        r#impl().map(Item::Impl),
+    ))
 }
 
 fn r#fn<'a>() -> impl Parser<'a, Fn<'a>> {
     let name = ident();
-    let arg = ident()
+    let arg = pattern()
         .then_ignore(token(':'))
         .then(r#type())
-        .map(|(name, r#type)| r#fn::Arg { name, r#type });
-    let args = arg
-        .separated_by(token(','))
-        .allow_trailing()
-        .collect::<Vec<_>>();
+        .map(|(pattern, r#type)| r#fn::Arg { pattern, r#type });
+    let receiver = keyword("mut")
+        .padded_by(whitespace())
+        .or_not()
+        .then(self_ident())
+        .then_ignore(choice((token(','), just(')').rewind().ignored())))
+        .map(|(r#mut, name)| r#fn::Receiver {
+            name,
+            mutable: r#mut.is_some(),
+        });
+    let args = receiver.or_not().then(
+        arg.separated_by(token(','))
+            .allow_trailing()
+            .collect::<Vec<_>>(),
+    );
     let return_value = token("->").ignore_then(r#type()).or_not().labelled("->");
     let body = block(expr());
 
-    token("fn")
-        .labelled("fn")
-        .ignore_then(name)
+    doc()
+        .then_ignore(token("fn").labelled("fn"))
+        .then(name)
+        .then(type_params())
         .then(args.delimited_by(token('('), token(')')))
         .then(return_value)
         .then(body)
-        .map(|(((name, args), return_type), body)| Fn {
+        .map(
+            |(((((doc, name), type_params), (receiver, args)), return_type), body)| Fn {
+                doc,
+                name,
+                type_params,
+                receiver,
+                args,
+                return_type,
+                body,
+            },
+        )
+}
+
+fn r#impl<'a>() -> impl Parser<'a, Impl<'a>> {
+    let type_args = r#type()
+        .separated_by(token(','))
+        .allow_trailing()
+        .collect::<Vec<_>>()
+        .delimited_by(token('<'), token('>'))
+        .or_not()
+        .map(Option::unwrap_or_default);
+    let fns = r#fn().repeated().collect::<Vec<_>>();
+
+    doc()
+        .then_ignore(keyword("impl").padded_by(whitespace()))
+        .then(type_params())
+        .then(ident().labelled("type"))
+        .then(type_args)
+        .then(fns.delimited_by(token('{'), token('}')))
+        .map(|((((doc, type_params), self_type), type_args), fns)| Impl {
+            doc,
+            type_params,
+            self_type,
+            type_args,
+            fns,
+        })
+}
+
+fn r#struct<'a>() -> impl Parser<'a, Struct<'a>> {
+    let fields = field()
+        .separated_by(token(','))
+        .allow_trailing()
+        .collect::<Vec<_>>();
+
+    doc()
+        .then_ignore(keyword("struct").padded_by(whitespace()))
+        .then(ident())
+        .then(type_params())
+        .then(fields.delimited_by(token('{'), token('}')))
+        .map(|(((doc, name), type_params), fields)| Struct {
+            doc,
+            name,
+            type_params,
+            fields,
+        })
+}
+
+fn r#enum<'a>() -> impl Parser<'a, Enum<'a>> {
+    let struct_fields = field()
+        .separated_by(token(','))
+        .allow_trailing()
+        .collect::<Vec<_>>()
+        .delimited_by(token('{'), token('}'))
+        .map(r#enum::VariantFields::Struct);
+    let tuple_fields = r#type()
+        .separated_by(token(','))
+        .allow_trailing()
+        .collect::<Vec<_>>()
+        .delimited_by(token('('), token(')'))
+        .map(r#enum::VariantFields::Tuple);
+    let fields = choice((struct_fields, tuple_fields)).or_not();
+    let variant = doc()
+        .then(ident())
+        .then(fields)
+        .map(|((doc, name), fields)| r#enum::Variant {
+            doc,
             name,
-            args,
-            return_type,
-            body,
+            fields: fields.unwrap_or(r#enum::VariantFields::Unit),
+        });
+    let variants = variant
+        .separated_by(token(','))
+        .allow_trailing()
+        .collect::<Vec<_>>();
+
+    doc()
+        .then_ignore(keyword("enum").padded_by(whitespace()))
+        .then(ident())
+        .then(type_params())
+        .then(variants.delimited_by(token('{'), token('}')))
+        .map(|(((doc, name), type_params), variants)| Enum {
+            doc,
+            name,
+            type_params,
+            variants,
         })
 }
 
+/// Parses a field declaration of a struct or struct variant, like `x: f64`.
+fn field<'a>() -> impl Parser<'a, r#struct::Field<'a>> {
+    doc()
+        .then(ident())
+        .then_ignore(colon())
+        .then(r#type())
+        .map(|((doc, name), r#type)| r#struct::Field { doc, name, r#type })
+}
+
+/// Parses the type parameters of a generic declaration like `<T, E>`, if any.
+fn type_params<'a>() -> impl Parser<'a, Vec<Ident<'a>>> {
+    ident()
+        .separated_by(token(','))
+        .allow_trailing()
+        .collect::<Vec<_>>()
+        .delimited_by(token('<'), token('>'))
+        .or_not()
+        .map(Option::unwrap_or_default)
+}
+
 fn block<'a>(expr: impl Parser<'a, Expr<'a>>) -> impl Parser<'a, Block<'a>> {
-    let stmts = stmt(expr).repeated().collect::<Vec<_>>();
+    recursive(|block| {
+        let stmts = stray_doc_comments()
+            .ignore_then(stmt(expr.clone(), block_like(expr, block)))
+            .repeated()
+            .collect::<Vec<_>>();
+
+        just('{')
+            .ignore_then(stmts.padded_by(whitespace()).with_ctx(Context::default()))
+            .then_ignore(stray_doc_comments())
+            .then_ignore(just('}'))
+            .map_with(|mut stmts, extra| {
+                // Only the last expression of a block can lack a `;`
+                let expr = match stmts.pop() {
+                    Some((Stmt::Expr(expr), false)) => Some(expr.boxed()),
+                    Some((stmt, _)) => {
+                        stmts.push((stmt, true));
+                        None
+                    }
+                    None => None,
+                };
+                let stmts = stmts.into_iter().map(|(stmt, _)| stmt).collect();
 
-    lbrace()
-        .ignore_then(stmts)
-        .then_ignore(rbrace())
-        .map(|stmts| Block { stmts })
+                Block {
+                    stmts,
+                    expr,
+                    span: extra.span(),
+                }
+            })
+            .recover_with(via_parser(nested_delimiters(
+                '{',
+                '}',
+                [('(', ')'), ('[', ']')],
+                |span| Block {
+                    stmts: Vec::new(),
+                    expr: Some(Expr::new(ExprKind::Error, span).boxed()),
+                    span,
+                },
+            )))
+            .padded_by(whitespace())
+            .boxed()
+    })
 }
 
-fn token<'a, T>(c: T) -> impl Parser<'a, ()>
-where
-    T: OrderedSeq<'a, char> + Clone,
-{
-    just(c).padded().ignored()
+/// Parses expressions ending with a block.
+/// These don't need to be terminated by a `;` when used as statements.
+fn block_like<'a>(
+    expr: impl Parser<'a, Expr<'a>>,
+    block: impl Parser<'a, Block<'a>>,
+) -> impl Parser<'a, Expr<'a>> {
+    let block_expr = block.clone().map(|block| {
+        let span = block.span;
+        Expr::new(ExprKind::Block(block), span)
+    });
+
+    let cond = expr.clone().with_ctx(Context {
+        no_struct_literals: true,
+    });
+
+    let r#if = recursive({
+        let (cond, block) = (cond.clone(), block.clone());
+
+        |r#if| {
+            let r#else = keyword("else")
+                .padded_by(whitespace())
+                .ignore_then(choice((r#if, block_expr.clone())));
+
+            keyword("if")
+                .map_with(|_, extra| extra.span())
+                .padded_by(whitespace())
+                .then(cond)
+                .then(block)
+                .then(r#else.or_not())
+                .map(|(((if_span, cond), then), r#else): (((Span, _), _), _)| {
+                    let end = match &r#else {
+                        Some(r#else) => r#else.span.end,
+                        None => then.span.end,
+                    };
+                    let kind = ExprKind::If {
+                        cond: cond.boxed(),
+                        then,
+                        r#else: r#else.map(Expr::boxed),
+                    };
+
+                    Expr::new(kind, Span::new(if_span.start, end))
+                })
+                .boxed()
+        }
+    });
+
+    let label = label().then_ignore(colon()).or_not();
+
+    let r#loop = label
+        .clone()
+        .then(
+            keyword("loop")
+                .map_with(|_, extra| extra.span())
+                .padded_by(whitespace()),
+        )
+        .then(block.clone())
+        .map(
+            |((label, keyword_span), body): ((Option<Ident>, Span), _)| {
+                let start = label.as_ref().map_or(keyword_span, Ident::span).start;
+                let span = Span::new(start, body.span.end);
+
+                Expr::new(ExprKind::Loop { label, body }, span)
+            },
+        );
+
+    let r#match = keyword("match")
+        .map_with(|_, extra| extra.span())
+        .padded_by(whitespace())
+        .then(cond.clone())
+        .then(match_arms(expr))
+        .then(
+            just('}')
+                .map_with(|_, extra| extra.span())
+                .padded_by(whitespace()),
+        )
+        .map(
+            |(((keyword_span, scrutinee), arms), rbrace_span): (((Span, Expr), _), Span)| {
+                let span = Span::new(keyword_span.start, rbrace_span.end);
+                let kind = ExprKind::Match {
+                    scrutinee: scrutinee.boxed(),
+                    arms,
+                };
+
+                Expr::new(kind, span)
+            },
+        );
+
+    let r#while = label
+        .clone()
+        .then(
+            keyword("while")
+                .map_with(|_, extra| extra.span())
+                .padded_by(whitespace()),
+        )
+        .then(cond.clone())
+        .then(block.clone())
+        .map(
+            |(((label, keyword_span), cond), body): (((Option<Ident>, Span), _), _)| {
+                let start = label.as_ref().map_or(keyword_span, Ident::span).start;
+                let span = Span::new(start, body.span.end);
+                let kind = ExprKind::While {
+                    label,
+                    cond: cond.boxed(),
+                    body,
+                };
+
+                Expr::new(kind, span)
+            },
+        );
+
+    let r#for = label
+        .then(
+            keyword("for")
+                .map_with(|_, extra| extra.span())
+                .padded_by(whitespace()),
+        )
+        .then(
+            ident()
+                .then_ignore(keyword("in").padded_by(whitespace()))
+                .then(cond),
+        )
+        .then(block)
+        .map(
+            |(((label, keyword_span), (var, iter)), body): (((Option<Ident>, Span), _), _)| {
+                let start = label.as_ref().map_or(keyword_span, Ident::span).start;
+                let span = Span::new(start, body.span.end);
+                let kind = ExprKind::For {
+                    label,
+                    var,
+                    iter: iter.boxed(),
+                    body,
+                };
+
+                Expr::new(kind, span)
+            },
+        );
+
+    choice((block_expr, r#if, r#loop, r#while, r#for, r#match)).boxed()
 }
 
-fn lbrace<'a>() -> impl Parser<'a, ()> {
+/// Parses the arms of a `match` after its opening `{`.
+fn match_arms<'a>(expr: impl Parser<'a, Expr<'a>>) -> impl Parser<'a, Vec<r#match::Arm<'a>>> {
+    let expr = expr.with_ctx(Context::default());
+    let guard = keyword("if")
+        .padded_by(whitespace())
+        .ignore_then(expr.clone())
+        .or_not();
+    // Like in Rust, arms need to be separated by `,` unless they end with a block
+    let end_of_arm = choice((
+        token(',').to(true),
+        rbrace().rewind().to(true),
+        empty().to(false),
+    ));
+    let body = expr.then(end_of_arm).try_map(|(body, terminated), span| {
+        if terminated || is_block_like(&body) {
+            Ok(body)
+        } else {
+            Err(Rich::custom(span, "expected `,` after match arm"))
+        }
+    });
+
     token('{')
+        .ignore_then(
+            pattern()
+                .then(guard)
+                .then_ignore(token("=>"))
+                .then(body)
+                .map(|((pattern, guard), body)| r#match::Arm {
+                    pattern,
+                    guard,
+                    body,
+                })
+                .repeated()
+                .collect::<Vec<_>>(),
+        )
+        .with_ctx(Context::default())
+}
+
+/// Whether the expression ends with a block and can't be continued by an operator.
+fn is_block_like(expr: &Expr) -> bool {
+    matches!(
+        expr.kind,
+        ExprKind::Block(_)
+            | ExprKind::If { .. }
+            | ExprKind::Loop { .. }
+            | ExprKind::While { .. }
+            | ExprKind::For { .. }
+            | ExprKind::Match { .. }
+    )
+}
+
+fn pattern<'a>() -> impl Parser<'a, Pattern<'a>> {
+    recursive(|pattern| {
+        let int = just('-')
+            .or_not()
+            .then(number())
+            .validate(|(minus, number), extra, emitter| {
+                let (value, suffix) = match number.kind {
+                    // Literals are at most `2^63`, so negating them can't overflow
+                    ExprKind::Int { value, suffix } if minus.is_some() => {
+                        ((value as i64).wrapping_neg(), suffix)
+                    }
+                    ExprKind::Int { value, suffix } => match i64::try_from(value) {
+                        Ok(value) => (value, suffix),
+                        Err(_) => {
+                            emitter.emit(Rich::custom(
+                                extra.span(),
+                                "integer literal is out of range for `i64`",
+                            ));
+                            (0, suffix)
+                        }
+                    },
+                    _ => {
+                        emitter.emit(Rich::custom(
+                            extra.span(),
+                            "float literals can't be used in patterns",
+                        ));
+                        (0, None)
+                    }
+                };
+
+                Pattern::new(PatternKind::Int { value, suffix }, extra.span())
+            })
+            .padded_by(whitespace());
+
+        let bool = choice((keyword("true").to(true), keyword("false").to(false)))
+            .map_with(|b, extra| Pattern::new(PatternKind::Bool(b), extra.span()))
+            .padded_by(whitespace());
+
+        let binding = keyword("mut")
+            .map_with(|_, extra| extra.span())
+            .then(ident().padded_by(whitespace()))
+            .map(|(keyword_span, name): (Span, Ident)| {
+                let span = Span::new(keyword_span.start, name.span().end);
+                let kind = PatternKind::Binding {
+                    name,
+                    mutable: true,
+                };
+
+                Pattern::new(kind, span)
+            })
+            .padded_by(whitespace());
+
+        // `(x)` is only parenthesized, while `(x,)` is a tuple
+        let tuple = pattern
+            .clone()
+            .then(
+                token(',')
+                    .ignore_then(
+                        pattern
+                            .clone()
+                            .separated_by(token(','))
+                            .allow_trailing()
+                            .collect::<Vec<_>>(),
+                    )
+                    .or_not(),
+            )
+            .delimited_by(token('('), just(')'))
+            .map_with(|(first, rest), extra| match rest {
+                Some(rest) => {
+                    let patterns = iter::once(first).chain(rest).collect();
+                    Pattern::new(PatternKind::Tuple(patterns), extra.span())
+                }
+                None => first,
+            })
+            .padded_by(whitespace());
+
+        let variant_fields = pattern
+            .clone()
+            .separated_by(token(','))
+            .allow_trailing()
+            .collect::<Vec<_>>()
+            .then(just(')').map_with(|_, extra| extra.span()))
+            .delimited_by(token('('), empty())
+            .padded_by(whitespace())
+            .map(|(patterns, rparen_span)| PatternSuffix::Tuple(patterns, rparen_span));
+
+        // `Point { x }` is short for `Point { x: x }`
+        let field = ident()
+            .then(colon().ignore_then(pattern).or_not())
+            .map(|(name, pattern)| {
+                let pattern = pattern.unwrap_or_else(|| {
+                    let span = name.span();
+                    let kind = PatternKind::Binding {
+                        name: name.clone(),
+                        mutable: false,
+                    };
+
+                    Pattern::new(kind, span)
+                });
+
+                pattern::FieldPattern { name, pattern }
+            });
+        let fields = field
+            .separated_by(token(','))
+            .allow_trailing()
+            .collect::<Vec<_>>();
+        let rest = token("..").or_not().map(|rest| rest.is_some());
+        let r#struct = token('{')
+            .ignore_then(fields)
+            .then(rest)
+            .then(just('}').map_with(|_, extra| extra.span()))
+            .padded_by(whitespace())
+            .map(|((fields, rest), rbrace_span)| PatternSuffix::Struct {
+                fields,
+                rest,
+                rbrace_span,
+            });
+
+        let path = path()
+            .then(choice((variant_fields, r#struct)).or_not())
+            .map(|(path, suffix)| {
+                let start = path.span().start;
+
+                match (suffix, path.as_ident()) {
+                    (Some(PatternSuffix::Tuple(patterns, rparen_span)), _) => Pattern::new(
+                        PatternKind::TupleVariant(path, patterns),
+                        Span::new(start, rparen_span.end),
+                    ),
+                    (
+                        Some(PatternSuffix::Struct {
+                            fields,
+                            rest,
+                            rbrace_span,
+                        }),
+                        _,
+                    ) => Pattern::new(
+                        PatternKind::Struct { path, fields, rest },
+                        Span::new(start, rbrace_span.end),
+                    ),
+                    (None, Some(ident)) if ident.as_str() == "_" => {
+                        Pattern::new(PatternKind::Wildcard, ident.span())
+                    }
+                    (None, Some(ident)) => {
+                        let kind = PatternKind::Binding {
+                            name: ident.clone(),
+                            mutable: false,
+                        };
+
+                        Pattern::new(kind, ident.span())
+                    }
+                    (None, None) => {
+                        let span = path.span();
+                        Pattern::new(PatternKind::Path(path), span)
+                    }
+                }
+            });
+
+        choice((int, bool, binding, tuple, path))
+            .labelled("pattern")
+            .boxed()
+    })
+}
+
+/// What follows the path of a pattern.
+enum PatternSuffix<'a> {
+    Tuple(Vec<Pattern<'a>>, Span),
+    Struct {
+        fields: Vec<pattern::FieldPattern<'a>>,
+        rest: bool,
+        rbrace_span: Span,
+    },
+}
+
+/// Parses a keyword that doesn't continue as an identifier, like `if` but not `iffy`.
+///
+/// Unlike `text::ascii::keyword`, a mismatch is reported like any other unexpected input,
+/// instead of hiding the errors of alternatives that got further.
+fn keyword<'a>(keyword: &'static str) -> impl Parser<'a, ()> {
+    just(keyword)
+        .then(any().filter(|c: &char| is_xid_continue(*c)).not())
+        .ignored()
+        .labelled(keyword)
+}
+
+/// Skips whitespace and comments, except doc comments: `//` line comments
+/// and `/* */` block comments, which can be nested.
+///
+/// Unlike a parser made of alternatives, skipping them doesn't add `//` or `/*`
+/// to the expected input of every error.
+fn whitespace<'a>() -> impl Parser<'a, ()> {
+    custom(|input| {
+        let start = input.offset();
+        let source: &str = input.slice_from(start..);
+        let mut len = 0;
+
+        loop {
+            let rest = &source[len..];
+
+            if rest.starts_with("//") && !is_doc_comment(rest) {
+                len += rest.find('\n').unwrap_or(rest.len());
+            } else if rest.starts_with("/*") {
+                let Some(comment_len) = block_comment_len(rest) else {
+                    source[..len].chars().for_each(|_| input.skip());
+                    let comment = input.offset();
+                    rest.chars().for_each(|_| input.skip());
+
+                    return Err(Rich::custom(
+                        input.span_since(comment),
+                        "unterminated block comment",
+                    ));
+                };
+                len += comment_len;
+            } else if let Some(c) = rest.chars().next().filter(|c| c.is_whitespace()) {
+                len += c.len_utf8();
+            } else {
+                break;
+            }
+        }
+
+        source[..len].chars().for_each(|_| input.skip());
+
+        Ok(())
+    })
+}
+
+/// Returns whether `source` starts with a doc comment, `///` or `//!` but not `////`.
+fn is_doc_comment(source: &str) -> bool {
+    (source.starts_with("///") && !source.starts_with("////")) || source.starts_with("//!")
+}
+
+/// Returns the length of the block comment `source` starts with, including nested comments,
+/// or `None` if it's never closed.
+fn block_comment_len(source: &str) -> Option<usize> {
+    let mut depth = 0;
+    let mut len = 0;
+
+    while len < source.len() {
+        let rest = &source[len..];
+
+        if rest.starts_with("/*") {
+            depth += 1;
+            len += 2;
+        } else if rest.starts_with("*/") {
+            depth -= 1;
+            len += 2;
+
+            if depth == 0 {
+                return Some(len);
+            }
+        } else {
+            len += rest.chars().next()?.len_utf8();
+        }
+    }
+
+    None
+}
+
+/// Parses the lines of the `///` doc comments before an item, without the `///`.
+fn doc<'a>() -> impl Parser<'a, Vec<&'a str>> {
+    doc_comments("///")
+}
+
+/// Parses the lines of consecutive doc comments starting with `marker`, without the `marker`.
+///
+/// Like `whitespace`, they're skipped by hand so that they aren't expected by every error.
+fn doc_comments<'a>(marker: &'static str) -> impl Parser<'a, Vec<&'a str>> {
+    whitespace().ignore_then(custom(move |input| {
+        let mut lines = Vec::new();
+
+        loop {
+            let start = input.offset();
+            let source: &str = input.slice_from(start..);
+
+            if !source.starts_with(marker) || !is_doc_comment(source) {
+                return Ok(lines);
+            }
+
+            let line = source.lines().next().unwrap_or_default();
+            line.chars().for_each(|_| input.skip());
+            lines.push(&line[marker.len()..]);
+
+            input.parse(whitespace())?;
+        }
+    }))
+}
+
+/// Skips doc comments that don't document an item, like those before a statement,
+/// which are only comments.
+fn stray_doc_comments<'a>() -> impl Parser<'a, ()> {
+    custom(|input| loop {
+        let start = input.offset();
+        input.parse(doc_comments("///"))?;
+        input.parse(doc_comments("//!"))?;
+
+        if input.offset() == start {
+            return Ok(());
+        }
+    })
+}
+
+fn token<'a, T>(c: T) -> impl Parser<'a, ()>
+where
+    T: OrderedSeq<'a, char> + Clone + 'a,
+{
+    just(c).padded_by(whitespace()).ignored()
 }
 
 fn rbrace<'a>() -> impl Parser<'a, ()> {
@@ -94,84 +791,954 @@ fn equals<'a>() -> impl Parser<'a, ()> {
     token('=')
 }
 
-fn stmt<'a>(expr: impl Parser<'a, Expr<'a>>) -> impl Parser<'a, Stmt<'a>> {
-    // TODO: pull into individual stmts, so that blocks don't need to be ; terminated
-    let end_of_statement = semi().or(rbrace().rewind());
-    let expr_stmt = expr.clone().map(Stmt::Expr);
+/// Parses a statement and whether it was terminated by a `;`.
+fn stmt<'a>(
+    expr: impl Parser<'a, Expr<'a>>,
+    block_like: impl Parser<'a, Expr<'a>>,
+) -> impl Parser<'a, (Stmt<'a>, bool)> {
+    let end_of_block = stray_doc_comments().then(rbrace()).rewind();
+    let end_of_statement = semi().to(true).or(end_of_block.to(false));
+    // Unless it is the value of the block, a block-like expression ends the statement
+    let block_like_stmt = block_like
+        .map(Stmt::Expr)
+        .then(end_of_statement.clone().or(empty().to(true)));
+    let expr_stmt = expr.clone().map(Stmt::Expr).then(end_of_statement);
+    // The value of a `let` only ends before another statement if its `;` is missing,
+    // which was already reported
+    let let_stmt = stmt_let(expr)
+        .then_ignore(semi().or(stmt_start().rewind()))
+        .map(|stmt| (stmt, true));
+    // After an error, the rest of the statement is skipped until its `;`,
+    // or until the next statement if the `;` is missing
+    let recovery = skip_in_stmt()
+        .then(stmt_start().not().ignore_then(skip_in_stmt()).repeated())
+        .then(semi().or(stmt_start().rewind()))
+        .ignored()
+        .or(semi())
+        .map_with(|_, extra| (Stmt::Expr(Expr::new(ExprKind::Error, extra.span())), true));
+
+    choice((let_stmt, block_like_stmt, expr_stmt)).recover_with(via_parser(recovery))
+}
+
+/// Parses the keyword starting a statement that can follow another one with a missing `;`.
+fn stmt_start<'a>() -> impl Parser<'a, ()> {
+    choice((
+        keyword("let"),
+        keyword("if"),
+        keyword("match"),
+        keyword("while"),
+        keyword("loop"),
+        keyword("for"),
+        keyword("return"),
+    ))
+}
 
-    choice((stmt_let(expr), expr_stmt)).then_ignore(end_of_statement)
+/// Skips a whole word or another character, so that keywords are only found at the start of words.
+fn skip_word_or_char<'a>() -> impl Parser<'a, ()> {
+    ident_chars().ignored().or(any().ignored())
 }
 
+/// Skips a whole word or another character of a statement, which doesn't end its block.
+fn skip_in_stmt<'a>() -> impl Parser<'a, ()> {
+    skip_word_or_char().and_is(one_of(";{}").not())
+}
+
+/// Parses a `let` statement without its `;`.
 fn stmt_let<'a>(expr: impl Parser<'a, Expr<'a>>) -> impl Parser<'a, Stmt<'a>> {
-    text::ascii::keyword("let")
-        .ignore_then(ident())
-        .then(colon().ignore_then(ident()).or_not())
+    // After an error, the rest of the value is skipped, so that the variable is still declared
+    let recovery = skip_in_stmt()
+        .then(stmt_start().not().ignore_then(skip_in_stmt()).repeated())
+        .then_ignore(semi().or(stmt_start()).rewind())
+        .map_with(|_, extra| Expr::new(ExprKind::Error, extra.span()));
+    let rhs = expr
+        .then_ignore(semi().rewind())
+        .recover_with(via_parser(recovery));
+
+    keyword("let")
+        .ignore_then(pattern())
+        .then(colon().ignore_then(r#type()).or_not())
         .then_ignore(equals())
-        .then(expr)
-        .map(|((name, ty), rhs)| Stmt::Let(Let { name, ty, rhs }))
-        .padded()
+        .then(rhs)
+        .map(|((pattern, ty), rhs)| Stmt::Let(Let { pattern, ty, rhs }))
+        .padded_by(whitespace())
+}
+
+fn expr_break<'a>(expr: impl Parser<'a, Expr<'a>>) -> impl Parser<'a, Expr<'a>> {
+    keyword("break")
+        .map_with(|_, extra| extra.span())
+        .padded_by(whitespace())
+        .then(label().or_not())
+        .then(expr.or_not())
+        .map(|((keyword_span, label), value): ((Span, _), _)| {
+            let end = match (&label, &value) {
+                (_, Some(value)) => value.span.end,
+                (Some(label), None) => label.span().end,
+                (None, None) => keyword_span.end,
+            };
+            let kind = ExprKind::Break {
+                label,
+                value: value.map(Expr::boxed),
+            };
+
+            Expr::new(kind, Span::new(keyword_span.start, end))
+        })
+}
+
+fn expr_continue<'a>() -> impl Parser<'a, Expr<'a>> {
+    keyword("continue")
+        .map_with(|_, extra| extra.span())
+        .padded_by(whitespace())
+        .then(label().or_not())
+        .map(|(keyword_span, label): (Span, Option<Ident>)| {
+            let end = label.as_ref().map_or(keyword_span, Ident::span).end;
+
+            Expr::new(
+                ExprKind::Continue { label },
+                Span::new(keyword_span.start, end),
+            )
+        })
+}
+
+fn expr_return<'a>(expr: impl Parser<'a, Expr<'a>>) -> impl Parser<'a, Expr<'a>> {
+    keyword("return")
+        .map_with(|_, extra| extra.span())
+        .padded_by(whitespace())
+        .then(expr.or_not())
+        .map(|(keyword_span, value): (Span, Option<Expr>)| {
+            let end = value.as_ref().map_or(keyword_span, |value| value.span).end;
+
+            Expr::new(
+                ExprKind::Return(value.map(Expr::boxed)),
+                Span::new(keyword_span.start, end),
+            )
+        })
+}
+
+fn expr_closure<'a>(
+    expr: impl Parser<'a, Expr<'a>>,
+    block: impl Parser<'a, Block<'a>>,
+) -> impl Parser<'a, Expr<'a>> {
+    let arg = pattern()
+        .then(colon().ignore_then(r#type()).or_not())
+        .map(|(pattern, r#type)| closure::Arg { pattern, r#type });
+    let args = choice((
+        just("||").map(|_| Vec::new()),
+        arg.separated_by(token(','))
+            .allow_trailing()
+            .collect::<Vec<_>>()
+            .delimited_by(just('|'), just('|')),
+    ));
+    let block = block.map(|block| {
+        let span = block.span;
+        Expr::new(ExprKind::Block(block), span)
+    });
+    // Like in Rust, the body has to be a block if the return type is given
+    let body = choice((
+        token("->")
+            .ignore_then(r#type())
+            .then(block)
+            .map(|(return_type, body)| (Some(return_type), body)),
+        expr.map(|body| (None, body)),
+    ));
+
+    args.map_with(|args, extra| (args, extra.span()))
+        .padded_by(whitespace())
+        .then(body)
+        .map(|((args, args_span), (return_type, body)): ((_, Span), _)| {
+            let span = Span::new(args_span.start, body.span.end);
+            let closure = Closure {
+                args,
+                return_type,
+                body: body.boxed(),
+            };
+
+            Expr::new(ExprKind::Closure(closure), span)
+        })
+}
+
+fn expr_struct<'a>(expr: impl Parser<'a, Expr<'a>>) -> impl Parser<'a, Expr<'a>> {
+    // `Point { x }` is short for `Point { x: x }`
+    let field = ident()
+        .then(colon().ignore_then(expr.clone()).or_not())
+        .map(|(name, value)| {
+            let value = value.unwrap_or_else(|| {
+                let span = name.span();
+                Expr::new(ExprKind::Var(name.clone()), span)
+            });
+
+            r#struct::FieldInit { name, value }
+        });
+    let fields = field
+        .separated_by(token(','))
+        .allow_trailing()
+        .collect::<Vec<_>>();
+    let base = token("..").ignore_then(expr).or_not();
+    let path = path().try_map_with(|path, extra| {
+        if extra.ctx().no_struct_literals {
+            Err(Rich::custom(
+                extra.span(),
+                "struct literals need to be parenthesized here",
+            ))
+        } else {
+            Ok(path)
+        }
+    });
+
+    path.then_ignore(token('{'))
+        .then(fields)
+        .then(base)
+        .then(just('}').map_with(|_, extra| extra.span()))
+        .map(|(((path, fields), base), rbrace_span): ((_, _), Span)| {
+            let span = Span::new(path.span().start, rbrace_span.end);
+            let kind = ExprKind::Struct {
+                path,
+                fields,
+                base: base.map(Expr::boxed),
+            };
+
+            Expr::new(kind, span)
+        })
+        .padded_by(whitespace())
 }
 
 fn expr<'a>() -> impl Parser<'a, Expr<'a>> {
     recursive(|expr| {
-        let parenthized = expr
-            .clone()
-            .delimited_by(token('('), token(')'))
-            .map(|expr: Expr| Expr::Parens(expr.boxed()))
-            .padded();
+        // `(x)` is only parenthesized, while `(x,)` is a tuple
+        let parenthized = {
+            let expr = expr.clone().with_ctx(Context::default());
 
-        let var = ident().map_with(|name, extra| Expr::Var {
-            name,
-            name_span: extra.span(),
+            just('(')
+                .ignore_then(
+                    expr.clone().then(
+                        token(',')
+                            .ignore_then(
+                                expr.separated_by(token(','))
+                                    .allow_trailing()
+                                    .collect::<Vec<_>>(),
+                            )
+                            .or_not(),
+                    ),
+                )
+                .then_ignore(just(')'))
+                .map_with(|(first, rest): (Expr, _), extra| {
+                    let kind = match rest {
+                        Some(rest) => ExprKind::Tuple(iter::once(first).chain(rest).collect()),
+                        None => ExprKind::Parens(first.boxed()),
+                    };
+
+                    Expr::new(kind, extra.span())
+                })
+                .recover_with(via_parser(nested_delimiters(
+                    '(',
+                    ')',
+                    [('[', ']'), ('{', '}')],
+                    |span| Expr::new(ExprKind::Error, span),
+                )))
+                .padded_by(whitespace())
+        };
+
+        let self_var = self_ident().map(|name| {
+            let span = name.span();
+            Expr::new(ExprKind::Var(name), span)
         });
+        // Keywords like `match` aren't variables, even if they fail to parse as expressions
+        let var = any_keyword()
+            .not()
+            .ignore_then(path())
+            .map(|path| match path.as_ident() {
+                Some(name) => {
+                    let span = name.span();
+                    Expr::new(ExprKind::Var(name.clone()), span)
+                }
+                None => {
+                    let span = path.span();
+                    Expr::new(ExprKind::Path(path), span)
+                }
+            });
 
-        let block = block(expr).map(Expr::Block);
+        let block_like = block_like(expr.clone(), block(expr.clone()));
 
-        let atom = choice((block, int(), parenthized, var));
+        let array =
+            {
+                let expr = expr.clone().with_ctx(Context::default());
+                let repeat = expr.clone().then_ignore(token(';')).then(expr.clone()).map(
+                    |(value, count)| ExprKind::Repeat {
+                        value: value.boxed(),
+                        count: count.boxed(),
+                    },
+                );
+                let elements = expr
+                    .separated_by(token(','))
+                    .allow_trailing()
+                    .collect::<Vec<_>>()
+                    .map(ExprKind::Array);
 
-        let op = |c| just(c).padded();
+                just('[')
+                    .ignore_then(choice((repeat, elements)).padded_by(whitespace()))
+                    .then_ignore(just(']'))
+                    .map_with(|kind, extra| Expr::new(kind, extra.span()))
+                    .recover_with(via_parser(nested_delimiters(
+                        '[',
+                        ']',
+                        [('(', ')'), ('{', '}')],
+                        |span| Expr::new(ExprKind::Error, span),
+                    )))
+                    .padded_by(whitespace())
+            };
 
-        let unary = op('-')
-            .repeated()
-            .foldr(atom, |_op, rhs| Expr::Neg(rhs.boxed()));
+        let atom = choice((
+            block_like,
+            number(),
+            bool(),
+            string(),
+            format_string(expr.clone()),
+            expr_break(expr.clone()),
+            expr_continue(),
+            expr_return(expr.clone()),
+            expr_closure(expr.clone(), block(expr.clone())),
+            parenthized,
+            array,
+            expr_struct(expr.clone()),
+            self_var,
+            var,
+        ));
 
-        let product = unary.clone().foldl(
-            choice((
-                op('*').to(Expr::Mul as fn(_, _) -> _),
-                op('/').to(Expr::Div as fn(_, _) -> _),
-            ))
-            .then(unary)
-            .repeated(),
-            |lhs, (op, rhs)| op(lhs.boxed(), rhs.boxed()),
-        );
+        let op = |c| just(c).padded_by(whitespace());
 
-        let sum = product.clone().foldl(
-            choice((
-                op('+').to(Expr::Add as fn(_, _) -> _),
-                op('-').to(Expr::Sub as fn(_, _) -> _),
-            ))
-            .then(product)
-            .repeated(),
-            |lhs, (op, rhs)| op(lhs.boxed(), rhs.boxed()),
-        );
+        let args = expr
+            .clone()
+            .with_ctx(Context::default())
+            .separated_by(token(','))
+            .allow_trailing()
+            .collect::<Vec<_>>()
+            .then(just(')').map_with(|_, extra| extra.span()))
+            .delimited_by(token('('), empty())
+            .padded_by(whitespace());
+        let call = args
+            .clone()
+            .map(|(args, rparen_span)| Postfix::Call(args, rparen_span));
+        let index = expr
+            .clone()
+            .with_ctx(Context::default())
+            .then(just(']').map_with(|_, extra| extra.span()))
+            .delimited_by(token('['), empty())
+            .padded_by(whitespace())
+            .map(|(index, rbracket_span)| Postfix::Index(index, rbracket_span));
+        let r#try = just('?')
+            .map_with(|_, extra| Postfix::Try(extra.span()))
+            .padded_by(whitespace());
+        // The fields of tuples are numbered, like `t.0`
+        let tuple_field = text::int(10)
+            .map_with(|index, extra| Ident::new(index, extra.span()))
+            .labelled("tuple field");
+        let field = token('.').ignore_then(choice((
+            ident().then(args.or_not()).map(|(name, args)| match args {
+                Some((args, rparen_span)) => Postfix::MethodCall(name, args, rparen_span),
+                None => Postfix::Field(name),
+            }),
+            tuple_field.map(Postfix::Field),
+        )));
+
+        let call = atom
+            .foldl(
+                choice((call, index, r#try, field)).repeated(),
+                |lhs, postfix| match postfix {
+                    Postfix::Call(args, rparen_span) => {
+                        let span = Span::new(lhs.span.start, rparen_span.end);
+                        Expr::new(ExprKind::Call(lhs.boxed(), args), span)
+                    }
+                    Postfix::MethodCall(method, args, rparen_span) => {
+                        let span = Span::new(lhs.span.start, rparen_span.end);
+                        let kind = ExprKind::MethodCall {
+                            receiver: lhs.boxed(),
+                            method,
+                            args,
+                        };
+
+                        Expr::new(kind, span)
+                    }
+                    Postfix::Index(index, rbracket_span) => {
+                        let span = Span::new(lhs.span.start, rbracket_span.end);
+                        Expr::new(ExprKind::Index(lhs.boxed(), index.boxed()), span)
+                    }
+                    Postfix::Try(question_mark_span) => {
+                        let span = Span::new(lhs.span.start, question_mark_span.end);
+                        Expr::new(ExprKind::Try(lhs.boxed()), span)
+                    }
+                    Postfix::Field(field) => {
+                        let span = Span::new(lhs.span.start, field.span().end);
+                        Expr::new(ExprKind::Field(lhs.boxed(), field), span)
+                    }
+                },
+            )
+            .boxed();
+
+        let unary = choice((
+            just('-').to(ExprKind::Neg as fn(_) -> _),
+            just('!').to(ExprKind::Not as fn(_) -> _),
+        ))
+        .map_with(|op, extra| (op, extra.span()))
+        .padded_by(whitespace())
+        .repeated()
+        .foldr(call, |(op, op_span): (fn(_) -> _, Span), rhs| {
+            let span = Span::new(op_span.start, rhs.span.end);
+            Expr::new(op(rhs.boxed()), span)
+        })
+        .boxed();
+
+        // Operators from the highest to the lowest precedence:
+        //
+        // | Operator                    | Associativity |
+        // |-----------------------------|---------------|
+        // | `-x` `!x`                   |               |
+        // | `*` `/`                     | left          |
+        // | `+` `-`                     | left          |
+        // | `==` `!=` `<` `<=` `>` `>=` | none          |
+        // | `&&`                        | left          |
+        // | `\|\|`                      | left          |
+        // | `=` `+=` `-=` `*=` `/=`     | right         |
+
+        let product = unary
+            .clone()
+            .foldl(
+                choice((
+                    op("*").to(ExprKind::Mul as fn(_, _) -> _),
+                    op("/").to(ExprKind::Div as fn(_, _) -> _),
+                ))
+                .then(unary)
+                .repeated(),
+                |lhs, (op, rhs)| binary(op, lhs, rhs),
+            )
+            .boxed();
+
+        let sum = product
+            .clone()
+            .foldl(
+                choice((
+                    op("+").to(ExprKind::Add as fn(_, _) -> _),
+                    op("-").to(ExprKind::Sub as fn(_, _) -> _),
+                ))
+                .then(product)
+                .repeated(),
+                |lhs, (op, rhs)| binary(op, lhs, rhs),
+            )
+            .boxed();
+
+        // Comparisons can't be chained, `a < b < c` is a syntax error
+        let comparison = sum
+            .clone()
+            .then(
+                choice((
+                    op("==").to(ExprKind::Eq as fn(_, _) -> _),
+                    op("!=").to(ExprKind::Ne as fn(_, _) -> _),
+                    op("<=").to(ExprKind::Le as fn(_, _) -> _),
+                    op(">=").to(ExprKind::Ge as fn(_, _) -> _),
+                    op("<").to(ExprKind::Lt as fn(_, _) -> _),
+                    op(">").to(ExprKind::Gt as fn(_, _) -> _),
+                ))
+                .then(sum)
+                .or_not(),
+            )
+            .map(|(lhs, rhs)| match rhs {
+                Some((op, rhs)) => binary(op, lhs, rhs),
+                None => lhs,
+            })
+            .boxed();
+
+        let and = comparison
+            .clone()
+            .foldl(
+                op("&&")
+                    .to(ExprKind::And as fn(_, _) -> _)
+                    .then(comparison)
+                    .repeated(),
+                |lhs, (op, rhs)| binary(op, lhs, rhs),
+            )
+            .boxed();
+
+        let or = and
+            .clone()
+            .foldl(
+                op("||")
+                    .to(ExprKind::Or as fn(_, _) -> _)
+                    .then(and)
+                    .repeated(),
+                |lhs, (op, rhs)| binary(op, lhs, rhs),
+            )
+            .boxed();
 
-        sum
+        // Ranges can't be chained, like comparisons
+        let range = or
+            .clone()
+            .then(
+                choice((op("..=").to(true), op("..").to(false)))
+                    .then(or)
+                    .or_not(),
+            )
+            .map(|(start, end)| match end {
+                Some((inclusive, end)) => {
+                    let span = Span::new(start.span.start, end.span.end);
+                    let kind = ExprKind::Range {
+                        start: start.boxed(),
+                        end: end.boxed(),
+                        inclusive,
+                    };
+
+                    Expr::new(kind, span)
+                }
+                None => start,
+            })
+            .boxed();
+
+        let assignment = range
+            .clone()
+            .then(
+                choice((
+                    op("=").to(ExprKind::Assign as fn(_, _) -> _),
+                    op("+=").to(ExprKind::AddAssign as fn(_, _) -> _),
+                    op("-=").to(ExprKind::SubAssign as fn(_, _) -> _),
+                    op("*=").to(ExprKind::MulAssign as fn(_, _) -> _),
+                    op("/=").to(ExprKind::DivAssign as fn(_, _) -> _),
+                ))
+                .then(expr)
+                .or_not(),
+            )
+            .map(|(lhs, rhs)| match rhs {
+                Some((op, rhs)) => binary(op, lhs, rhs),
+                None => lhs,
+            })
+            .boxed();
+
+        assignment
     })
 }
 
+/// Operators following an expression, like calls and field accesses.
+enum Postfix<'a> {
+    Call(Vec<Expr<'a>>, Span),
+    /// `[index]` and the span of its `]`
+    Index(Expr<'a>, Span),
+    /// `?` and its span
+    Try(Span),
+    MethodCall(Ident<'a>, Vec<Expr<'a>>, Span),
+    Field(Ident<'a>),
+}
+
+fn binary<'a>(
+    op: fn(Box<Expr<'a>>, Box<Expr<'a>>) -> ExprKind<'a>,
+    lhs: Expr<'a>,
+    rhs: Expr<'a>,
+) -> Expr<'a> {
+    let span = Span::new(lhs.span.start, rhs.span.end);
+
+    Expr::new(op(lhs.boxed(), rhs.boxed()), span)
+}
+
+/// The words that can't be identifiers, unless they are written as raw identifiers like `r#fn`.
+const KEYWORDS: &[&str] = &[
+    "break", "continue", "else", "enum", "false", "fn", "for", "if", "impl", "in", "let", "loop",
+    "match", "mut", "return", "self", "struct", "true", "while",
+];
+
+/// Parses an identifier, or a raw identifier like `r#type`, whose name doesn't include the `r#`.
+/// Only raw identifiers can be keywords.
 fn ident<'a>() -> impl Parser<'a, Ident<'a>> {
-    text::ascii::ident()
-        .padded()
-        .map(Ident::new)
+    // `r#"` starts a raw string instead
+    let raw = just("r#").labelled("identifier").ignore_then(
+        ident_chars()
+            .labelled("identifier")
+            .try_map(|ident: &str, span| match ident {
+                "self" => Err(Rich::custom(span, "`self` can't be a raw identifier")),
+                _ => Ok(ident),
+            }),
+    );
+    // A keyword is still parsed as an identifier, so that the rest of the code is checked
+    let ident = ident_chars()
         .labelled("identifier")
+        .validate(|ident: &str, extra, emitter| {
+            if KEYWORDS.contains(&ident) {
+                // `self` can't be a raw identifier either
+                let message = match ident {
+                    "self" => "`self` is a keyword and can't be an identifier".to_string(),
+                    _ => format!("`{ident}` is a keyword, use `r#{ident}` for an identifier"),
+                };
+                emitter.emit(Rich::custom(extra.span(), message));
+            }
+
+            ident
+        });
+
+    raw.or(ident)
+        .map_with(|ident, extra| Ident::new(nfc(ident), extra.span()))
+        .padded_by(whitespace())
+}
+
+/// Parses the characters of an identifier: `_` or a Unicode letter, then letters, digits and `_`,
+/// following the XID rules like Rust does.
+fn ident_chars<'a>() -> impl Parser<'a, &'a str> {
+    any()
+        .try_map(|c: char, span| {
+            if c == '_' || is_xid_start(c) {
+                Ok(c)
+            } else {
+                Err(Error::<&str>::expected_found([], Some(c.into()), span))
+            }
+        })
+        .then(select! { c if is_xid_continue(c) => () }.repeated())
+        .to_slice()
+}
+
+/// Normalizes an identifier to NFC, so that differently encoded names like `é` and `e\u{301}` are
+/// the same identifier.
+fn nfc(ident: &str) -> Cow<'_, str> {
+    if is_nfc(ident) {
+        Cow::Borrowed(ident)
+    } else {
+        Cow::Owned(ident.nfc().collect())
+    }
+}
+
+/// Parses any keyword, which a variable can't start with.
+fn any_keyword<'a>() -> impl Parser<'a, ()> {
+    ident_chars()
+        .filter(|ident: &&str| KEYWORDS.contains(ident))
+        .ignored()
+}
+
+/// Parses `self`, which refers to the receiver of a method.
+fn self_ident<'a>() -> impl Parser<'a, Ident<'a>> {
+    keyword("self")
+        .to_slice()
+        .map_with(|name, extra| Ident::new(name, extra.span()))
+        .padded_by(whitespace())
+}
+
+/// Parses a path like `Shape::Circle`.
+fn path<'a>() -> impl Parser<'a, Path<'a>> {
+    ident()
+        .separated_by(token("::"))
+        .at_least(1)
+        .collect::<Vec<_>>()
+        .map(|segments| Path { segments })
+        .labelled("path")
+}
+
+/// Parses a loop label like `'outer`.
+fn label<'a>() -> impl Parser<'a, Ident<'a>> {
+    just('\'')
+        .ignore_then(ident_chars())
+        .map_with(|label, extra| Ident::new(nfc(label), extra.span()))
+        .padded_by(whitespace())
+        .labelled("label")
+}
+
+fn r#type<'a>() -> impl Parser<'a, Type<'a>> {
+    recursive(|r#type| {
+        let args = r#type
+            .clone()
+            .separated_by(token(','))
+            .allow_trailing()
+            .collect::<Vec<_>>()
+            .then(just('>').map_with(|_, extra| extra.span()))
+            .delimited_by(token('<'), empty())
+            .padded_by(whitespace());
+        let named = path()
+            .then(args.or_not())
+            .map(|(path, args): (Path, _)| match args {
+                Some((args, rangle_span)) => {
+                    let span = Span::new(path.span().start, rangle_span.end);
+                    Type::new(TypeKind::Named(path, args), span)
+                }
+                None => {
+                    let span = path.span();
+                    Type::new(TypeKind::Named(path, Vec::new()), span)
+                }
+            });
+        let r#fn = keyword("fn")
+            .padded_by(whitespace())
+            .ignore_then(
+                r#type
+                    .clone()
+                    .separated_by(token(','))
+                    .allow_trailing()
+                    .collect::<Vec<_>>()
+                    .delimited_by(token('('), token(')')),
+            )
+            .then(token("->").ignore_then(r#type.clone()).or_not())
+            .map_with(|(params, return_type), extra| {
+                Type::new(
+                    TypeKind::Fn(params, return_type.map(Box::new)),
+                    extra.span(),
+                )
+            });
+        let array = r#type
+            .clone()
+            .delimited_by(just('['), just(']'))
+            .map_with(|ty, extra| Type::new(TypeKind::Array(Box::new(ty)), extra.span()))
+            .padded_by(whitespace());
+        // `(T)` is only parenthesized, while `(T,)` is a tuple
+        let tuple = r#type
+            .separated_by(token(','))
+            .collect::<Vec<_>>()
+            .then(token(',').or_not())
+            .delimited_by(token('('), just(')'))
+            .map_with(|(mut types, comma), extra| match types.len() {
+                1 if comma.is_none() => types.remove(0),
+                _ => Type::new(TypeKind::Tuple(types), extra.span()),
+            })
+            .padded_by(whitespace());
+
+        choice((r#fn, named, array, tuple))
+    })
+    .labelled("type")
 }
 
-fn r#type<'a>() -> impl Parser<'a, Ident<'a>> {
-    ident().labelled("type")
+fn bool<'a>() -> impl Parser<'a, Expr<'a>> {
+    choice((keyword("true").to(true), keyword("false").to(false)))
+        .map_with(|b, extra| Expr::new(ExprKind::Bool(b), extra.span()))
+        .padded_by(whitespace())
+}
+
+/// Parses a string literal like `"a\tb"`, or a raw string literal like `r#"a "b""#`.
+fn string<'a>() -> impl Parser<'a, Expr<'a>> {
+    let quoted = string_char("\\\"")
+        .repeated()
+        .collect::<Vec<_>>()
+        .delimited_by(just('"'), just('"'))
+        .map(|chars| chars.into_iter().flatten().collect());
+
+    choice((quoted, raw_string()))
+        .map_with(|string, extra| Expr::new(ExprKind::String(string), extra.span()))
+        .padded_by(whitespace())
+        .labelled("string")
+}
+
+/// Parses a raw string literal, which has no escapes.
+/// It ends at a quote followed by as many `#` as it started with.
+fn raw_string<'a>() -> impl Parser<'a, String> {
+    // The contents get the number of `#` as their context
+    let terminator = just('"').then(
+        just('#')
+            .repeated()
+            .configure(|repeated, hashes: &usize| repeated.exactly(*hashes)),
+    );
+    let contents = any()
+        .and_is(terminator.not())
+        .repeated()
+        .to_slice()
+        .then_ignore(terminator);
+
+    just('r')
+        .ignore_then(just('#').repeated().count())
+        .then_ignore(just('"'))
+        .ignore_with_ctx(contents)
+        .map(str::to_string)
+}
+
+/// Parses an interpolated string like `f"{name} is {age} years old"`.
+/// Braces are written as `{{` and `}}`.
+fn format_string<'a>(expr: impl Parser<'a, Expr<'a>>) -> impl Parser<'a, Expr<'a>> {
+    let text = choice((
+        just("{{").to(Some('{')),
+        just("}}").to(Some('}')),
+        string_char("\\\"{}"),
+    ))
+    .repeated()
+    .at_least(1)
+    .collect::<Vec<_>>()
+    .map(|chars| FormatPart::Str(chars.into_iter().flatten().collect()));
+    let expr = expr
+        .with_ctx(Context::default())
+        .delimited_by(just('{'), just('}'))
+        .map(FormatPart::Expr);
+
+    choice((text, expr))
+        .repeated()
+        .collect::<Vec<_>>()
+        .delimited_by(just("f\""), just('"'))
+        .map_with(|parts, extra| Expr::new(ExprKind::Format(parts), extra.span()))
+        .padded_by(whitespace())
+        .labelled("string")
+}
+
+/// Parses a character of a string literal other than `excluded` ones, resolving escapes.
+/// Escaped newlines are skipped together with the following whitespace.
+fn string_char<'a>(excluded: &'static str) -> impl Parser<'a, Option<char>> {
+    let unicode = text::digits(16)
+        .at_least(1)
+        .at_most(6)
+        .to_slice()
+        .delimited_by(just("u{"), just('}'))
+        .validate(|digits: &str, extra, emitter| {
+            let code = u32::from_str_radix(digits, 16).expect("BUG: digits are hexadecimal");
+
+            char::from_u32(code).unwrap_or_else(|| {
+                emitter.emit(Rich::custom(
+                    extra.span(),
+                    format!("`{digits}` isn't a valid unicode character"),
+                ));
+                char::REPLACEMENT_CHARACTER
+            })
+        });
+    let escape = choice((
+        just('n').to('\n'),
+        just('r').to('\r'),
+        just('t').to('\t'),
+        just('0').to('\0'),
+        just('\\').to('\\'),
+        just('"').to('"'),
+        just('\'').to('\''),
+        unicode,
+    ))
+    .map(Some)
+    .or(just('\n').then(text::whitespace()).to(None))
+    .labelled("escape sequence");
+
+    choice((just('\\').ignore_then(escape), none_of(excluded).map(Some)))
+}
+
+/// Parses a number literal like `42`, `0xff_ff`, `1.5e-3` or `1_f64`.
+/// Literals are `f64` if they have a fraction, an exponent or the suffix `f64`.
+/// Integer literals get their type from their suffix, or else from the context.
+fn number<'a>() -> impl Parser<'a, Expr<'a>> {
+    let decimal = digits(10)
+        .then(fraction_and_exponent())
+        .to_slice()
+        .map(|digits| (digits, 10));
+    let prefixed = choice((
+        just("0x")
+            .ignore_then(digits(16))
+            .map(|digits| (digits, 16)),
+        just("0o").ignore_then(digits(8)).map(|digits| (digits, 8)),
+        just("0b").ignore_then(digits(2)).map(|digits| (digits, 2)),
+    ));
+    let suffix = custom(|input| {
+        let start = input.offset();
+        let source: &str = input.slice_from(start..);
+
+        if !source.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+            return Ok(None);
+        }
+
+        let len = source
+            .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+            .unwrap_or(source.len());
+        source[..len].chars().for_each(|_| input.skip());
+
+        Ok(Some(&source[..len]))
+    });
+
+    choice((prefixed, decimal))
+        .then(suffix)
+        // Malformed literals are reported without failing the parse,
+        // so that the error isn't replaced by those of other alternatives
+        .validate(
+            |((digits, radix), suffix): ((&str, u32), _), extra, emitter| {
+                let span: Span = extra.span();
+                let digits = digits.replace('_', "");
+                let is_float = radix == 10 && digits.contains(['.', 'e', 'E']);
+                // The placeholder keeps the literal's uses from being reported too
+                let mut error = |message: String| {
+                    emitter.emit(Rich::custom(span, message));
+                    ExprKind::Error
+                };
+
+                let kind = match suffix {
+                    Some("f64") if radix != 10 => {
+                        error("only decimal literals can be `f64`".into())
+                    }
+                    Some("f64") => parse_float(&digits).map_or_else(error, ExprKind::Float),
+                    None if is_float => parse_float(&digits).map_or_else(error, ExprKind::Float),
+                    Some(suffix @ ("i32" | "i64")) if is_float => {
+                        error(format!("`{suffix}` literals can't have a fraction"))
+                    }
+                    // The exact range depends on the type and sign, which are checked later.
+                    // Here the value only needs to fit into the largest integer type.
+                    Some("i32" | "i64") | None => {
+                        let max = match suffix {
+                            Some("i32") => 1 << 31,
+                            _ => 1 << 63,
+                        };
+
+                        match u64::from_str_radix(&digits, radix) {
+                            Ok(value) if value <= max => ExprKind::Int { value, suffix },
+                            _ => error(format!(
+                                "integer literal is out of range for `{}`",
+                                suffix.unwrap_or("i64"),
+                            )),
+                        }
+                    }
+                    Some(suffix) => error(format!("invalid suffix `{suffix}` for number literal")),
+                };
+
+                Expr::new(kind, span)
+            },
+        )
+        .padded_by(whitespace())
+        .labelled("number")
+}
+
+/// Parses digits of the given radix, which can be separated by `_`.
+fn digits<'a>(radix: u32) -> impl Parser<'a, &'a str> {
+    // `try_map` reports the unexpected character, unlike `filter`
+    any()
+        .try_map(move |c: char, span| {
+            if c.is_digit(radix) {
+                Ok(c)
+            } else {
+                Err(Error::<&str>::expected_found([], Some(c.into()), span))
+            }
+        })
+        .then(custom(move |input| {
+            let start = input.offset();
+            let source: &str = input.slice_from(start..);
+            source
+                .chars()
+                .take_while(|c| c.is_digit(radix) || *c == '_')
+                .for_each(|_| input.skip());
+
+            Ok(())
+        }))
+        .to_slice()
+}
+
+/// Skips the fraction and exponent of a decimal literal, like `.5e-3`, if it has them.
+/// A `.` is only a fraction if a digit follows, so that `1..2` and `1.abs()` still work.
+///
+/// Like `whitespace`, the optional parts of numbers are skipped by hand,
+/// so that the characters they start with aren't expected by every error after a number.
+fn fraction_and_exponent<'a>() -> impl Parser<'a, ()> {
+    custom(|input| {
+        let start = input.offset();
+        let source: &str = input.slice_from(start..);
+        let digits_len = |s: &str| {
+            s.find(|c: char| !c.is_ascii_digit() && c != '_')
+                .unwrap_or(s.len())
+        };
+        let starts_with_digit = |s: &str| s.starts_with(|c: char| c.is_ascii_digit());
+        let mut len = 0;
+
+        if let Some(fraction) = source.strip_prefix('.').filter(|s| starts_with_digit(s)) {
+            len += 1 + digits_len(fraction);
+        }
+
+        if let Some(exponent) = source[len..].strip_prefix(['e', 'E']) {
+            let sign_len = usize::from(exponent.starts_with(['+', '-']));
+
+            if starts_with_digit(&exponent[sign_len..]) {
+                len += 1 + sign_len + digits_len(&exponent[sign_len..]);
+            }
+        }
+
+        source[..len].chars().for_each(|_| input.skip());
+
+        Ok(())
+    })
 }
 
-fn int<'a>() -> impl Parser<'a, Expr<'a>> {
-    text::int(10)
-        .map(|s: &str| Expr::Int(s.parse().unwrap()))
-        .padded()
+fn parse_float(digits: &str) -> Result<f64, String> {
+    match digits.parse::<f64>() {
+        Ok(n) if n.is_finite() => Ok(n),
+        _ => Err("float literal is out of range for `f64`".into()),
+    }
 }
@@ -0,0 +1,53 @@
+//! Items that every file can use without declaring them.
+
+use std::sync::OnceLock;
+
+use chumsky::Parser;
+
+use crate::parser;
+use crate::syntax::File;
+
+const SOURCE: &str = "
+enum Option<T> {
+    Some(T),
+    None,
+}
+
+enum Result<T, E> {
+    Ok(T),
+    Err(E),
+}
+";
+
+/// The parsed prelude.
+///
+/// Its spans don't refer to the file being compiled,
+/// so the types of its items must never be reported with their declaration.
+pub fn file() -> &'static File<'static> {
+    static FILE: OnceLock<File<'static>> = OnceLock::new();
+
+    FILE.get_or_init(|| {
+        parser::file()
+            .parse(SOURCE)
+            .into_result()
+            .expect("BUG: the prelude parses")
+    })
+}
+
+/// Returns the enum of a variant that can be used without the name of its enum, like `Some`.
+pub fn variant_enum(name: &str) -> Option<&'static str> {
+    match name {
+        "Some" | "None" => Some("Option"),
+        "Ok" | "Err" => Some("Result"),
+        _ => None,
+    }
+}
+
+/// Returns the variant of the enum `name` that `?` returns early with, if it supports `?`.
+pub fn try_residual(name: &str) -> Option<&'static str> {
+    match name {
+        "Option" => Some("None"),
+        "Result" => Some("Err"),
+        _ => None,
+    }
+}
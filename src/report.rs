@@ -1,6 +1,8 @@
 use ariadne::{ColorGenerator, Fmt, Label, Report, ReportKind};
+use chumsky::error::{Rich, RichReason};
 
 use crate::syntax::Span;
+use crate::typeck::Type;
 
 pub fn undeclared_variable(name: &str, span: &Span) -> Report<'static> {
     let mut c = ColorGenerator::new();
@@ -17,15 +19,835 @@ pub fn undeclared_variable(name: &str, span: &Span) -> Report<'static> {
         .finish()
 }
 
-pub fn parse_err(err: chumsky::error::Rich<char>) -> Report<'static> {
+pub fn undeclared_function(name: &str, span: &Span) -> Report<'static> {
+    let mut c = ColorGenerator::new();
+    let name_color = c.next();
+    let name = name.fg(name_color);
+
+    Report::build(ReportKind::Error, (), span.start)
+        .with_label(
+            Label::new(span.into_range())
+                .with_message(format!("Function `{}` was not declared", name))
+                .with_color(name_color),
+        )
+        .with_help(format!("Use `fn {name}(…) {{ … }}`"))
+        .finish()
+}
+
+pub fn unknown_type(name: &str, span: &Span) -> Report<'static> {
+    let mut c = ColorGenerator::new();
+    let name_color = c.next();
+    let name = name.fg(name_color);
+
+    Report::build(ReportKind::Error, (), span.start)
+        .with_label(
+            Label::new(span.into_range())
+                .with_message(format!("Type `{}` does not exist", name))
+                .with_color(name_color),
+        )
+        .with_help("Built-in types are `i32`, `i64`, `f64`, `bool` and `string`")
+        .finish()
+}
+
+pub fn mismatched_types(
+    expected: &Type,
+    expected_span: Option<&Span>,
+    found: &Type,
+    found_span: &Span,
+) -> Report<'static> {
+    let mut c = ColorGenerator::new();
+    let expected_color = c.next();
+    let found_color = c.next();
+    let expected = expected.fg(expected_color);
+    let found = found.fg(found_color);
+
+    let mut report = Report::build(ReportKind::Error, (), found_span.start).with_label(
+        Label::new(found_span.into_range())
+            .with_message(format!("Expected `{expected}`, found `{found}`"))
+            .with_color(found_color),
+    );
+
+    if let Some(expected_span) = expected_span {
+        report = report.with_label(
+            Label::new(expected_span.into_range())
+                .with_message(format!("Expected `{expected}` because of this"))
+                .with_color(expected_color),
+        );
+    }
+
+    report.finish()
+}
+
+/// Reports a closure argument without a type, which is named `name` unless it's destructured.
+pub fn type_annotation_needed(name: Option<&str>, span: &Span) -> Report<'static> {
+    let mut c = ColorGenerator::new();
+    let name_color = c.next();
+
+    let (message, help) = match name {
+        Some(name) => {
+            let name = name.fg(name_color);
+
+            (
+                format!("The type of `{name}` can't be inferred"),
+                format!("Annotate the type, like `|{name}: i32| …`"),
+            )
+        }
+        None => (
+            "The type of this argument can't be inferred".into(),
+            "Annotate the type, like `|(a, b): (i32, i32)| …`".into(),
+        ),
+    };
+
+    Report::build(ReportKind::Error, (), span.start)
+        .with_label(
+            Label::new(span.into_range())
+                .with_message(message)
+                .with_color(name_color),
+        )
+        .with_help(help)
+        .finish()
+}
+
+pub fn mismatched_tuple_pattern(
+    expected: &Type,
+    expected_span: Option<&Span>,
+    span: &Span,
+) -> Report<'static> {
+    let mut c = ColorGenerator::new();
+    let expected_color = c.next();
+    let pattern_color = c.next();
+    let expected = expected.fg(expected_color);
+
+    let mut report = Report::build(ReportKind::Error, (), span.start).with_label(
+        Label::new(span.into_range())
+            .with_message(format!("Expected `{expected}`, found a tuple"))
+            .with_color(pattern_color),
+    );
+
+    if let Some(expected_span) = expected_span {
+        report = report.with_label(
+            Label::new(expected_span.into_range())
+                .with_message(format!("Expected `{expected}` because of this"))
+                .with_color(expected_color),
+        );
+    }
+
+    report.finish()
+}
+
+pub fn empty_array_type(span: &Span) -> Report<'static> {
     let mut c = ColorGenerator::new();
-    let span = err.span();
 
     Report::build(ReportKind::Error, (), span.start)
         .with_label(
             Label::new(span.into_range())
-                .with_message(format!("{}", err.reason()))
+                .with_message("The type of the elements of an empty array can't be inferred")
                 .with_color(c.next()),
         )
+        .with_help("Annotate the type, like `let xs: [i32] = [];`")
+        .finish()
+}
+
+pub fn unsupported_operator(op: &str, ty: &Type, span: &Span) -> Report<'static> {
+    let mut c = ColorGenerator::new();
+    let ty_color = c.next();
+    let ty = ty.fg(ty_color);
+
+    Report::build(ReportKind::Error, (), span.start)
+        .with_label(
+            Label::new(span.into_range())
+                .with_message(format!("Operator `{op}` can't be applied to `{ty}`"))
+                .with_color(ty_color),
+        )
+        .finish()
+}
+
+pub fn int_out_of_range(ty: &Type, min: i128, max: i128, span: &Span) -> Report<'static> {
+    let mut c = ColorGenerator::new();
+    let ty_color = c.next();
+    let ty = ty.fg(ty_color);
+
+    Report::build(ReportKind::Error, (), span.start)
+        .with_label(
+            Label::new(span.into_range())
+                .with_message(format!("This doesn't fit into `{ty}`"))
+                .with_color(ty_color),
+        )
+        .with_help(format!("`{ty}` ranges from `{min}` to `{max}`"))
+        .finish()
+}
+
+pub fn unformattable(ty: &Type, span: &Span) -> Report<'static> {
+    let mut c = ColorGenerator::new();
+    let ty_color = c.next();
+    let ty = ty.fg(ty_color);
+
+    Report::build(ReportKind::Error, (), span.start)
+        .with_label(
+            Label::new(span.into_range())
+                .with_message(format!("Values of type `{ty}` can't be formatted"))
+                .with_color(ty_color),
+        )
+        .with_help("Only numbers, `bool` and `string` can be formatted")
+        .finish()
+}
+
+pub fn not_callable(ty: &Type, span: &Span) -> Report<'static> {
+    let mut c = ColorGenerator::new();
+    let ty_color = c.next();
+    let ty = ty.fg(ty_color);
+
+    Report::build(ReportKind::Error, (), span.start)
+        .with_label(
+            Label::new(span.into_range())
+                .with_message(format!("Expected a function, found `{ty}`"))
+                .with_color(ty_color),
+        )
+        .finish()
+}
+
+pub fn not_indexable(ty: &Type, span: &Span) -> Report<'static> {
+    let mut c = ColorGenerator::new();
+    let ty_color = c.next();
+    let ty = ty.fg(ty_color);
+
+    Report::build(ReportKind::Error, (), span.start)
+        .with_label(
+            Label::new(span.into_range())
+                .with_message(format!("Expected an array, found `{ty}`"))
+                .with_color(ty_color),
+        )
+        .finish()
+}
+
+pub fn not_iterable(ty: &Type, span: &Span) -> Report<'static> {
+    let mut c = ColorGenerator::new();
+    let ty_color = c.next();
+    let ty = ty.fg(ty_color);
+
+    Report::build(ReportKind::Error, (), span.start)
+        .with_label(
+            Label::new(span.into_range())
+                .with_message(format!("Expected an array or a range, found `{ty}`"))
+                .with_color(ty_color),
+        )
+        .with_help("Only arrays and ranges like `0..n` can be iterated over")
+        .finish()
+}
+
+pub fn wrong_number_of_args(
+    expected: usize,
+    found: usize,
+    span: &Span,
+    fn_name_span: Option<Span>,
+) -> Report<'static> {
+    let mut c = ColorGenerator::new();
+    let call_color = c.next();
+    let fn_color = c.next();
+    let plural = |n| if n == 1 { "" } else { "s" };
+
+    let mut report = Report::build(ReportKind::Error, (), span.start).with_label(
+        Label::new(span.into_range())
+            .with_message(format!(
+                "Expected {expected} argument{}, found {found}",
+                plural(expected),
+            ))
+            .with_color(call_color),
+    );
+
+    if let Some(fn_name_span) = fn_name_span {
+        report = report.with_label(
+            Label::new(fn_name_span.into_range())
+                .with_message("Function declared here")
+                .with_color(fn_color),
+        );
+    }
+
+    report.finish()
+}
+
+/// Reports a field that doesn't exist on the struct or variant `name`.
+pub fn unknown_field(name: &str, field: &str, span: &Span, fields: &[&str]) -> Report<'static> {
+    let mut c = ColorGenerator::new();
+    let field_color = c.next();
+    let ty_color = c.next();
+    let field = field.fg(field_color);
+    let ty = name.fg(ty_color);
+
+    let mut report = Report::build(ReportKind::Error, (), span.start).with_label(
+        Label::new(span.into_range())
+            .with_message(format!("`{ty}` has no field `{field}`"))
+            .with_color(field_color),
+    );
+
+    if !fields.is_empty() {
+        report = report.with_help(format!("Available fields are {}", list(fields)));
+    }
+
+    report.finish()
+}
+
+/// Reports fields of the struct or variant `name` that are missing from a literal or pattern.
+pub fn missing_fields(
+    name: &str,
+    fields: &[&str],
+    span: &Span,
+    help: Option<String>,
+) -> Report<'static> {
+    let mut c = ColorGenerator::new();
+    let ty_color = c.next();
+    let ty = name.fg(ty_color);
+    let plural = if fields.len() == 1 { "" } else { "s" };
+
+    let mut report = Report::build(ReportKind::Error, (), span.start).with_label(
+        Label::new(span.into_range())
+            .with_message(format!("Missing field{plural} {} of `{ty}`", list(fields)))
+            .with_color(ty_color),
+    );
+
+    if let Some(help) = help {
+        report = report.with_help(help);
+    }
+
+    report.finish()
+}
+
+pub fn duplicate_field(field: &str, span: &Span, previous_span: &Span) -> Report<'static> {
+    let mut c = ColorGenerator::new();
+    let field_color = c.next();
+    let previous_color = c.next();
+    let field = field.fg(field_color);
+
+    Report::build(ReportKind::Error, (), span.start)
+        .with_label(
+            Label::new(span.into_range())
+                .with_message(format!("Field `{field}` is given more than once"))
+                .with_color(field_color),
+        )
+        .with_label(
+            Label::new(previous_span.into_range())
+                .with_message("First given here")
+                .with_color(previous_color),
+        )
+        .finish()
+}
+
+pub fn duplicate_type_param(param: &str, span: &Span, previous_span: &Span) -> Report<'static> {
+    let mut c = ColorGenerator::new();
+    let param_color = c.next();
+    let previous_color = c.next();
+    let param = param.fg(param_color);
+
+    Report::build(ReportKind::Error, (), span.start)
+        .with_label(
+            Label::new(span.into_range())
+                .with_message(format!(
+                    "Type parameter `{param}` is declared more than once"
+                ))
+                .with_color(param_color),
+        )
+        .with_label(
+            Label::new(previous_span.into_range())
+                .with_message("First declared here")
+                .with_color(previous_color),
+        )
+        .finish()
+}
+
+pub fn duplicate_binding(name: &str, span: &Span, previous_span: &Span) -> Report<'static> {
+    let mut c = ColorGenerator::new();
+    let name_color = c.next();
+    let previous_color = c.next();
+    let name = name.fg(name_color);
+
+    Report::build(ReportKind::Error, (), span.start)
+        .with_label(
+            Label::new(span.into_range())
+                .with_message(format!("`{name}` is bound more than once"))
+                .with_color(name_color),
+        )
+        .with_label(
+            Label::new(previous_span.into_range())
+                .with_message("First bound here")
+                .with_color(previous_color),
+        )
+        .finish()
+}
+
+pub fn duplicate_variant(variant: &str, span: &Span, previous_span: &Span) -> Report<'static> {
+    let mut c = ColorGenerator::new();
+    let variant_color = c.next();
+    let previous_color = c.next();
+    let variant = variant.fg(variant_color);
+
+    Report::build(ReportKind::Error, (), span.start)
+        .with_label(
+            Label::new(span.into_range())
+                .with_message(format!("Variant `{variant}` is declared more than once"))
+                .with_color(variant_color),
+        )
+        .with_label(
+            Label::new(previous_span.into_range())
+                .with_message("First declared here")
+                .with_color(previous_color),
+        )
+        .finish()
+}
+
+/// Reports a function, struct or enum sharing its name with another one.
+/// Structs and enums share their names, but functions don't share theirs with types.
+pub fn duplicate_item(name: &str, span: &Span, previous_span: &Span) -> Report<'static> {
+    let mut c = ColorGenerator::new();
+    let name_color = c.next();
+    let previous_color = c.next();
+    let name = name.fg(name_color);
+
+    Report::build(ReportKind::Error, (), span.start)
+        .with_label(
+            Label::new(span.into_range())
+                .with_message(format!("`{name}` is declared more than once"))
+                .with_color(name_color),
+        )
+        .with_label(
+            Label::new(previous_span.into_range())
+                .with_message("First declared here")
+                .with_color(previous_color),
+        )
+        .finish()
+}
+
+pub fn unknown_variant(
+    ty: &Type,
+    variant: &str,
+    span: &Span,
+    variants: &[&str],
+) -> Report<'static> {
+    let mut c = ColorGenerator::new();
+    let variant_color = c.next();
+    let ty_color = c.next();
+    let variant = variant.fg(variant_color);
+    let ty = ty.fg(ty_color);
+
+    let mut report = Report::build(ReportKind::Error, (), span.start).with_label(
+        Label::new(span.into_range())
+            .with_message(format!("`{ty}` has no variant `{variant}`"))
+            .with_color(variant_color),
+    );
+
+    if !variants.is_empty() {
+        report = report.with_help(format!("Available variants are {}", list(variants)));
+    }
+
+    report.finish()
+}
+
+/// Reports a variant used with the syntax of another kind of variant,
+/// like `Shape::Rect(1, 2)` for a variant with named fields.
+pub fn wrong_variant_syntax(
+    path: &str,
+    kind: &str,
+    expected_syntax: &str,
+    span: &Span,
+) -> Report<'static> {
+    let mut c = ColorGenerator::new();
+    let path_color = c.next();
+    let path = path.fg(path_color);
+
+    Report::build(ReportKind::Error, (), span.start)
+        .with_label(
+            Label::new(span.into_range())
+                .with_message(format!("`{path}` is a {kind}"))
+                .with_color(path_color),
+        )
+        .with_help(format!("Use `{expected_syntax}`"))
+        .finish()
+}
+
+pub fn update_of_variant(span: &Span) -> Report<'static> {
+    let mut c = ColorGenerator::new();
+
+    Report::build(ReportKind::Error, (), span.start)
+        .with_label(
+            Label::new(span.into_range())
+                .with_message("Remaining fields can only be copied into structs, not variants")
+                .with_color(c.next()),
+        )
+        .finish()
+}
+
+pub fn wrong_number_of_fields(
+    path: &str,
+    expected: usize,
+    found: usize,
+    span: &Span,
+) -> Report<'static> {
+    let mut c = ColorGenerator::new();
+    let path_color = c.next();
+    let path = path.fg(path_color);
+    let plural = if expected == 1 { "" } else { "s" };
+
+    Report::build(ReportKind::Error, (), span.start)
+        .with_label(
+            Label::new(span.into_range())
+                .with_message(format!(
+                    "`{path}` has {expected} field{plural}, but the pattern has {found}"
+                ))
+                .with_color(path_color),
+        )
+        .finish()
+}
+
+pub fn non_exhaustive_match(patterns: &[&str], span: &Span) -> Report<'static> {
+    let mut c = ColorGenerator::new();
+    let plural = if patterns.len() == 1 {
+        "Pattern"
+    } else {
+        "Patterns"
+    };
+
+    Report::build(ReportKind::Error, (), span.start)
+        .with_message("Non-exhaustive `match`")
+        .with_label(
+            Label::new(span.into_range())
+                .with_message(format!("{plural} {} not covered", list(patterns)))
+                .with_color(c.next()),
+        )
+        .with_help("Add arms for the missing patterns or a wildcard arm `_ => …`")
+        .finish()
+}
+
+pub fn unreachable_arm(span: &Span, covering_span: &Span) -> Report<'static> {
+    let mut c = ColorGenerator::new();
+
+    Report::build(ReportKind::Warning, (), span.start)
+        .with_message("Unreachable `match` arm")
+        .with_label(
+            Label::new(span.into_range())
+                .with_message("This arm is never matched")
+                .with_color(c.next()),
+        )
+        .with_label(
+            Label::new(covering_span.into_range())
+                .with_message("The arms up to this one match every value")
+                .with_color(c.next()),
+        )
+        .finish()
+}
+
+pub fn refutable_pattern(patterns: &[&str], span: &Span) -> Report<'static> {
+    let mut c = ColorGenerator::new();
+    let plural = if patterns.len() == 1 {
+        "Pattern"
+    } else {
+        "Patterns"
+    };
+
+    Report::build(ReportKind::Error, (), span.start)
+        .with_message("Refutable pattern")
+        .with_label(
+            Label::new(span.into_range())
+                .with_message(format!("{plural} {} not covered", list(patterns)))
+                .with_color(c.next()),
+        )
+        .with_help("Use `match` to handle the other values")
+        .finish()
+}
+
+/// Formats names like "`a`, `b` and `c`".
+fn list(names: &[&str]) -> String {
+    let names = names
+        .iter()
+        .map(|name| format!("`{name}`"))
+        .collect::<Vec<_>>();
+
+    match names.split_last() {
+        Some((last, [])) => last.clone(),
+        Some((last, init)) => format!("{} and {last}", init.join(", ")),
+        None => String::new(),
+    }
+}
+
+pub fn invalid_impl_type(ty: &Type, span: &Span) -> Report<'static> {
+    let mut c = ColorGenerator::new();
+    let ty_color = c.next();
+    let ty = ty.fg(ty_color);
+
+    Report::build(ReportKind::Error, (), span.start)
+        .with_label(
+            Label::new(span.into_range())
+                .with_message(format!("Can't declare methods for `{ty}`"))
+                .with_color(ty_color),
+        )
+        .with_help("Only structs and enums can have methods")
+        .finish()
+}
+
+/// Reports a method or associated function sharing its name with another one or a variant.
+pub fn duplicate_associated_item(
+    ty: &Type,
+    name: &str,
+    span: &Span,
+    previous_span: &Span,
+) -> Report<'static> {
+    let mut c = ColorGenerator::new();
+    let name_color = c.next();
+    let previous_color = c.next();
+    let name = name.fg(name_color);
+
+    Report::build(ReportKind::Error, (), span.start)
+        .with_label(
+            Label::new(span.into_range())
+                .with_message(format!("`{ty}::{name}` is declared more than once"))
+                .with_color(name_color),
+        )
+        .with_label(
+            Label::new(previous_span.into_range())
+                .with_message("First declared here")
+                .with_color(previous_color),
+        )
+        .finish()
+}
+
+pub fn receiver_outside_impl(span: &Span) -> Report<'static> {
+    let mut c = ColorGenerator::new();
+    let self_color = c.next();
+
+    Report::build(ReportKind::Error, (), span.start)
+        .with_label(
+            Label::new(span.into_range())
+                .with_message(format!(
+                    "`{}` is only allowed in methods",
+                    "self".fg(self_color)
+                ))
+                .with_color(self_color),
+        )
+        .with_help("Declare the function in an `impl` block")
+        .finish()
+}
+
+pub fn unknown_method(
+    ty: &Type,
+    method: &str,
+    span: &Span,
+    help: Option<String>,
+) -> Report<'static> {
+    let mut c = ColorGenerator::new();
+    let method_color = c.next();
+    let ty_color = c.next();
+    let method = method.fg(method_color);
+    let ty = ty.fg(ty_color);
+
+    let mut report = Report::build(ReportKind::Error, (), span.start).with_label(
+        Label::new(span.into_range())
+            .with_message(format!("`{ty}` has no method `{method}`"))
+            .with_color(method_color),
+    );
+
+    if let Some(help) = help {
+        report = report.with_help(help);
+    }
+
+    report.finish()
+}
+
+pub fn assignment_to_immutable(name: &str, span: &Span, decl_span: &Span) -> Report<'static> {
+    let mut c = ColorGenerator::new();
+    let name_color = c.next();
+    let decl_color = c.next();
+    let name = name.fg(name_color);
+
+    Report::build(ReportKind::Error, (), span.start)
+        .with_label(
+            Label::new(span.into_range())
+                .with_message(format!("Variable `{name}` is immutable"))
+                .with_color(name_color),
+        )
+        .with_label(
+            Label::new(decl_span.into_range())
+                .with_message("Variable declared here")
+                .with_color(decl_color),
+        )
+        .with_help(format!("Use `let mut {name} = …;`"))
+        .finish()
+}
+
+pub fn invalid_assignment_target(span: &Span) -> Report<'static> {
+    let mut c = ColorGenerator::new();
+
+    Report::build(ReportKind::Error, (), span.start)
+        .with_label(
+            Label::new(span.into_range())
+                .with_message("Only variables can be assigned to")
+                .with_color(c.next()),
+        )
+        .finish()
+}
+
+pub fn outside_of_loop(keyword: &str, span: &Span) -> Report<'static> {
+    let mut c = ColorGenerator::new();
+    let keyword_color = c.next();
+    let keyword = keyword.fg(keyword_color);
+
+    Report::build(ReportKind::Error, (), span.start)
+        .with_label(
+            Label::new(span.into_range())
+                .with_message(format!("`{keyword}` can only be used inside of a loop"))
+                .with_color(keyword_color),
+        )
+        .finish()
+}
+
+pub fn undeclared_label(name: &str, span: &Span) -> Report<'static> {
+    let mut c = ColorGenerator::new();
+    let name_color = c.next();
+    let name = name.fg(name_color);
+
+    Report::build(ReportKind::Error, (), span.start)
+        .with_label(
+            Label::new(span.into_range())
+                .with_message(format!(
+                    "Label `'{name}` is not declared by any enclosing loop"
+                ))
+                .with_color(name_color),
+        )
+        .with_help(format!("Use `'{name}: loop {{ … }}`"))
+        .finish()
+}
+
+pub fn break_with_value(keyword: &str, span: &Span) -> Report<'static> {
+    let mut c = ColorGenerator::new();
+
+    Report::build(ReportKind::Error, (), span.start)
+        .with_label(
+            Label::new(span.into_range())
+                .with_message("`break` can only take a value inside of `loop`")
+                .with_color(c.next()),
+        )
+        .with_help(format!(
+            "Remove the value or use `loop` instead of `{keyword}`"
+        ))
+        .finish()
+}
+
+/// Reports a type given `found` type arguments where `name` takes `expected` ones.
+pub fn wrong_number_of_type_args(
+    name: &str,
+    expected: usize,
+    found: usize,
+    span: &Span,
+) -> Report<'static> {
+    let mut c = ColorGenerator::new();
+    let ty_color = c.next();
+    let ty = name.fg(ty_color);
+    let plural = if expected == 1 { "" } else { "s" };
+
+    Report::build(ReportKind::Error, (), span.start)
+        .with_label(
+            Label::new(span.into_range())
+                .with_message(format!(
+                    "`{ty}` takes {expected} type argument{plural}, found {found}"
+                ))
+                .with_color(ty_color),
+        )
+        .finish()
+}
+
+/// Reports a value of a generic enum whose type arguments can't be inferred,
+/// like the `Option<_>` of `None`.
+pub fn type_args_needed(ty: &str, example: &str, span: &Span) -> Report<'static> {
+    let mut c = ColorGenerator::new();
+    let ty_color = c.next();
+    let ty = ty.fg(ty_color);
+
+    Report::build(ReportKind::Error, (), span.start)
+        .with_label(
+            Label::new(span.into_range())
+                .with_message(format!("The type `{ty}` can't be inferred"))
+                .with_color(ty_color),
+        )
+        .with_help(format!("Annotate the type, like `let x: {example} = …;`"))
+        .finish()
+}
+
+/// Reports a `?` applied to a value of type `ty` in a function returning `return_type`.
+pub fn invalid_try(
+    ty: &Type,
+    return_type: &Type,
+    return_span: Option<&Span>,
+    span: &Span,
+) -> Report<'static> {
+    let mut c = ColorGenerator::new();
+    let ty_color = c.next();
+    let return_color = c.next();
+    let ty = ty.fg(ty_color);
+    let return_type = return_type.fg(return_color);
+
+    let mut report = Report::build(ReportKind::Error, (), span.start).with_label(
+        Label::new(span.into_range())
+            .with_message(format!(
+                "`?` on `{ty}` can't return early from a function returning `{return_type}`"
+            ))
+            .with_color(ty_color),
+    );
+
+    if let Some(return_span) = return_span {
+        report = report.with_label(
+            Label::new(return_span.into_range())
+                .with_message(format!(
+                    "The function returns `{return_type}` because of this"
+                ))
+                .with_color(return_color),
+        );
+    }
+
+    report
+        .with_help("`?` can only be used in functions returning `Option` or `Result`")
+        .finish()
+}
+
+/// Reports a `?` in a closure whose return type isn't known yet.
+pub fn try_without_return_type(span: &Span) -> Report<'static> {
+    let mut c = ColorGenerator::new();
+
+    Report::build(ReportKind::Error, (), span.start)
+        .with_label(
+            Label::new(span.into_range())
+                .with_message("The return type of this closure can't be inferred")
+                .with_color(c.next()),
+        )
+        .with_help("Annotate the return type, like `|x: i32| -> Option<i32> { … }`")
+        .finish()
+}
+
+pub fn parse_err(err: Rich<char>) -> Report<'static> {
+    let is_custom = |reason: &RichReason<char>| matches!(reason, RichReason::Custom(_));
+    let mut c = ColorGenerator::new();
+    let span = err.span();
+
+    // Alternatives that fail at the same place for different reasons are merged,
+    // and custom reasons like a misused keyword are the most specific ones
+    let reasons = match err.reason() {
+        RichReason::Many(reasons) if reasons.iter().any(is_custom) => {
+            reasons.iter().filter(|reason| is_custom(reason)).collect()
+        }
+        RichReason::Many(reasons) => reasons.iter().collect(),
+        reason => vec![reason],
+    };
+    let mut messages = Vec::new();
+
+    for reason in reasons {
+        let message = reason.to_string();
+
+        if !messages.contains(&message) {
+            messages.push(message);
+        }
+    }
+
+    Report::build(ReportKind::Error, (), span.start)
+        .with_labels(messages.into_iter().map(|message| {
+            Label::new(span.into_range())
+                .with_message(message)
+                .with_color(c.next())
+        }))
         .finish()
 }
@@ -1,19 +1,35 @@
 mod block;
+pub mod closure;
+pub mod r#enum;
 mod expr;
 mod file;
 pub mod r#fn;
+pub mod format;
 mod ident;
+mod r#impl;
 mod item;
 mod r#let;
+pub mod r#match;
+mod path;
+pub mod pattern;
 mod span;
 mod stmt;
+pub mod r#struct;
+pub mod r#type;
 
 pub use block::Block;
-pub use expr::Expr;
+pub use closure::Closure;
+pub use expr::{Expr, ExprKind};
 pub use file::File;
 pub use ident::Ident;
 pub use item::Item;
+pub use path::Path;
+pub use pattern::Pattern;
+pub use r#enum::Enum;
 pub use r#fn::Fn;
+pub use r#impl::Impl;
 pub use r#let::Let;
+pub use r#struct::Struct;
+pub use r#type::Type;
 pub use span::Span;
 pub use stmt::Stmt;
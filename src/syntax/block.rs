@@ -1,6 +1,21 @@
-use crate::syntax::Stmt;
+use crate::syntax::{Expr, Span, Stmt};
 
 #[derive(Debug)]
 pub struct Block<'a> {
     pub stmts: Vec<Stmt<'a>>,
+    /// The trailing expression without `;`, which is the value of the block.
+    pub expr: Option<Box<Expr<'a>>>,
+    pub span: Span,
+}
+
+impl Block<'_> {
+    /// Whether the block needs statements to be expressed in JS.
+    pub fn contains_block(&self) -> bool {
+        !self.stmts.is_empty() || self.expr.as_ref().is_none_or(|expr| expr.contains_block())
+    }
+
+    /// The span of the expression producing the value of the block.
+    pub fn value_span(&self) -> Span {
+        self.expr.as_ref().map_or(self.span, |expr| expr.span)
+    }
 }
@@ -0,0 +1,15 @@
+use crate::syntax::{Expr, Pattern, Type};
+
+#[derive(Debug)]
+pub struct Closure<'a> {
+    pub args: Vec<Arg<'a>>,
+    pub return_type: Option<Type<'a>>,
+    pub body: Box<Expr<'a>>,
+}
+
+#[derive(Debug)]
+pub struct Arg<'a> {
+    pub pattern: Pattern<'a>,
+    /// Taken from the expected type of the closure if omitted
+    pub r#type: Option<Type<'a>>,
+}
@@ -0,0 +1,30 @@
+use crate::syntax::r#struct::Field;
+use crate::syntax::{Ident, Type};
+
+#[derive(Debug)]
+pub struct Enum<'a> {
+    /// The lines of the `///` doc comments, without the `///`
+    pub doc: Vec<&'a str>,
+    pub name: Ident<'a>,
+    /// Like `T` in `enum Option<T>`
+    pub type_params: Vec<Ident<'a>>,
+    pub variants: Vec<Variant<'a>>,
+}
+
+#[derive(Debug)]
+pub struct Variant<'a> {
+    /// The lines of the `///` doc comments, without the `///`
+    pub doc: Vec<&'a str>,
+    pub name: Ident<'a>,
+    pub fields: VariantFields<'a>,
+}
+
+#[derive(Debug)]
+pub enum VariantFields<'a> {
+    /// `Empty`
+    Unit,
+    /// `Circle(f64)`
+    Tuple(Vec<Type<'a>>),
+    /// `Rect { w: f64, h: f64 }`
+    Struct(Vec<Field<'a>>),
+}
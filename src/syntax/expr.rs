@@ -1,15 +1,37 @@
-use crate::syntax::{Block, Ident, Span};
+use crate::syntax::format::FormatPart;
+use crate::syntax::r#match::Arm;
+use crate::syntax::r#struct::FieldInit;
+use crate::syntax::{Block, Closure, Ident, Path, Span};
 
 #[derive(Debug)]
-pub enum Expr<'a> {
-    // TODO: use i53
-    // https://developer.mozilla.org/en-US/docs/Web/JavaScript/Reference/Global_Objects/Number/MAX_SAFE_INTEGER
-    Int(i32),
-    Parens(Box<Expr<'a>>),
-    Var {
-        name: Ident<'a>,
-        name_span: Span,
+pub struct Expr<'a> {
+    pub kind: ExprKind<'a>,
+    pub span: Span,
+}
+
+#[derive(Debug)]
+pub enum ExprKind<'a> {
+    /// An integer literal, whose type is given by its suffix or else by the context.
+    /// Negative literals are negations of positive ones.
+    Int {
+        value: u64,
+        suffix: Option<&'a str>,
     },
+    Float(f64),
+    Bool(bool),
+    /// A string literal, with its escapes resolved
+    String(String),
+    /// An interpolated string like `f"x = {x}"`
+    Format(Vec<FormatPart<'a>>),
+    /// A placeholder for an expression that failed to parse,
+    /// so that the rest of the file can still be checked
+    Error,
+    Parens(Box<Expr<'a>>),
+    /// A tuple like `(a, b)` or `(a,)`
+    Tuple(Vec<Expr<'a>>),
+    Var(Ident<'a>),
+    /// A path with more than one segment, like `Shape::Empty`
+    Path(Path<'a>),
 
     Neg(Box<Expr<'a>>),
     Add(Box<Expr<'a>>, Box<Expr<'a>>),
@@ -17,33 +39,164 @@ pub enum Expr<'a> {
     Mul(Box<Expr<'a>>, Box<Expr<'a>>),
     Div(Box<Expr<'a>>, Box<Expr<'a>>),
 
-    Call(String, Vec<Expr<'a>>),
-    Fn {
-        name: &'a str,
-        args: Vec<String>,
-        body: Box<Expr<'a>>,
-        then: Box<Expr<'a>>,
+    Eq(Box<Expr<'a>>, Box<Expr<'a>>),
+    Ne(Box<Expr<'a>>, Box<Expr<'a>>),
+    Lt(Box<Expr<'a>>, Box<Expr<'a>>),
+    Le(Box<Expr<'a>>, Box<Expr<'a>>),
+    Gt(Box<Expr<'a>>, Box<Expr<'a>>),
+    Ge(Box<Expr<'a>>, Box<Expr<'a>>),
+
+    Not(Box<Expr<'a>>),
+    And(Box<Expr<'a>>, Box<Expr<'a>>),
+    Or(Box<Expr<'a>>, Box<Expr<'a>>),
+    Assign(Box<Expr<'a>>, Box<Expr<'a>>),
+    AddAssign(Box<Expr<'a>>, Box<Expr<'a>>),
+    SubAssign(Box<Expr<'a>>, Box<Expr<'a>>),
+    MulAssign(Box<Expr<'a>>, Box<Expr<'a>>),
+    DivAssign(Box<Expr<'a>>, Box<Expr<'a>>),
+
+    Call(Box<Expr<'a>>, Vec<Expr<'a>>),
+    /// A method call like `p.len()`
+    MethodCall {
+        receiver: Box<Expr<'a>>,
+        method: Ident<'a>,
+        args: Vec<Expr<'a>>,
+    },
+    /// A struct literal like `Point { x: 1, ..p }` or `Shape::Rect { w: 1, h: 2 }`
+    Struct {
+        path: Path<'a>,
+        fields: Vec<FieldInit<'a>>,
+        /// The struct that the remaining fields are copied from
+        base: Option<Box<Expr<'a>>>,
+    },
+    Field(Box<Expr<'a>>, Ident<'a>),
+    /// An array literal like `[1, 2, 3]`
+    Array(Vec<Expr<'a>>),
+    /// An array of `count` copies of `value`, like `[0; n]`
+    Repeat {
+        value: Box<Expr<'a>>,
+        count: Box<Expr<'a>>,
+    },
+    Index(Box<Expr<'a>>, Box<Expr<'a>>),
+    /// `start..end`, or `start..=end` if `inclusive`
+    Range {
+        start: Box<Expr<'a>>,
+        end: Box<Expr<'a>>,
+        inclusive: bool,
     },
+    Closure(Closure<'a>),
     Block(Block<'a>),
+    If {
+        cond: Box<Expr<'a>>,
+        then: Block<'a>,
+        /// Either an [`ExprKind::Block`] or an [`ExprKind::If`]
+        r#else: Option<Box<Expr<'a>>>,
+    },
+    Loop {
+        label: Option<Ident<'a>>,
+        body: Block<'a>,
+    },
+    While {
+        label: Option<Ident<'a>>,
+        cond: Box<Expr<'a>>,
+        body: Block<'a>,
+    },
+    /// A loop over the elements of an array or a range
+    For {
+        label: Option<Ident<'a>>,
+        var: Ident<'a>,
+        iter: Box<Expr<'a>>,
+        body: Block<'a>,
+    },
+    Break {
+        label: Option<Ident<'a>>,
+        value: Option<Box<Expr<'a>>>,
+    },
+    Continue {
+        label: Option<Ident<'a>>,
+    },
+    Return(Option<Box<Expr<'a>>>),
+    /// `expr?`, which returns early with a `None` or an `Err`
+    Try(Box<Expr<'a>>),
+    Match {
+        scrutinee: Box<Expr<'a>>,
+        arms: Vec<Arm<'a>>,
+    },
 }
 
-impl Expr<'_> {
+impl<'a> Expr<'a> {
+    pub fn new(kind: ExprKind<'a>, span: Span) -> Self {
+        Self { kind, span }
+    }
+
+    /// Whether the expression needs statements to be expressed in JS.
     pub fn contains_block(&self) -> bool {
-        match self {
-            Expr::Int(_) => false,
-            Expr::Parens(expr) => expr.contains_block(),
-            Expr::Var {
-                name: _,
-                name_span: _,
-            } => false,
-            Expr::Neg(expr) => expr.contains_block(),
-            Expr::Add(lhs, rhs) => lhs.contains_block() || rhs.contains_block(),
-            Expr::Sub(lhs, rhs) => lhs.contains_block() || rhs.contains_block(),
-            Expr::Mul(lhs, rhs) => lhs.contains_block() || rhs.contains_block(),
-            Expr::Div(lhs, rhs) => lhs.contains_block() || rhs.contains_block(),
-            Expr::Call(_, args) => args.iter().any(Expr::contains_block),
-            Expr::Fn { .. } => todo!("Move FN decls from Expr to Stmt"),
-            Expr::Block(_) => true,
+        match &self.kind {
+            ExprKind::Int { .. } => false,
+            ExprKind::Float(_) => false,
+            ExprKind::Bool(_) => false,
+            ExprKind::String(_) => false,
+            ExprKind::Format(parts) => parts.iter().any(|part| match part {
+                FormatPart::Str(_) => false,
+                FormatPart::Expr(expr) => expr.contains_block(),
+            }),
+            ExprKind::Error => false,
+            ExprKind::Parens(expr) => expr.contains_block(),
+            ExprKind::Tuple(elements) => elements.iter().any(Expr::contains_block),
+            ExprKind::Var(_) => false,
+            ExprKind::Path(_) => false,
+            ExprKind::Neg(expr) => expr.contains_block(),
+            ExprKind::Add(lhs, rhs) => lhs.contains_block() || rhs.contains_block(),
+            ExprKind::Sub(lhs, rhs) => lhs.contains_block() || rhs.contains_block(),
+            ExprKind::Mul(lhs, rhs) => lhs.contains_block() || rhs.contains_block(),
+            ExprKind::Div(lhs, rhs) => lhs.contains_block() || rhs.contains_block(),
+            ExprKind::Eq(lhs, rhs) => lhs.contains_block() || rhs.contains_block(),
+            ExprKind::Ne(lhs, rhs) => lhs.contains_block() || rhs.contains_block(),
+            ExprKind::Lt(lhs, rhs) => lhs.contains_block() || rhs.contains_block(),
+            ExprKind::Le(lhs, rhs) => lhs.contains_block() || rhs.contains_block(),
+            ExprKind::Gt(lhs, rhs) => lhs.contains_block() || rhs.contains_block(),
+            ExprKind::Ge(lhs, rhs) => lhs.contains_block() || rhs.contains_block(),
+            ExprKind::Not(expr) => expr.contains_block(),
+            ExprKind::And(lhs, rhs) => lhs.contains_block() || rhs.contains_block(),
+            ExprKind::Or(lhs, rhs) => lhs.contains_block() || rhs.contains_block(),
+            // Assignments are translated to JS statements, since their value is `()`
+            ExprKind::Assign(..)
+            | ExprKind::AddAssign(..)
+            | ExprKind::SubAssign(..)
+            | ExprKind::MulAssign(..)
+            | ExprKind::DivAssign(..) => true,
+            ExprKind::Call(callee, args) => {
+                callee.contains_block() || args.iter().any(Expr::contains_block)
+            }
+            ExprKind::MethodCall { receiver, args, .. } => {
+                receiver.contains_block() || args.iter().any(Expr::contains_block)
+            }
+            ExprKind::Struct { fields, base, .. } => {
+                fields.iter().any(|field| field.value.contains_block())
+                    || base.as_ref().is_some_and(|base| base.contains_block())
+            }
+            ExprKind::Field(expr, _) => expr.contains_block(),
+            ExprKind::Array(elements) => elements.iter().any(Expr::contains_block),
+            ExprKind::Repeat { value, count } => value.contains_block() || count.contains_block(),
+            ExprKind::Index(expr, index) => expr.contains_block() || index.contains_block(),
+            ExprKind::Range { start, end, .. } => start.contains_block() || end.contains_block(),
+            // The body becomes part of the JS function
+            ExprKind::Closure(_) => false,
+            ExprKind::Block(block) => block.contains_block(),
+            ExprKind::If { cond, then, r#else } => {
+                cond.contains_block()
+                    || then.contains_block()
+                    || r#else.as_ref().is_none_or(|r#else| r#else.contains_block())
+            }
+            ExprKind::Loop { .. } => true,
+            ExprKind::While { .. } => true,
+            ExprKind::For { .. } => true,
+            ExprKind::Break { .. } => true,
+            ExprKind::Continue { .. } => true,
+            ExprKind::Return(_) => true,
+            // The early return is a JS statement
+            ExprKind::Try(_) => true,
+            ExprKind::Match { .. } => true,
         }
     }
 
@@ -2,5 +2,7 @@ use crate::syntax::Item;
 
 #[derive(Debug)]
 pub struct File<'a> {
+    /// The lines of the `//!` doc comments at the start of the file, without the `//!`
+    pub doc: Vec<&'a str>,
     pub items: Vec<Item<'a>>,
 }
@@ -1,15 +1,28 @@
-use crate::syntax::{Block, Ident};
+use crate::syntax::{Block, Ident, Pattern, Type};
 
 #[derive(Debug)]
 pub struct Fn<'a> {
+    /// The lines of the `///` doc comments, without the `///`
+    pub doc: Vec<&'a str>,
     pub name: Ident<'a>,
+    /// Like `T` in `fn id<T>(x: T) -> T`
+    pub type_params: Vec<Ident<'a>>,
+    /// The `self` argument, which makes the function a method
+    pub receiver: Option<Receiver<'a>>,
     pub args: Vec<Arg<'a>>,
-    pub return_type: Option<Ident<'a>>,
+    pub return_type: Option<Type<'a>>,
     pub body: Block<'a>,
 }
 
 #[derive(Debug)]
 pub struct Arg<'a> {
+    pub pattern: Pattern<'a>,
+    pub r#type: Type<'a>,
+}
+
+/// `self` or `mut self`
+#[derive(Debug)]
+pub struct Receiver<'a> {
     pub name: Ident<'a>,
-    pub r#type: Ident<'a>,
+    pub mutable: bool,
 }
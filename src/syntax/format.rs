@@ -0,0 +1,10 @@
+use crate::syntax::Expr;
+
+/// A part of an interpolated string like `f"x = {x}"`
+#[derive(Debug)]
+pub enum FormatPart<'a> {
+    /// Text with its escapes resolved
+    Str(String),
+    /// An expression between braces, whose value gets inserted
+    Expr(Expr<'a>),
+}
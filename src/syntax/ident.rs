@@ -1,15 +1,29 @@
+use std::borrow::Cow;
 use std::ops;
 
-#[derive(Debug)]
-pub struct Ident<'a>(&'a str);
+use crate::syntax::Span;
+
+#[derive(Debug, Clone)]
+pub struct Ident<'a> {
+    /// The name in NFC, which is only owned if the source has another normalization form
+    ident: Cow<'a, str>,
+    span: Span,
+}
 
 impl<'a> Ident<'a> {
-    pub fn new(ident: &'a str) -> Self {
-        Self(ident)
+    pub fn new(ident: impl Into<Cow<'a, str>>, span: Span) -> Self {
+        Self {
+            ident: ident.into(),
+            span,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.ident
     }
 
-    pub fn as_str(&self) -> &'a str {
-        self.0
+    pub fn span(&self) -> Span {
+        self.span
     }
 }
 
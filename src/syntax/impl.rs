@@ -0,0 +1,15 @@
+use crate::syntax::{Fn, Ident, Type};
+
+/// An `impl` block declaring the methods and associated functions of a struct or enum
+#[derive(Debug)]
+pub struct Impl<'a> {
+    /// The lines of the `///` doc comments, without the `///`
+    pub doc: Vec<&'a str>,
+    /// The type parameters of a generic `impl` block like `impl<T> Wrap<T>`,
+    /// which its functions can refer to
+    pub type_params: Vec<Ident<'a>>,
+    pub self_type: Ident<'a>,
+    /// The type arguments of the type, like `T` in `impl<T> Wrap<T>`
+    pub type_args: Vec<Type<'a>>,
+    pub fns: Vec<Fn<'a>>,
+}
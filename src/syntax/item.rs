@@ -1,6 +1,9 @@
-use crate::syntax::Fn;
+use crate::syntax::{Enum, Fn, Impl, Struct};
 
 #[derive(Debug)]
 pub enum Item<'a> {
     Fn(Fn<'a>),
+    Struct(Struct<'a>),
+    Enum(Enum<'a>),
+    Impl(Impl<'a>),
 }
@@ -1,8 +1,9 @@
-use crate::syntax::{Expr, Ident};
+use crate::syntax::{Expr, Pattern, Type};
 
 #[derive(Debug)]
 pub struct Let<'a> {
-    pub name: Ident<'a>,
-    pub ty: Option<Ident<'a>>,
+    /// A pattern that matches every value, like `(x, y)` or `mut x`
+    pub pattern: Pattern<'a>,
+    pub ty: Option<Type<'a>>,
     pub rhs: Expr<'a>,
 }
@@ -0,0 +1,9 @@
+use crate::syntax::{Expr, Pattern};
+
+/// An arm of a `match`, like `Shape::Circle(r) if r > 0 => r * r`
+#[derive(Debug)]
+pub struct Arm<'a> {
+    pub pattern: Pattern<'a>,
+    pub guard: Option<Expr<'a>>,
+    pub body: Expr<'a>,
+}
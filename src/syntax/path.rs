@@ -0,0 +1,45 @@
+use std::fmt;
+
+use crate::syntax::{Ident, Span};
+
+/// A path like `Shape::Circle`
+#[derive(Debug, Clone)]
+pub struct Path<'a> {
+    /// Never empty
+    pub segments: Vec<Ident<'a>>,
+}
+
+impl<'a> Path<'a> {
+    pub fn span(&self) -> Span {
+        let first = self.segments.first().expect("BUG: empty path");
+
+        Span::new(first.span().start, self.last().span().end)
+    }
+
+    /// The last segment, like `Circle` in `Shape::Circle`.
+    pub fn last(&self) -> &Ident<'a> {
+        self.segments.last().expect("BUG: empty path")
+    }
+
+    /// The path as a single identifier, if it has only one segment.
+    pub fn as_ident(&self) -> Option<&Ident<'a>> {
+        match self.segments.as_slice() {
+            [ident] => Some(ident),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Path<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, segment) in self.segments.iter().enumerate() {
+            if i > 0 {
+                write!(f, "::")?;
+            }
+
+            write!(f, "{}", segment.as_str())?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,85 @@
+use crate::syntax::{Ident, Path, Span};
+
+#[derive(Debug)]
+pub struct Pattern<'a> {
+    pub kind: PatternKind<'a>,
+    pub span: Span,
+}
+
+#[derive(Debug)]
+pub enum PatternKind<'a> {
+    /// `_`
+    Wildcard,
+    /// A variable bound to the matched value, like `x` or `mut x`
+    Binding {
+        name: Ident<'a>,
+        mutable: bool,
+    },
+    /// An integer literal, which can be negative
+    Int {
+        value: i64,
+        suffix: Option<&'a str>,
+    },
+    Bool(bool),
+    /// A unit variant like `Shape::Empty`
+    Path(Path<'a>),
+    /// A tuple like `(x, _)`
+    Tuple(Vec<Pattern<'a>>),
+    /// A tuple variant like `Shape::Circle(r)`
+    TupleVariant(Path<'a>, Vec<Pattern<'a>>),
+    /// A struct or struct variant like `Shape::Rect { w, h: 0, .. }`
+    Struct {
+        path: Path<'a>,
+        fields: Vec<FieldPattern<'a>>,
+        /// Whether the remaining fields are ignored with `..`
+        rest: bool,
+    },
+}
+
+/// A field of a struct pattern, like `h: 0`
+#[derive(Debug)]
+pub struct FieldPattern<'a> {
+    pub name: Ident<'a>,
+    pub pattern: Pattern<'a>,
+}
+
+impl<'a> Pattern<'a> {
+    pub fn new(kind: PatternKind<'a>, span: Span) -> Self {
+        Self { kind, span }
+    }
+
+    /// Whether any variable bound by the pattern is mutable.
+    pub fn is_mutable(&self) -> bool {
+        match &self.kind {
+            PatternKind::Binding { mutable, .. } => *mutable,
+            PatternKind::Tuple(patterns) | PatternKind::TupleVariant(_, patterns) => {
+                patterns.iter().any(Pattern::is_mutable)
+            }
+            PatternKind::Struct { fields, .. } => {
+                fields.iter().any(|field| field.pattern.is_mutable())
+            }
+            PatternKind::Wildcard
+            | PatternKind::Int { .. }
+            | PatternKind::Bool(_)
+            | PatternKind::Path(_) => false,
+        }
+    }
+
+    /// The names that the pattern binds, in order.
+    pub fn bindings(&self) -> Vec<&Ident<'a>> {
+        match &self.kind {
+            PatternKind::Binding { name, .. } => vec![name],
+            PatternKind::Tuple(patterns) | PatternKind::TupleVariant(_, patterns) => {
+                patterns.iter().flat_map(Pattern::bindings).collect()
+            }
+            PatternKind::Struct { fields, .. } => fields
+                .iter()
+                .flat_map(|field| field.pattern.bindings())
+                .collect(),
+            PatternKind::Wildcard
+            | PatternKind::Int { .. }
+            | PatternKind::Bool(_)
+            | PatternKind::Path(_) => Vec::new(),
+        }
+    }
+}
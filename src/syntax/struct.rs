@@ -0,0 +1,26 @@
+use crate::syntax::{Expr, Ident, Type};
+
+#[derive(Debug)]
+pub struct Struct<'a> {
+    /// The lines of the `///` doc comments, without the `///`
+    pub doc: Vec<&'a str>,
+    pub name: Ident<'a>,
+    /// Like `T` in `struct Wrapper<T>`
+    pub type_params: Vec<Ident<'a>>,
+    pub fields: Vec<Field<'a>>,
+}
+
+#[derive(Debug)]
+pub struct Field<'a> {
+    /// The lines of the `///` doc comments, without the `///`
+    pub doc: Vec<&'a str>,
+    pub name: Ident<'a>,
+    pub r#type: Type<'a>,
+}
+
+/// A field of a struct literal, like `x: 1`
+#[derive(Debug)]
+pub struct FieldInit<'a> {
+    pub name: Ident<'a>,
+    pub value: Expr<'a>,
+}
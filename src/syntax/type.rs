@@ -0,0 +1,27 @@
+use crate::syntax::{Path, Span};
+
+/// A type annotation, like `i32` or `[Point]`
+#[derive(Debug)]
+pub struct Type<'a> {
+    pub kind: TypeKind<'a>,
+    pub span: Span,
+}
+
+#[derive(Debug)]
+pub enum TypeKind<'a> {
+    /// A built-in type, a struct, an enum or a type parameter,
+    /// with type arguments like `Option<i32>`
+    Named(Path<'a>, Vec<Type<'a>>),
+    /// An array like `[i32]`
+    Array(Box<Type<'a>>),
+    /// A tuple like `(i32, bool)`, or `()` without elements
+    Tuple(Vec<Type<'a>>),
+    /// A function type like `fn(i32) -> bool`, which returns `()` without a return type
+    Fn(Vec<Type<'a>>, Option<Box<Type<'a>>>),
+}
+
+impl<'a> Type<'a> {
+    pub fn new(kind: TypeKind<'a>, span: Span) -> Self {
+        Self { kind, span }
+    }
+}
@@ -1,29 +1,107 @@
 use std::borrow::Cow;
+use std::collections::{BTreeSet, HashMap};
+use std::iter;
 
-use ariadne::Report;
+use crate::syntax::format::FormatPart;
+use crate::syntax::pattern::PatternKind;
+use crate::syntax::r#enum::VariantFields;
+use crate::syntax::r#match::Arm;
+use crate::syntax::r#struct::{Field, FieldInit};
+use crate::syntax::{
+    Block, Closure, Expr, ExprKind, File, Fn, Ident, Item, Let, Path, Pattern, Span, Stmt, Struct,
+};
+use crate::typeck::{Type, Types};
+use crate::{js, prelude};
 
-use crate::syntax::{Block, Expr, File, Fn, Item, Let, Span, Stmt};
-use crate::{js, report};
+pub mod names;
+mod runtime;
+
+use names::Names;
+use runtime::Helper;
+
+/// The property of enum values holding the name of their variant.
+/// `$` can't appear in Jester identifiers, so it never collides with a field.
+const TAG: &str = "$tag";
+
+/// The functions of the `impl` blocks of each type
+type Impls<'a> = HashMap<&'a str, Vec<&'a Fn<'a>>>;
 
 pub struct Trans {
     scopes: Vec<Scope>,
+    names: Names,
+    temporaries: usize,
+    loops: Vec<Loop>,
+    types: Types,
+    /// JS class names of structs and of enums with methods
+    classes: HashMap<String, String>,
+    /// JS names of the functions and classes, by the span of their declared name,
+    /// so that each declaration keeps the name it was given
+    item_names: HashMap<Span, String>,
+    /// Number of fields of tuple variants, by paths like `Shape::Circle`
+    tuple_variants: HashMap<String, usize>,
+    /// Methods and associated functions, by paths like `Point::new`
+    assoc_fns: HashMap<String, AssocFn>,
+    /// Runtime helpers used by the translated code
+    helpers: BTreeSet<Helper>,
+    /// Whether indexing checks that the index is in bounds instead of returning `undefined`
+    bounds_checks: bool,
 }
 
 impl Trans {
-    pub fn new(span: Span) -> Self {
+    pub fn new(types: Types) -> Self {
         Self {
-            scopes: vec![Scope::new(span)],
+            scopes: vec![Scope::new()],
+            names: Names::new(),
+            temporaries: 0,
+            loops: Vec::new(),
+            types,
+            classes: HashMap::new(),
+            item_names: HashMap::new(),
+            tuple_variants: HashMap::new(),
+            assoc_fns: HashMap::new(),
+            helpers: BTreeSet::new(),
+            bounds_checks: false,
         }
     }
 
+    /// Makes out of bounds indexing throw a `RangeError`, like a panic in Rust.
+    pub fn with_bounds_checks(mut self, bounds_checks: bool) -> Self {
+        self.bounds_checks = bounds_checks;
+        self
+    }
+
+    /// Returns a call of a runtime helper, which gets emitted with the file.
+    fn call_helper<'a>(&mut self, helper: Helper, args: Vec<js::Expr<'a>>) -> js::Expr<'a> {
+        self.helpers.insert(helper);
+
+        js::Expr::Call(js::Expr::Var(Cow::Borrowed(helper.name())).boxed(), args)
+    }
+
     fn last_scope_mut(&mut self) -> &mut Scope {
         self.scopes
             .last_mut()
             .expect("BUG: expected at least one scope")
     }
 
-    fn declare_variable<'a>(&mut self, jester_name: &'a str) -> Cow<'a, str> {
-        self.last_scope_mut().declare_variable(jester_name)
+    fn declare_variable<'a>(&mut self, jester_name: &'a str, mutable: bool) -> Cow<'a, str> {
+        let js_name = self.names.declare(jester_name);
+        self.last_scope_mut()
+            .declare_variable(jester_name, &js_name, mutable)
+    }
+
+    fn declare_arg<'a>(&mut self, jester_name: &'a str, mutable: bool) -> Cow<'a, str> {
+        let js_name = self.names.declare_arg(jester_name);
+        self.last_scope_mut()
+            .declare_variable(jester_name, &js_name, mutable)
+    }
+
+    /// Declares a JS-only variable for holding intermediate values.
+    /// `$` can't appear in Jester identifiers, so these never collide with user variables.
+    fn declare_temporary<'a>(&mut self) -> Cow<'a, str> {
+        let name = format!("$tmp{}", self.temporaries);
+        self.temporaries += 1;
+
+        Cow::Owned(name)
     }
 
     fn resolve_variable(&mut self, jester_name: &str) -> Option<&str> {
@@ -35,170 +113,2257 @@ impl Trans {
             .map(|variable| variable.js_name.as_str())
     }
 
-    pub fn trans_file<'a>(&mut self, file: &'a File) -> Result<js::File<'a>, Report<'static>> {
-        let stmts = file
-            .items
+    /// Whether the JS variable can be reassigned.
+    /// Temporaries aren't reassigned once they are used.
+    fn is_mutable(&self, js_name: &str) -> bool {
+        self.scopes
+            .iter()
+            .rev()
+            .flat_map(|scope| scope.variables.iter().rev())
+            .find(|variable| variable.js_name == js_name)
+            .is_some_and(|variable| variable.mutable)
+    }
+
+    /// Translates a file that was checked without errors.
+    pub fn trans_file<'a>(&mut self, file: &'a File) -> js::File<'a> {
+        let mut impls = Impls::new();
+
+        for item in &file.items {
+            if let Item::Impl(r#impl) = item {
+                impls
+                    .entry(r#impl.self_type.as_str())
+                    .or_default()
+                    .extend(&r#impl.fns);
+            }
+        }
+
+        // Functions can be called and structs constructed before their declaration
+        for item in prelude::file().items.iter().chain(&file.items) {
+            match item {
+                Item::Fn(r#fn) => {
+                    let js_name = self.declare_variable(&r#fn.name, false);
+                    self.item_names
+                        .insert(r#fn.name.span(), js_name.into_owned());
+                }
+                Item::Struct(r#struct) => {
+                    self.declare_class(&r#struct.name);
+                    self.declare_assoc_fns(&r#struct.name, Some(&r#struct.fields), &impls);
+                }
+                Item::Enum(r#enum) => {
+                    for variant in &r#enum.variants {
+                        if let VariantFields::Tuple(fields) = &variant.fields {
+                            let path =
+                                format!("{}::{}", r#enum.name.as_str(), variant.name.as_str());
+                            self.tuple_variants.insert(path, fields.len());
+                        }
+                    }
+
+                    if impls.contains_key(r#enum.name.as_str()) {
+                        self.declare_class(&r#enum.name);
+                        self.declare_assoc_fns(&r#enum.name, None, &impls);
+                    }
+                }
+                Item::Impl(_) => {}
+            }
+        }
+
+        let mut items = Vec::new();
+
+        for item in &file.items {
+            items.extend(self.trans_item(item, &impls));
+        }
+
+        let mut stmts = self
+            .helpers
             .iter()
-            .map(|item| self.trans_item(item))
-            .collect::<Result<Vec<_>, _>>()?;
+            .map(|helper| helper.decl())
+            .collect::<Vec<_>>();
+        stmts.extend(items);
+
+        js::File { stmts }
+    }
+
+    /// Picks the JS names of the functions in the `impl` blocks of a struct or enum.
+    ///
+    /// Methods of structs become methods of their class, which would be hidden
+    /// by a field of the same name, so these get a `$` appended.
+    /// Methods of enums and associated functions become `static` methods,
+    /// which take `self` as their first argument.
+    /// `fields` are those of a struct, or `None` for an enum.
+    fn declare_assoc_fns(&mut self, type_name: &str, fields: Option<&[Field]>, impls: &Impls) {
+        for r#fn in impls.get(type_name).into_iter().flatten() {
+            let name = r#fn.name.as_str();
+            let is_static = r#fn.receiver.is_none() || fields.is_none();
+            let is_taken = if is_static {
+                name == "prototype"
+            } else {
+                name == "constructor"
+                    || fields
+                        .into_iter()
+                        .flatten()
+                        .any(|field| field_name(&field.name) == name)
+            };
+            let js_name = if is_taken {
+                format!("{name}$")
+            } else {
+                name.to_owned()
+            };
 
-        Ok(js::File { stmts })
+            self.assoc_fns.insert(
+                format!("{type_name}::{name}"),
+                AssocFn {
+                    js_name,
+                    is_static,
+                    args: r#fn.args.len(),
+                },
+            );
+        }
     }
 
-    pub fn trans_item<'a>(&mut self, item: &'a Item) -> Result<js::Stmt<'a>, Report<'static>> {
+    /// Translates an item, if it exists at runtime.
+    /// Enums only do if they have methods, since their values are plain objects
+    /// tagged with their variant.
+    pub fn trans_item<'a>(&mut self, item: &'a Item, impls: &Impls<'a>) -> Option<js::Stmt<'a>> {
+        let methods = |name: &str| impls.get(name).map_or(&[][..], Vec::as_slice);
+
         match item {
-            Item::Fn(r#fn) => self.trans_fn(r#fn),
+            Item::Fn(r#fn) => Some(self.trans_fn(r#fn)),
+            Item::Struct(r#struct) => {
+                let mut class = self.trans_struct(r#struct);
+                class
+                    .methods
+                    .extend(self.trans_methods(&r#struct.name, methods(&r#struct.name)));
+
+                Some(js::Stmt::Class(class))
+            }
+            Item::Enum(r#enum) if impls.contains_key(r#enum.name.as_str()) => {
+                let class = js::Class {
+                    name: Cow::Owned(self.item_name(&r#enum.name)),
+                    methods: self.trans_methods(&r#enum.name, methods(&r#enum.name)),
+                };
+
+                Some(js::Stmt::Class(class))
+            }
+            Item::Enum(_) | Item::Impl(_) => None,
+        }
+    }
+
+    /// Translates a struct to a class whose constructor takes the fields as an object:
+    ///
+    /// ```js
+    /// class Point
+    /// {
+    ///     constructor(fields)
+    ///     {
+    ///         this.x = fields.x;
+    ///         this.y = fields.y;
+    ///     }
+    /// }
+    /// ```
+    fn trans_struct<'a>(&mut self, r#struct: &'a Struct) -> js::Class<'a> {
+        let stmts = r#struct
+            .fields
+            .iter()
+            .map(|field| {
+                let name = field_name(&field.name);
+                let this = js::Expr::Var("this".into()).boxed();
+                let fields = js::Expr::Var("fields".into()).boxed();
+
+                js::Stmt::Expr(js::Expr::Assign(
+                    js::Expr::Member(this, name.clone()).boxed(),
+                    js::Expr::Member(fields, name).boxed(),
+                ))
+            })
+            .collect();
+
+        js::Class {
+            name: Cow::Owned(self.item_name(&r#struct.name)),
+            methods: vec![js::Method {
+                name: "constructor".into(),
+                args: vec![js::Pattern::Var("fields".into())],
+                body: js::Block { stmts },
+                is_static: false,
+            }],
         }
     }
 
-    pub fn trans_fn<'a>(&mut self, r#fn: &'a Fn) -> Result<js::Stmt<'a>, Report<'static>> {
-        Ok(js::Stmt::Fn(js::Fn {
-            name: &r#fn.name,
-            args: r#fn.args.iter().map(|arg| arg.name.as_str()).collect(),
-            body: {
-                self.scopes.push(Scope::new(Span::splat(0)));
+    fn declare_class(&mut self, name: &Ident) {
+        let js_name = self.names.declare(name);
+        self.classes.insert(name.to_string(), js_name.clone());
+        self.item_names.insert(name.span(), js_name);
+    }
+
+    /// The JS name of the declaration of a function or class.
+    fn item_name(&self, name: &Ident) -> String {
+        self.item_names
+            .get(&name.span())
+            .expect("BUG: items are declared before their translation")
+            .clone()
+    }
+
+    fn class_name(&self, jester_name: &str) -> String {
+        self.classes
+            .get(jester_name)
+            .expect("BUG: classes are declared before their translation")
+            .clone()
+    }
+
+    /// Translates the functions of the `impl` blocks of a type to methods of its class.
+    fn trans_methods<'a>(&mut self, type_name: &str, fns: &[&'a Fn]) -> Vec<js::Method<'a>> {
+        fns.iter()
+            .map(|r#fn| {
+                let assoc_fn = &self.assoc_fns[&format!("{type_name}::{}", r#fn.name.as_str())];
+                let name = Cow::Owned(assoc_fn.js_name.clone());
+                let is_static = assoc_fn.is_static;
+                let (args, body) = self.trans_fn_args_and_body(r#fn, !is_static);
 
-                for arg in &r#fn.args {
-                    // TODO: do not apply shadowing logic ("x" -> "x__2")
-                    self.declare_variable(&arg.name);
+                js::Method {
+                    name,
+                    args,
+                    body,
+                    is_static,
                 }
+            })
+            .collect()
+    }
 
-                let body = self.trans_block(&r#fn.body)?;
-                self.scopes.pop();
+    pub fn trans_fn<'a>(&mut self, r#fn: &'a Fn) -> js::Stmt<'a> {
+        let name = self.item_name(&r#fn.name);
 
-                body
-            },
-        }))
+        let (args, body) = self.trans_fn_args_and_body(r#fn, false);
+
+        js::Stmt::Fn(js::Fn {
+            name: Cow::Owned(name),
+            args,
+            body,
+        })
+    }
+
+    /// Translates the arguments and body of a function.
+    ///
+    /// The `self` argument of a method refers to `this` if `self_is_this` is set,
+    /// otherwise it is passed as the first argument.
+    fn trans_fn_args_and_body<'a>(
+        &mut self,
+        r#fn: &'a Fn,
+        self_is_this: bool,
+    ) -> (Vec<js::Pattern<'a>>, js::Block<'a>) {
+        self.temporaries = 0;
+        self.names.enter_fn();
+        self.scopes.push(Scope::new());
+
+        let mut args = Vec::with_capacity(r#fn.args.len() + 1);
+        let mut prologue = Vec::new();
+
+        match &r#fn.receiver {
+            // A `mut self` gets a copy of the receiver, like other `mut` arguments
+            Some(receiver) if receiver.mutable => {
+                let receiver_value = match self_is_this {
+                    true => js::Expr::Var("this".into()),
+                    false => {
+                        let arg = self.declare_temporary();
+                        args.push(js::Pattern::Var(arg.clone()));
+
+                        js::Expr::Var(arg)
+                    }
+                };
+                let name = self.declare_variable(&receiver.name, true);
+
+                prologue.push(js::Stmt::Let(js::Let {
+                    pattern: js::Pattern::Var(name),
+                    mutable: true,
+                    rhs: Some(
+                        self.call_helper(Helper::Clone, vec![receiver_value])
+                            .boxed(),
+                    ),
+                }));
+            }
+            Some(receiver) if !self_is_this => {
+                let js_name = self.names.declare_arg(&receiver.name);
+                args.push(js::Pattern::Var(self.last_scope_mut().declare_variable(
+                    &receiver.name,
+                    &js_name,
+                    receiver.mutable,
+                )));
+            }
+            Some(receiver) => {
+                self.last_scope_mut()
+                    .declare_variable(&receiver.name, "this", false);
+            }
+            None => {}
+        }
+
+        for arg in &r#fn.args {
+            let arg = self.trans_param(&arg.pattern, true, &mut prologue);
+            args.push(arg);
+        }
+
+        let mut body = self.trans_fn_body(&r#fn.body, r#fn.return_type.is_some());
+
+        self.scopes.pop();
+        self.names.exit_fn();
+
+        body.stmts.splice(0..0, prologue);
+
+        (args, body)
+    }
+
+    /// Translates the statements of a function body into the current scope,
+    /// returning the trailing expression if the function has a return value.
+    fn trans_fn_body<'a>(&mut self, body: &'a Block, returns_value: bool) -> js::Block<'a> {
+        if !returns_value {
+            return self.trans_block(body);
+        }
+
+        let mut stmts = Vec::new();
+
+        for stmt in &body.stmts {
+            self.trans_stmt(stmt, &mut stmts);
+        }
+
+        if let Some(expr) = &body.expr {
+            self.trans_return_value(expr, &mut stmts);
+        }
+
+        js::Block { stmts }
+    }
+
+    /// Translates the trailing expression of a function into a `return`.
+    fn trans_return_value<'a>(&mut self, expr: &'a Expr, stmts: &mut Vec<js::Stmt<'a>>) {
+        match self.trans_expr(expr, stmts) {
+            // Placeholder value of a diverging expression like `return`
+            js::Expr::Undefined => {}
+            value => stmts.push(js::Stmt::Return(Some(value))),
+        }
     }
 
-    pub fn trans_block<'a>(&mut self, block: &'a Block) -> Result<js::Block<'a>, Report<'static>> {
-        let stmts = block
-            .stmts
+    /// Translates a closure to an arrow function, which captures variables the same way.
+    fn trans_closure<'a>(&mut self, closure: &'a Closure) -> js::Expr<'a> {
+        // `break` and `continue` can't leave the closure
+        let loops = std::mem::take(&mut self.loops);
+        self.scopes.push(Scope::new());
+
+        let mut prologue = Vec::new();
+        let args = closure
+            .args
             .iter()
-            .map(|stmt| self.trans_stmt(stmt))
-            .collect::<Result<Vec<_>, _>>()?;
+            .map(|arg| self.trans_param(&arg.pattern, false, &mut prologue))
+            .collect();
+        // Closures don't know whether they return `()`, which is returned as `undefined` anyway
+        let mut body = match &closure.body.kind {
+            ExprKind::Block(block) => self.trans_fn_body(block, true),
+            _ => {
+                let mut stmts = Vec::new();
+                self.trans_return_value(&closure.body, &mut stmts);
+
+                js::Block { stmts }
+            }
+        };
+
+        self.scopes.pop();
+        self.loops = loops;
+        body.stmts.splice(0..0, prologue);
+
+        js::Expr::Arrow(args, body)
+    }
+
+    /// Translates the statements of a block into the current scope.
+    pub fn trans_block<'a>(&mut self, block: &'a Block) -> js::Block<'a> {
+        let mut stmts = Vec::new();
+
+        for stmt in &block.stmts {
+            self.trans_stmt(stmt, &mut stmts);
+        }
+
+        if let Some(expr) = &block.expr {
+            self.trans_expr_stmt(expr, &mut stmts);
+        }
+
+        js::Block { stmts }
+    }
+
+    /// Translates a block into a new scope.
+    fn trans_scoped_block<'a>(&mut self, block: &'a Block) -> js::Block<'a> {
+        self.scopes.push(Scope::new());
+        let block = self.trans_block(block);
+        self.scopes.pop();
 
-        Ok(js::Block { stmts })
+        block
     }
 
-    fn trans_stmt<'a>(&mut self, stmt: &'a Stmt) -> Result<js::Stmt<'a>, Report<'static>> {
-        Ok(match stmt {
+    fn trans_stmt<'a>(&mut self, stmt: &'a Stmt, stmts: &mut Vec<js::Stmt<'a>>) {
+        match stmt {
             Stmt::Let(Let {
-                name: _,
+                pattern,
                 ty: _,
                 rhs,
-            }) if rhs.contains_block() => {
-                todo!("blocks in let not supported right now")
+            }) => {
+                let rhs_value = self.trans_expr(rhs, stmts);
+                let rhs = self.copy_value(rhs, rhs_value, pattern.is_mutable());
+
+                // Declared after translating `rhs`, which may still refer to shadowed variables
+                match self.trans_irrefutable_pattern(pattern, false) {
+                    Some(js_pattern) => stmts.push(js::Stmt::Let(js::Let {
+                        pattern: js_pattern,
+                        mutable: pattern.is_mutable(),
+                        rhs: Some(rhs.boxed()),
+                    })),
+                    // `let _ = rhs;` only evaluates `rhs`
+                    None => match rhs {
+                        js::Expr::Undefined => {}
+                        rhs => stmts.push(js::Stmt::Expr(rhs)),
+                    },
+                }
             }
-            Stmt::Let(Let { name, ty: _, rhs }) => {
-                let name = self.declare_variable(name);
+            Stmt::Expr(expr) => self.trans_expr_stmt(expr, stmts),
+        }
+    }
 
-                js::Stmt::Let(js::Let {
-                    name,
-                    rhs: self.trans_expr(rhs)?.boxed(),
-                })
+    /// Translates an expression whose value is discarded.
+    fn trans_expr_stmt<'a>(&mut self, expr: &'a Expr, stmts: &mut Vec<js::Stmt<'a>>) {
+        match &expr.kind {
+            ExprKind::Block(block) => stmts.push(js::Stmt::Block(self.trans_scoped_block(block))),
+            ExprKind::If { cond, then, r#else } => {
+                let r#if = self.trans_if(cond, then, r#else.as_deref(), stmts, None);
+                stmts.push(js::Stmt::If(r#if));
             }
-            Stmt::Expr(expr) => js::Stmt::Expr(self.trans_expr(expr)?),
-        })
+            ExprKind::Match { scrutinee, arms } => self.trans_match(scrutinee, arms, stmts, None),
+            ExprKind::Loop { label, body } => {
+                let r#while = self.trans_loop(label.as_ref(), body, None);
+                stmts.push(js::Stmt::While(r#while));
+            }
+            ExprKind::While { label, cond, body } => {
+                let r#while = self.trans_while(label.as_ref(), cond, body);
+                stmts.push(js::Stmt::While(r#while));
+            }
+            _ => match self.trans_expr(expr, stmts) {
+                // Placeholder values of statements like `break`
+                js::Expr::Undefined => {}
+                expr => stmts.push(js::Stmt::Expr(expr)),
+            },
+        }
     }
 
-    fn trans_expr<'a>(&mut self, expr: &'a Expr) -> Result<js::Expr<'a>, Report<'static>> {
-        Ok(match expr {
-            Expr::Int(n) => js::Expr::Number((*n).into()),
-            Expr::Parens(expr) => js::Expr::Parens(self.trans_expr(expr)?.boxed()),
-            Expr::Var {
-                name,
-                name_span: span,
-            } => {
-                let name = self
-                    .resolve_variable(name)
-                    .ok_or_else(|| report::undeclared_variable(name, span))?;
+    /// Translates an expression into a JS expression.
+    ///
+    /// Parts of the expression that can only be expressed as JS statements (e.g. blocks)
+    /// are hoisted into `stmts`, which get executed before the returned expression.
+    fn trans_expr<'a>(&mut self, expr: &'a Expr, stmts: &mut Vec<js::Stmt<'a>>) -> js::Expr<'a> {
+        match &expr.kind {
+            ExprKind::Int { value, .. } => match self.types.expr(expr) {
+                Type::I64 => js::Expr::BigInt(*value),
+                _ => js::Expr::Number(*value as f64),
+            },
+            ExprKind::Float(n) => js::Expr::Number(*n),
+            ExprKind::Bool(b) => js::Expr::Bool(*b),
+            ExprKind::String(s) => js::Expr::String(Cow::Borrowed(s)),
+            ExprKind::Error => js::Expr::Undefined,
+            ExprKind::Format(parts) => {
+                let exprs = parts
+                    .iter()
+                    .filter_map(|part| match part {
+                        FormatPart::Str(_) => None,
+                        FormatPart::Expr(expr) => Some(expr),
+                    })
+                    .collect::<Vec<_>>();
+                let mut exprs = self.trans_args(&exprs, stmts).into_iter();
+                let parts = parts
+                    .iter()
+                    .map(|part| match part {
+                        FormatPart::Str(s) => js::TemplatePart::Str(Cow::Borrowed(s)),
+                        FormatPart::Expr(_) => js::TemplatePart::Expr(
+                            exprs.next().expect("BUG: every expression is translated"),
+                        ),
+                    })
+                    .collect();
 
-                js::Expr::Var(Cow::Owned(name.into()))
+                js::Expr::Template(parts)
             }
-            Expr::Neg(expr) => js::Expr::Neg(self.trans_expr(expr)?.boxed()),
-            Expr::Add(lhs, rhs) => {
-                js::Expr::Add(self.trans_expr(lhs)?.boxed(), self.trans_expr(rhs)?.boxed())
+            ExprKind::Parens(expr) => js::Expr::Parens(self.trans_expr(expr, stmts).boxed()),
+            // Tuples are arrays, which are destructured the same way
+            ExprKind::Tuple(elements) => {
+                let elements = self.trans_args(&elements.iter().collect::<Vec<_>>(), stmts);
+
+                js::Expr::Array(elements)
             }
-            Expr::Sub(lhs, rhs) => {
-                js::Expr::Sub(self.trans_expr(lhs)?.boxed(), self.trans_expr(rhs)?.boxed())
+            ExprKind::Var(name) => match self.resolve_variable(name).map(str::to_owned) {
+                Some(name) => js::Expr::Var(Cow::Owned(name)),
+                None => match self.variant_path(expr) {
+                    Some((path, name)) => self.trans_variant_path(&path, name),
+                    None => unreachable!("BUG: variables are declared before their use"),
+                },
+            },
+            // Negative literals are in range, so they don't need to wrap around
+            ExprKind::Neg(operand) if matches!(operand.kind, ExprKind::Int { .. }) => {
+                js::Expr::Neg(self.trans_expr(operand, stmts).boxed())
             }
-            Expr::Mul(lhs, rhs) => {
-                js::Expr::Mul(self.trans_expr(lhs)?.boxed(), self.trans_expr(rhs)?.boxed())
+            ExprKind::Neg(operand) => {
+                let operand = self.trans_expr(operand, stmts);
+
+                wrap(self.types.expr(expr), js::Expr::Neg(operand.boxed()))
             }
-            Expr::Div(lhs, rhs) => {
-                js::Expr::Div(self.trans_expr(lhs)?.boxed(), self.trans_expr(rhs)?.boxed())
+            ExprKind::Add(lhs, rhs) => {
+                self.trans_arithmetic(expr, lhs, rhs, Arithmetic::Add, stmts)
             }
-            Expr::Call(_, _) => todo!(),
-            Expr::Fn {
-                name,
+            ExprKind::Sub(lhs, rhs) => {
+                self.trans_arithmetic(expr, lhs, rhs, Arithmetic::Sub, stmts)
+            }
+            ExprKind::Mul(lhs, rhs) => {
+                self.trans_arithmetic(expr, lhs, rhs, Arithmetic::Mul, stmts)
+            }
+            ExprKind::Div(lhs, rhs) => {
+                self.trans_arithmetic(expr, lhs, rhs, Arithmetic::Div, stmts)
+            }
+            // Loose equality would coerce the operands, which Jester never does
+            ExprKind::Eq(lhs, rhs) => self.trans_equality(lhs, rhs, true, stmts),
+            ExprKind::Ne(lhs, rhs) => self.trans_equality(lhs, rhs, false, stmts),
+            ExprKind::Lt(lhs, rhs) => self.trans_binary(lhs, rhs, stmts, js::Expr::Lt),
+            ExprKind::Le(lhs, rhs) => self.trans_binary(lhs, rhs, stmts, js::Expr::Le),
+            ExprKind::Gt(lhs, rhs) => self.trans_binary(lhs, rhs, stmts, js::Expr::Gt),
+            ExprKind::Ge(lhs, rhs) => self.trans_binary(lhs, rhs, stmts, js::Expr::Ge),
+            ExprKind::Not(expr) => js::Expr::Not(self.trans_expr(expr, stmts).boxed()),
+            ExprKind::And(lhs, rhs) => {
+                self.trans_logical(lhs, rhs, stmts, js::Expr::And, |lhs| lhs)
+            }
+            ExprKind::Or(lhs, rhs) => self.trans_logical(lhs, rhs, stmts, js::Expr::Or, |lhs| {
+                js::Expr::Not(lhs.boxed())
+            }),
+            ExprKind::Assign(lhs, rhs) => self.trans_assignment(lhs, rhs, stmts, js::Expr::Assign),
+            ExprKind::AddAssign(lhs, rhs) if self.types.expr(lhs).is_integer() => {
+                self.trans_integer_assignment(lhs, rhs, Arithmetic::Add, stmts)
+            }
+            ExprKind::AddAssign(lhs, rhs) => {
+                self.trans_assignment(lhs, rhs, stmts, js::Expr::AddAssign)
+            }
+            ExprKind::SubAssign(lhs, rhs) if self.types.expr(lhs).is_integer() => {
+                self.trans_integer_assignment(lhs, rhs, Arithmetic::Sub, stmts)
+            }
+            ExprKind::SubAssign(lhs, rhs) => {
+                self.trans_assignment(lhs, rhs, stmts, js::Expr::SubAssign)
+            }
+            ExprKind::MulAssign(lhs, rhs) if self.types.expr(lhs).is_integer() => {
+                self.trans_integer_assignment(lhs, rhs, Arithmetic::Mul, stmts)
+            }
+            ExprKind::MulAssign(lhs, rhs) => {
+                self.trans_assignment(lhs, rhs, stmts, js::Expr::MulAssign)
+            }
+            ExprKind::DivAssign(lhs, rhs) if self.types.expr(lhs).is_integer() => {
+                self.trans_integer_assignment(lhs, rhs, Arithmetic::Div, stmts)
+            }
+            ExprKind::DivAssign(lhs, rhs) => {
+                self.trans_assignment(lhs, rhs, stmts, js::Expr::DivAssign)
+            }
+            ExprKind::Call(callee, args) => {
+                let call = self.trans_call(callee, args, stmts);
+                let is_method = match &callee.kind {
+                    ExprKind::Path(path) => self
+                        .assoc_fns
+                        .get(&path.to_string())
+                        .is_some_and(|assoc_fn| !assoc_fn.is_static),
+                    _ => false,
+                };
+
+                // `Point::len(p)` is called like `p.len()`
+                match is_method {
+                    true => {
+                        let result_type = self.types.expr(expr).clone();
+                        self.copy_method_result(&args[0], &result_type, call)
+                    }
+                    false => call,
+                }
+            }
+            ExprKind::MethodCall {
+                receiver,
+                method,
                 args,
+            } => {
+                let call = self.trans_method_call(receiver, method, args, stmts);
+                let result_type = self.types.expr(expr).clone();
+
+                self.copy_method_result(receiver, &result_type, call)
+            }
+            ExprKind::Path(path) => match self.trans_assoc_fn_path(path) {
+                Some(r#fn) => r#fn,
+                None => self.trans_variant_path(&path.to_string(), path.last().as_str()),
+            },
+            ExprKind::Struct { path, fields, base } => {
+                self.trans_struct_expr(path, fields, base.as_deref(), stmts)
+            }
+            ExprKind::Match { scrutinee, arms } => {
+                let temporary = self.declare_temporary();
+
+                stmts.push(js::Stmt::Let(js::Let {
+                    pattern: js::Pattern::Var(temporary.clone()),
+                    mutable: true,
+                    rhs: None,
+                }));
+                self.trans_match(scrutinee, arms, stmts, Some(&temporary));
+
+                js::Expr::Var(temporary)
+            }
+            ExprKind::Field(expr, name) => {
+                let expr = self.trans_expr(expr, stmts);
+
+                member(expr, name)
+            }
+            ExprKind::Array(elements) => {
+                let elements = self.trans_args(&elements.iter().collect::<Vec<_>>(), stmts);
+
+                js::Expr::Array(elements)
+            }
+            ExprKind::Repeat {
+                value: repeated,
+                count,
+            } => {
+                let mut value = self.trans_expr(repeated, stmts);
+                let is_primitive = self.types.expr(repeated).is_primitive();
+
+                // `count` is evaluated first in JS, and a copied value only once
+                if !is_primitive || !matches!(count.kind, ExprKind::Int { .. } | ExprKind::Var(_)) {
+                    value = self.hoist(value, stmts);
+                }
+
+                let count = self.trans_expr(count, stmts);
+
+                if is_primitive {
+                    let array =
+                        js::Expr::New(js::Expr::Var(Cow::Borrowed("Array")).boxed(), vec![count]);
+                    let fill = js::Expr::Member(array.boxed(), Cow::Borrowed("fill"));
+
+                    return js::Expr::Call(fill.boxed(), vec![value]);
+                }
+
+                // Each element gets its own copy, instead of all of them sharing one object
+                let copy = self.call_helper(Helper::Clone, vec![value]);
+                let from = js::Expr::Member(
+                    js::Expr::Var(Cow::Borrowed("Array")).boxed(),
+                    Cow::Borrowed("from"),
+                );
+                let length =
+                    js::Expr::Object(vec![js::Prop::KeyValue(Cow::Borrowed("length"), count)]);
+                let element = js::Expr::Arrow(
+                    Vec::new(),
+                    js::Block {
+                        stmts: vec![js::Stmt::Return(Some(copy))],
+                    },
+                );
+
+                js::Expr::Call(from.boxed(), vec![length, element])
+            }
+            ExprKind::Index(expr, index) => {
+                let mut expr = self.trans_expr(expr, stmts);
+
+                if index.contains_block() {
+                    expr = self.hoist(expr, stmts);
+                }
+
+                let index = self.trans_expr(index, stmts);
+
+                self.trans_index(expr, index, stmts)
+            }
+            // Ranges outside of `for` loops are objects with an exclusive end
+            ExprKind::Range {
+                start,
+                end,
+                inclusive,
+            } => {
+                let (start, mut end) = self.trans_range(start, end, stmts);
+
+                if *inclusive {
+                    let Type::Range(ty) = self.types.expr(expr) else {
+                        unreachable!("BUG: ranges have range types");
+                    };
+
+                    end = js::Expr::Add(end.boxed(), one(ty).boxed());
+                }
+
+                js::Expr::Object(vec![
+                    js::Prop::KeyValue(Cow::Borrowed("start"), start),
+                    js::Prop::KeyValue(Cow::Borrowed("end"), end),
+                ])
+            }
+            ExprKind::Closure(closure) => self.trans_closure(closure),
+            ExprKind::Block(block) => self.trans_block_expr(block, stmts),
+            ExprKind::If { cond, then, r#else } => {
+                self.trans_if_expr(cond, then, r#else.as_deref(), stmts)
+            }
+            ExprKind::Loop { label, body } => {
+                let temporary = self.declare_temporary();
+                let r#while = self.trans_loop(label.as_ref(), body, Some(&temporary));
+
+                stmts.push(js::Stmt::Let(js::Let {
+                    pattern: js::Pattern::Var(temporary.clone()),
+                    mutable: true,
+                    rhs: None,
+                }));
+                stmts.push(js::Stmt::While(r#while));
+
+                js::Expr::Var(temporary)
+            }
+            ExprKind::While { label, cond, body } => {
+                let r#while = self.trans_while(label.as_ref(), cond, body);
+                stmts.push(js::Stmt::While(r#while));
+
+                js::Expr::Undefined
+            }
+            ExprKind::For {
+                label,
+                var,
+                iter,
                 body,
-                then,
-            } => todo!(),
-            Expr::Block(block) => todo!("block!"),
-        })
-    }
-}
+            } => {
+                let r#for = self.trans_for(label.as_ref(), var, iter, body, stmts);
+                stmts.push(r#for);
 
-#[derive(Debug)]
-struct Scope {
-    variables: Vec<Variable>,
-    span: Span,
-}
+                js::Expr::Undefined
+            }
+            ExprKind::Break { label, value } => {
+                let r#loop = self.resolve_loop(label.as_ref());
+                let js_label = r#loop.js_label.clone().map(Cow::Owned);
+                let result = r#loop.result.clone().map(Cow::Owned);
 
-impl Scope {
-    fn new(span: Span) -> Self {
-        Self {
-            variables: Vec::new(),
-            span,
+                if let Some(value) = value {
+                    let value = self.trans_expr(value, stmts);
+
+                    stmts.push(js::Stmt::Expr(match result {
+                        Some(result) => {
+                            js::Expr::Assign(js::Expr::Var(result).boxed(), value.boxed())
+                        }
+                        None => value,
+                    }));
+                }
+
+                stmts.push(js::Stmt::Break(js_label));
+
+                js::Expr::Undefined
+            }
+            ExprKind::Continue { label } => {
+                let js_label = self.resolve_loop(label.as_ref()).js_label.clone();
+                stmts.push(js::Stmt::Continue(js_label.map(Cow::Owned)));
+
+                js::Expr::Undefined
+            }
+            ExprKind::Return(value) => {
+                let value = match value {
+                    Some(value) => match self.trans_expr(value, stmts) {
+                        // `()` is returned as `undefined` anyway
+                        js::Expr::Undefined => None,
+                        value => Some(value),
+                    },
+                    None => None,
+                };
+
+                stmts.push(js::Stmt::Return(value));
+
+                js::Expr::Undefined
+            }
+            // `if (value.$tag === "None") return value;` and then `value._0`
+            ExprKind::Try(operand) => {
+                let Type::Enum(enum_name, _) = self.types.expr(operand) else {
+                    unreachable!("BUG: `?` is applied to enums");
+                };
+                let residual = prelude::try_residual(enum_name)
+                    .expect("BUG: `?` is applied to `Option` or `Result`");
+                let value = self.trans_expr(operand, stmts);
+                let value = self.hoist(value, stmts);
+
+                stmts.push(js::Stmt::If(js::If {
+                    cond: tag_test(value.clone(), residual).boxed(),
+                    then: js::Block {
+                        stmts: vec![js::Stmt::Return(Some(value.clone()))],
+                    },
+                    r#else: None,
+                }));
+
+                js::Expr::Member(value.boxed(), Cow::Owned(tuple_field(0)))
+            }
         }
     }
 
-    fn has_js_variable(&self, js_name: &str) -> bool {
-        for variable in &self.variables {
-            if variable.js_name == js_name {
-                return true;
-            }
+    /// Translates a `loop` to `while (true)`.
+    /// Values passed to `break` are assigned to `result`, if any.
+    fn trans_loop<'a>(
+        &mut self,
+        label: Option<&Ident>,
+        body: &'a Block,
+        result: Option<&Cow<'a, str>>,
+    ) -> js::While<'a> {
+        let js_label = self.push_loop(label, result);
+        let body = self.trans_scoped_block(body);
+        self.loops.pop();
+
+        js::While {
+            label: js_label.map(Cow::Owned),
+            cond: js::Expr::Bool(true).boxed(),
+            body,
         }
+    }
+
+    /// Translates a `while` loop.
+    ///
+    /// Statements hoisted out of the condition must run before every iteration,
+    /// so in that case the condition is moved into the body:
+    ///
+    /// ```js
+    /// while (true)
+    /// {
+    ///     …
+    ///     if (!cond)
+    ///     {
+    ///         break;
+    ///     }
+    ///     …
+    /// }
+    /// ```
+    fn trans_while<'a>(
+        &mut self,
+        label: Option<&Ident>,
+        cond: &'a Expr,
+        body: &'a Block,
+    ) -> js::While<'a> {
+        let js_label = self.push_loop(label, None);
+        let (cond, body) = self.trans_while_body(cond, body);
+        self.loops.pop();
 
-        false
+        js::While {
+            label: js_label.map(Cow::Owned),
+            cond: cond.boxed(),
+            body,
+        }
     }
 
-    fn declare_variable<'a>(&mut self, jester_name: &'a str) -> Cow<'a, str> {
-        if !self.has_js_variable(jester_name) {
-            self.declare_variable_unchecked(jester_name, jester_name);
+    fn trans_while_body<'a>(
+        &mut self,
+        cond: &'a Expr,
+        body: &'a Block,
+    ) -> (js::Expr<'a>, js::Block<'a>) {
+        if !cond.contains_block() {
+            let cond = self.trans_expr(cond, &mut Vec::new());
+            let body = self.trans_scoped_block(body);
+
+            return (cond, body);
+        }
+
+        let mut stmts = Vec::new();
+        let cond = self.trans_expr(cond, &mut stmts);
+
+        stmts.push(js::Stmt::If(js::If {
+            cond: js::Expr::Not(cond.boxed()).boxed(),
+            then: js::Block {
+                stmts: vec![js::Stmt::Break(None)],
+            },
+            r#else: None,
+        }));
+        stmts.push(js::Stmt::Block(self.trans_scoped_block(body)));
 
-            return Cow::Borrowed(jester_name);
+        (js::Expr::Bool(true), js::Block { stmts })
+    }
+
+    /// Translates a `for` loop.
+    /// Loops over ranges become counted loops instead of iterating over an array:
+    ///
+    /// ```js
+    /// for (let i = start; i < end; i++)
+    /// ```
+    fn trans_for<'a>(
+        &mut self,
+        label: Option<&Ident>,
+        var: &'a Ident,
+        iter: &'a Expr,
+        body: &'a Block,
+        stmts: &mut Vec<js::Stmt<'a>>,
+    ) -> js::Stmt<'a> {
+        enum Iter<'a> {
+            Range(js::Expr<'a>, js::Expr<'a>, bool),
+            Array(js::Expr<'a>),
         }
 
-        let n = 2;
+        let iter = match &iter.kind {
+            ExprKind::Range {
+                start,
+                end,
+                inclusive,
+            } => {
+                let (start, end) = self.trans_range(start, end, stmts);
 
-        loop {
-            let js_name = format!("{jester_name}__{n}");
+                // The end is only evaluated once
+                Iter::Range(start, self.hoist(end, stmts), *inclusive)
+            }
+            _ => {
+                let value = self.trans_expr(iter, stmts);
 
-            if !self.has_js_variable(&js_name) {
-                self.declare_variable_unchecked(jester_name, &js_name);
+                match self.types.expr(iter) {
+                    Type::Range(_) => {
+                        let range = self.hoist(value, stmts);
+                        let member =
+                            |name| js::Expr::Member(range.clone().boxed(), Cow::Borrowed(name));
 
-                return Cow::Owned(js_name);
+                        Iter::Range(member("start"), member("end"), false)
+                    }
+                    _ => Iter::Array(value),
+                }
             }
+        };
+
+        let js_label = self.push_loop(label, None).map(Cow::Owned);
+        self.scopes.push(Scope::new());
+        let var = self.declare_variable(var, false);
+        let body = self.trans_scoped_block(body);
+        self.scopes.pop();
+        self.loops.pop();
+
+        match iter {
+            Iter::Range(start, end, inclusive) => js::Stmt::For(js::For {
+                label: js_label,
+                var,
+                start: start.boxed(),
+                end: end.boxed(),
+                inclusive,
+                body,
+            }),
+            Iter::Array(iter) => js::Stmt::ForOf(js::ForOf {
+                label: js_label,
+                var,
+                iter: iter.boxed(),
+                body,
+            }),
         }
     }
 
-    fn declare_variable_unchecked(
+    /// Translates the bounds of a range in order.
+    fn trans_range<'a>(
         &mut self,
-        jester_name: impl Into<String>,
-        js_name: impl Into<String>,
-    ) {
-        self.variables.push(Variable {
-            jester_name: jester_name.into(),
-            js_name: js_name.into(),
+        start: &'a Expr,
+        end: &'a Expr,
+        stmts: &mut Vec<js::Stmt<'a>>,
+    ) -> (js::Expr<'a>, js::Expr<'a>) {
+        let mut start = self.trans_expr(start, stmts);
+
+        if end.contains_block() {
+            start = self.hoist(start, stmts);
+        }
+
+        let end = self.trans_expr(end, stmts);
+
+        (start, end)
+    }
+
+    /// Indexes an array, checking the index first if bounds checks are enabled.
+    fn trans_index<'a>(
+        &mut self,
+        mut array: js::Expr<'a>,
+        index: js::Expr<'a>,
+        stmts: &mut Vec<js::Stmt<'a>>,
+    ) -> js::Expr<'a> {
+        if !self.bounds_checks {
+            return js::Expr::Index(array.boxed(), index.boxed());
+        }
+
+        // Reading a variable twice has no side effects
+        if !matches!(array, js::Expr::Var(_)) {
+            array = self.hoist(array, stmts);
+        }
+
+        let index = self.call_helper(Helper::CheckIndex, vec![array.clone(), index]);
+
+        js::Expr::Index(array.boxed(), index.boxed())
+    }
+
+    /// Enters a loop and returns its JS label, if it has one.
+    fn push_loop(&mut self, label: Option<&Ident>, result: Option<&Cow<str>>) -> Option<String> {
+        let js_label = label.map(|label| {
+            // JS doesn't allow a label to shadow the label of an enclosing loop
+            names::unique_name(label, |js_label| {
+                self.loops
+                    .iter()
+                    .any(|r#loop| r#loop.js_label.as_deref() == Some(js_label))
+            })
         });
+
+        self.loops.push(Loop {
+            label: label.map(|label| label.to_string()),
+            js_label: js_label.clone(),
+            result: result.map(|result| result.to_string()),
+        });
+
+        js_label
+    }
+
+    /// Finds the loop that a `break` or `continue` refers to.
+    fn resolve_loop(&self, label: Option<&Ident>) -> &Loop {
+        let r#loop = match label {
+            Some(label) => self
+                .loops
+                .iter()
+                .rfind(|r#loop| r#loop.label.as_deref() == Some(label.as_str())),
+            None => self.loops.last(),
+        };
+
+        r#loop.expect("BUG: loops are resolved by the type checker")
+    }
+
+    fn trans_binary<'a>(
+        &mut self,
+        lhs: &'a Expr,
+        rhs: &'a Expr,
+        stmts: &mut Vec<js::Stmt<'a>>,
+        op: impl FnOnce(Box<js::Expr<'a>>, Box<js::Expr<'a>>) -> js::Expr<'a>,
+    ) -> js::Expr<'a> {
+        let (lhs, rhs) = self.trans_operands(lhs, rhs, stmts);
+
+        op(lhs.boxed(), rhs.boxed())
     }
+
+    /// Translates the operands of a binary operator in order.
+    fn trans_operands<'a>(
+        &mut self,
+        lhs: &'a Expr,
+        rhs: &'a Expr,
+        stmts: &mut Vec<js::Stmt<'a>>,
+    ) -> (js::Expr<'a>, js::Expr<'a>) {
+        let mut lhs = self.trans_expr(lhs, stmts);
+
+        // Hoisting `rhs` must not move its side effects before the evaluation of `lhs`
+        if rhs.contains_block() {
+            lhs = self.hoist(lhs, stmts);
+        }
+
+        let rhs = self.trans_expr(rhs, stmts);
+
+        (lhs, rhs)
+    }
+
+    /// Translates `==`, or `!=` if not `equal`.
+    /// Values that are JS objects are compared by their contents instead of their identity.
+    fn trans_equality<'a>(
+        &mut self,
+        lhs: &'a Expr,
+        rhs: &'a Expr,
+        equal: bool,
+        stmts: &mut Vec<js::Stmt<'a>>,
+    ) -> js::Expr<'a> {
+        let is_primitive = matches!(
+            self.types.expr(lhs),
+            Type::I32
+                | Type::I64
+                | Type::F64
+                | Type::Bool
+                | Type::String
+                | Type::Unit
+                | Type::Never
+        );
+
+        if is_primitive {
+            let op = if equal {
+                js::Expr::StrictEq
+            } else {
+                js::Expr::StrictNe
+            };
+
+            return self.trans_binary(lhs, rhs, stmts, op);
+        }
+
+        let (lhs, rhs) = self.trans_operands(lhs, rhs, stmts);
+        let eq = self.call_helper(Helper::Eq, vec![lhs, rhs]);
+
+        if equal {
+            eq
+        } else {
+            js::Expr::Not(eq.boxed())
+        }
+    }
+
+    /// Translates `+`, `-`, `*` and `/`, where integers wrap around on overflow.
+    fn trans_arithmetic<'a>(
+        &mut self,
+        expr: &'a Expr,
+        lhs: &'a Expr,
+        rhs: &'a Expr,
+        op: Arithmetic,
+        stmts: &mut Vec<js::Stmt<'a>>,
+    ) -> js::Expr<'a> {
+        let ty = self.types.expr(expr).clone();
+        let (lhs, rhs) = self.trans_operands(lhs, rhs, stmts);
+        let rhs = self.check_divisor(op, &ty, rhs);
+
+        op.apply(&ty, lhs.boxed(), rhs.boxed())
+    }
+
+    /// Makes integer division by zero panic like in Rust,
+    /// instead of giving `0` for `i32` and throwing a `BigInt` error for `i64`.
+    fn check_divisor<'a>(&mut self, op: Arithmetic, ty: &Type, rhs: js::Expr<'a>) -> js::Expr<'a> {
+        match (op, &rhs) {
+            // Literals like the `2` of `x / 2` are known not to be zero
+            (Arithmetic::Div, js::Expr::Number(n)) if *n != 0.0 => rhs,
+            (Arithmetic::Div, js::Expr::BigInt(n)) if *n != 0 => rhs,
+            (Arithmetic::Div, _) if ty.is_integer() => {
+                self.call_helper(Helper::CheckDivisor, vec![rhs])
+            }
+            _ => rhs,
+        }
+    }
+
+    /// Translates `&&` and `||`.
+    ///
+    /// Statements hoisted out of `rhs` must only run if `rhs` gets evaluated,
+    /// so in that case the short-circuiting is done with an `if` instead,
+    /// using `evaluates_rhs` to build its condition:
+    ///
+    /// ```js
+    /// let $tmp0 = lhs;
+    /// if ($tmp0) { // `if (!$tmp0)` for `||`
+    ///     …
+    ///     $tmp0 = rhs;
+    /// }
+    /// ```
+    fn trans_logical<'a>(
+        &mut self,
+        lhs: &'a Expr,
+        rhs: &'a Expr,
+        stmts: &mut Vec<js::Stmt<'a>>,
+        op: fn(Box<js::Expr<'a>>, Box<js::Expr<'a>>) -> js::Expr<'a>,
+        evaluates_rhs: fn(js::Expr<'a>) -> js::Expr<'a>,
+    ) -> js::Expr<'a> {
+        if !rhs.contains_block() {
+            return self.trans_binary(lhs, rhs, stmts, op);
+        }
+
+        let lhs = self.trans_expr(lhs, stmts);
+        let temporary = self.declare_temporary();
+        let mut then_stmts = Vec::new();
+        let rhs = self.trans_expr(rhs, &mut then_stmts);
+
+        then_stmts.push(js::Stmt::Expr(js::Expr::Assign(
+            js::Expr::Var(temporary.clone()).boxed(),
+            rhs.boxed(),
+        )));
+
+        let cond = evaluates_rhs(js::Expr::Var(temporary.clone()));
+
+        stmts.push(js::Stmt::Let(js::Let {
+            pattern: js::Pattern::Var(temporary.clone()),
+            mutable: true,
+            rhs: Some(lhs.boxed()),
+        }));
+        stmts.push(js::Stmt::If(js::If {
+            cond: cond.boxed(),
+            then: js::Block { stmts: then_stmts },
+            r#else: None,
+        }));
+
+        js::Expr::Var(temporary)
+    }
+
+    /// Translates an assignment to a JS statement, since its value is `()`.
+    fn trans_assignment<'a>(
+        &mut self,
+        lhs: &'a Expr,
+        rhs: &'a Expr,
+        stmts: &mut Vec<js::Stmt<'a>>,
+        op: fn(Box<js::Expr<'a>>, Box<js::Expr<'a>>) -> js::Expr<'a>,
+    ) -> js::Expr<'a> {
+        let lhs = self.trans_expr(lhs, stmts);
+        let rhs_value = self.trans_expr(rhs, stmts);
+        let rhs_value = self.copy_value(rhs, rhs_value, true);
+
+        stmts.push(js::Stmt::Expr(op(lhs.boxed(), rhs_value.boxed())));
+
+        js::Expr::Undefined
+    }
+
+    /// Translates an assignment like `a += b` to an integer place,
+    /// which becomes `a = a + b | 0` to wrap around on overflow.
+    fn trans_integer_assignment<'a>(
+        &mut self,
+        lhs: &'a Expr,
+        rhs: &'a Expr,
+        op: Arithmetic,
+        stmts: &mut Vec<js::Stmt<'a>>,
+    ) -> js::Expr<'a> {
+        let ty = self.types.expr(lhs).clone();
+        let place = self.trans_place(lhs, stmts);
+        let mut lhs = place.clone();
+
+        // Hoisting `rhs` must not move its side effects before reading `lhs`
+        if rhs.contains_block() {
+            lhs = self.hoist(lhs, stmts);
+        }
+
+        let rhs = self.trans_expr(rhs, stmts);
+        let rhs = self.check_divisor(op, &ty, rhs);
+        let value = op.apply(&ty, lhs.boxed(), rhs.boxed());
+
+        stmts.push(js::Stmt::Expr(js::Expr::Assign(
+            place.boxed(),
+            value.boxed(),
+        )));
+
+        js::Expr::Undefined
+    }
+
+    /// Translates a place that gets evaluated twice, like the `a[i]` in `a[i] = a[i] + 1 | 0`.
+    /// Indices are stored in temporaries, since they may have side effects.
+    fn trans_place<'a>(&mut self, place: &'a Expr, stmts: &mut Vec<js::Stmt<'a>>) -> js::Expr<'a> {
+        match &place.kind {
+            ExprKind::Parens(place) => self.trans_place(place, stmts),
+            ExprKind::Field(expr, name) => {
+                let expr = self.trans_place(expr, stmts);
+
+                member(expr, name)
+            }
+            ExprKind::Index(expr, index) => {
+                let expr = self.trans_place(expr, stmts);
+                let index = self.trans_expr(index, stmts);
+                let index = self.hoist(index, stmts);
+
+                self.trans_index(expr, index, stmts)
+            }
+            _ => self.trans_expr(place, stmts),
+        }
+    }
+
+    fn trans_call<'a>(
+        &mut self,
+        callee: &'a Expr,
+        args: &'a [Expr],
+        stmts: &mut Vec<js::Stmt<'a>>,
+    ) -> js::Expr<'a> {
+        // Tuple variants are constructed directly instead of calling a function
+        if let Some((path, name)) = self.variant_path(callee) {
+            if self.tuple_variants.contains_key(&path) {
+                let values = args
+                    .iter()
+                    .enumerate()
+                    .map(|(i, arg)| (Cow::Owned(tuple_field(i)), arg))
+                    .collect::<Vec<_>>();
+                let mut props = self.trans_props(&values, false, stmts);
+                props.insert(0, tag(name));
+
+                return js::Expr::Object(props);
+            }
+        }
+
+        if let ExprKind::Path(path) = &callee.kind {
+            // `Point::len(p)` is called like `p.len()`
+            if let Some(assoc_fn) = self.assoc_fns.get(&path.to_string()) {
+                if !assoc_fn.is_static {
+                    let js_name = Cow::Owned(assoc_fn.js_name.clone());
+
+                    return self.trans_instance_method_call(&args[0], js_name, &args[1..], stmts);
+                }
+            }
+        }
+
+        let mut callee = match &callee.kind {
+            ExprKind::Var(name) => match self.resolve_variable(name) {
+                Some(name) => js::Expr::Var(Cow::Owned(name.into())),
+                None => unreachable!("BUG: functions are declared before their use"),
+            },
+            _ => self.trans_expr(callee, stmts),
+        };
+
+        if args.iter().any(Expr::contains_block) {
+            callee = self.hoist(callee, stmts);
+        }
+
+        let args = self.trans_args(&args.iter().collect::<Vec<_>>(), stmts);
+
+        js::Expr::Call(callee.boxed(), args)
+    }
+
+    /// Translates the arguments of a call in order.
+    /// Arguments are only hoisted if later ones need statements.
+    fn trans_args<'a>(
+        &mut self,
+        args: &[&'a Expr],
+        stmts: &mut Vec<js::Stmt<'a>>,
+    ) -> Vec<js::Expr<'a>> {
+        let mut js_args = Vec::with_capacity(args.len());
+
+        for (i, arg) in args.iter().enumerate() {
+            let value = self.trans_expr(arg, stmts);
+            let mut arg = self.copy_value(arg, value, false);
+
+            if args[i + 1..].iter().any(|arg| arg.contains_block()) {
+                arg = self.hoist(arg, stmts);
+            }
+
+            js_args.push(arg);
+        }
+
+        js_args
+    }
+
+    /// Translates a method call like `p.len()`.
+    /// Methods of enums are `static`, so `shape.area()` becomes `Shape.area(shape)`.
+    fn trans_method_call<'a>(
+        &mut self,
+        receiver: &'a Expr,
+        method: &Ident,
+        args: &'a [Expr],
+        stmts: &mut Vec<js::Stmt<'a>>,
+    ) -> js::Expr<'a> {
+        if let Type::Array(_) = self.types.expr(receiver) {
+            let receiver = self.trans_expr(receiver, stmts);
+
+            return js::Expr::Member(receiver.boxed(), Cow::Borrowed("length"));
+        }
+
+        let (Type::Struct(type_name, _) | Type::Enum(type_name, _)) = self.types.expr(receiver)
+        else {
+            unreachable!("BUG: methods are resolved by the type checker");
+        };
+        let type_name = type_name.clone();
+        let assoc_fn = &self.assoc_fns[&format!("{type_name}::{}", method.as_str())];
+        let js_name = Cow::Owned(assoc_fn.js_name.clone());
+
+        if !assoc_fn.is_static {
+            return self.trans_instance_method_call(receiver, js_name, args, stmts);
+        }
+
+        let class = js::Expr::Var(Cow::Owned(self.class_name(&type_name)));
+        let callee = js::Expr::Member(class.boxed(), js_name);
+        let args = iter::once(receiver).chain(args).collect::<Vec<_>>();
+        let args = self.trans_args(&args, stmts);
+
+        js::Expr::Call(callee.boxed(), args)
+    }
+
+    /// Translates a call of a method of a class instance, like `p.len()`.
+    fn trans_instance_method_call<'a>(
+        &mut self,
+        receiver: &'a Expr,
+        js_name: Cow<'a, str>,
+        args: &'a [Expr],
+        stmts: &mut Vec<js::Stmt<'a>>,
+    ) -> js::Expr<'a> {
+        let mut receiver = self.trans_expr(receiver, stmts);
+
+        if args.iter().any(Expr::contains_block) {
+            receiver = self.hoist(receiver, stmts);
+        }
+
+        let callee = js::Expr::Member(receiver.boxed(), js_name);
+        let args = self.trans_args(&args.iter().collect::<Vec<_>>(), stmts);
+
+        js::Expr::Call(callee.boxed(), args)
+    }
+
+    /// Translates a struct literal to `new Point({ x: 1, y: 2 })`.
+    ///
+    /// The base of `Point { x: 1, ..p }` is spread into the object before the fields,
+    /// so that the fields override it: `new Point({ ...p, x: 1 })`.
+    ///
+    /// Struct variants are translated to tagged objects like `{ $tag: "Rect", w: 1, h: 2 }`.
+    fn trans_struct_expr<'a>(
+        &mut self,
+        path: &'a Path,
+        fields: &'a [FieldInit],
+        base: Option<&'a Expr>,
+        stmts: &mut Vec<js::Stmt<'a>>,
+    ) -> js::Expr<'a> {
+        let values = fields
+            .iter()
+            .map(|field| (field_name(&field.name), &field.value))
+            .collect::<Vec<_>>();
+        // The base is evaluated last, but spread first
+        let mut props = self.trans_props(&values, base.is_some(), stmts);
+
+        let Some(name) = path.as_ident() else {
+            props.insert(0, tag(path.last().as_str()));
+
+            return js::Expr::Object(props);
+        };
+
+        if let Some(base) = base {
+            let value = self.trans_expr(base, stmts);
+            let value = self.copy_value(base, value, false);
+            props.insert(0, js::Prop::Spread(value));
+        }
+
+        let class = js::Expr::Var(Cow::Owned(self.class_name(name)));
+
+        js::Expr::New(class.boxed(), vec![js::Expr::Object(props)])
+    }
+
+    /// Translates the values of object properties in order.
+    /// Unless `hoist_all` is set, values are only hoisted if later ones need statements.
+    fn trans_props<'a>(
+        &mut self,
+        values: &[(Cow<'a, str>, &'a Expr)],
+        hoist_all: bool,
+        stmts: &mut Vec<js::Stmt<'a>>,
+    ) -> Vec<js::Prop<'a>> {
+        let mut props = Vec::with_capacity(values.len() + 1);
+
+        for (i, (key, expr)) in values.iter().enumerate() {
+            let value = self.trans_expr(expr, stmts);
+            let mut value = self.copy_value(expr, value, false);
+
+            if hoist_all
+                || values[i + 1..]
+                    .iter()
+                    .any(|(_, value)| value.contains_block())
+            {
+                value = self.hoist(value, stmts);
+            }
+
+            props.push(js::Prop::KeyValue(key.clone(), value));
+        }
+
+        props
+    }
+
+    /// Translates a path to a method or associated function like `Point::new` to `Point.new`,
+    /// if it refers to one.
+    ///
+    /// Methods of class instances are wrapped in a function taking `self` as the first argument:
+    /// `(_0, _1) => _0.len(_1)`.
+    fn trans_assoc_fn_path<'a>(&self, path: &Path) -> Option<js::Expr<'a>> {
+        let assoc_fn = self.assoc_fns.get(&path.to_string())?;
+        let js_name = Cow::Owned(assoc_fn.js_name.clone());
+
+        if assoc_fn.is_static {
+            let class = js::Expr::Var(Cow::Owned(self.class_name(&path.segments[0])));
+
+            return Some(js::Expr::Member(class.boxed(), js_name));
+        }
+
+        let args = (0..=assoc_fn.args)
+            .map(|i| Cow::<str>::Owned(tuple_field(i)))
+            .collect::<Vec<_>>();
+        let receiver = js::Expr::Var(args[0].clone());
+        let call = js::Expr::Call(
+            js::Expr::Member(receiver.boxed(), js_name).boxed(),
+            args[1..].iter().cloned().map(js::Expr::Var).collect(),
+        );
+        let body = js::Block {
+            stmts: vec![js::Stmt::Return(Some(call))],
+        };
+
+        let args = args.into_iter().map(js::Pattern::Var).collect();
+
+        Some(js::Expr::Arrow(args, body))
+    }
+
+    /// Returns the full path of the variant that `expr` refers to, like `Option::Some` for `Some`,
+    /// and the name of the variant.
+    fn variant_path<'a>(&mut self, expr: &'a Expr) -> Option<(String, &'a str)> {
+        match &expr.kind {
+            ExprKind::Var(name) if self.resolve_variable(name).is_none() => {
+                let enum_name = prelude::variant_enum(name)?;
+
+                Some((format!("{enum_name}::{}", name.as_str()), name.as_str()))
+            }
+            ExprKind::Path(path) if !self.assoc_fns.contains_key(&path.to_string()) => {
+                Some((path.to_string(), path.last().as_str()))
+            }
+            _ => None,
+        }
+    }
+
+    /// Translates a unit variant like `Shape::Empty` to `{ $tag: "Empty" }`,
+    /// given its full path and name.
+    /// Tuple variants become functions constructing them.
+    fn trans_variant_path<'a>(&mut self, path: &str, name: &'a str) -> js::Expr<'a> {
+        let Some(&len) = self.tuple_variants.get(path) else {
+            return js::Expr::Object(vec![tag(name)]);
+        };
+
+        let args = (0..len)
+            .map(|i| Cow::<str>::Owned(tuple_field(i)))
+            .collect::<Vec<_>>();
+        let props = iter::once(tag(name))
+            .chain(
+                args.iter()
+                    .map(|arg| js::Prop::KeyValue(arg.clone(), js::Expr::Var(arg.clone()))),
+            )
+            .collect();
+        let body = js::Block {
+            stmts: vec![js::Stmt::Return(Some(js::Expr::Object(props)))],
+        };
+        let args = args.into_iter().map(js::Pattern::Var).collect();
+
+        js::Expr::Arrow(args, body)
+    }
+
+    /// Translates a `match` to an `if` chain testing the patterns of the arms in order.
+    /// The value of the matching arm is assigned to `target`, if any.
+    ///
+    /// ```js
+    /// if (shape.$tag === "Circle")
+    /// {
+    ///     const r = shape._0;
+    ///     …
+    /// }
+    /// else
+    /// {
+    ///     …
+    /// }
+    /// ```
+    ///
+    /// A guard can only be checked after binding the variables of its pattern,
+    /// so if there are guards, arms are tested one after another instead,
+    /// leaving a labeled block once an arm matched:
+    ///
+    /// ```js
+    /// $tmp1:
+    /// {
+    ///     if (shape.$tag === "Circle")
+    ///     {
+    ///         const r = shape._0;
+    ///         if (r > 0)
+    ///         {
+    ///             …
+    ///             break $tmp1;
+    ///         }
+    ///     }
+    ///     …
+    /// }
+    /// ```
+    fn trans_match<'a>(
+        &mut self,
+        scrutinee: &'a Expr,
+        arms: &'a [Arm],
+        stmts: &mut Vec<js::Stmt<'a>>,
+        target: Option<&Cow<'a, str>>,
+    ) {
+        // Patterns access the scrutinee repeatedly, so it is evaluated only once
+        let scrutinee = match self.trans_expr(scrutinee, stmts) {
+            js::Expr::Var(name) if !self.is_mutable(&name) => name,
+            scrutinee => {
+                let temporary = self.declare_temporary();
+
+                stmts.push(js::Stmt::Let(js::Let {
+                    pattern: js::Pattern::Var(temporary.clone()),
+                    mutable: false,
+                    rhs: Some(scrutinee.boxed()),
+                }));
+
+                temporary
+            }
+        };
+        let has_guards = arms.iter().any(|arm| arm.guard.is_some());
+        let exit = has_guards.then(|| self.declare_temporary());
+        let mut branches = Vec::new();
+
+        for (i, arm) in arms.iter().enumerate() {
+            // Matches are exhaustive, so the last arm doesn't need to be tested
+            let is_last = i + 1 == arms.len();
+            let exit = exit.as_ref().filter(|_| !is_last);
+
+            self.scopes.push(Scope::new());
+            let (cond, block) = self.trans_arm(arm, &scrutinee, target, exit);
+            self.scopes.pop();
+
+            let cond = cond.filter(|_| !is_last || arm.guard.is_some());
+            // Arms after an arm matching anything are unreachable,
+            // but a guard can still fail inside the arm's block
+            let is_irrefutable = cond.is_none() && arm.guard.is_none();
+
+            branches.push((cond, block));
+
+            if is_irrefutable {
+                break;
+            }
+        }
+
+        if let Some(exit) = exit {
+            let stmts_in_block = branches
+                .into_iter()
+                .map(|(cond, block)| match cond {
+                    Some(cond) => js::Stmt::If(js::If {
+                        cond: cond.boxed(),
+                        then: block,
+                        r#else: None,
+                    }),
+                    None => js::Stmt::Block(block),
+                })
+                .collect();
+
+            stmts.push(js::Stmt::Labeled(
+                exit,
+                js::Block {
+                    stmts: stmts_in_block,
+                },
+            ));
+
+            return;
+        }
+
+        let mut chain = None;
+
+        for (cond, block) in branches.into_iter().rev() {
+            chain = Some(match (cond, chain) {
+                (Some(cond), Some(r#else)) => js::Else::If(
+                    js::If {
+                        cond: cond.boxed(),
+                        then: block,
+                        r#else: Some(r#else),
+                    }
+                    .into(),
+                ),
+                _ => js::Else::Block(block),
+            });
+        }
+
+        match chain {
+            Some(js::Else::If(r#if)) => stmts.push(js::Stmt::If(*r#if)),
+            Some(js::Else::Block(block)) => stmts.push(js::Stmt::Block(block)),
+            None => {}
+        }
+    }
+
+    /// Translates an arm of a `match` to the condition of its pattern, if any,
+    /// and a block binding its variables and evaluating its body.
+    /// The block is left with `break exit;` after evaluating the body, if `exit` is given.
+    fn trans_arm<'a>(
+        &mut self,
+        arm: &'a Arm,
+        scrutinee: &Cow<'a, str>,
+        target: Option<&Cow<'a, str>>,
+        exit: Option<&Cow<'a, str>>,
+    ) -> (Option<js::Expr<'a>>, js::Block<'a>) {
+        let mut conds = Vec::new();
+        let mut stmts = Vec::new();
+
+        self.trans_pattern(
+            &arm.pattern,
+            &|| js::Expr::Var(scrutinee.clone()),
+            &mut conds,
+            &mut stmts,
+        );
+
+        let mut body = Vec::new();
+        self.trans_arm_body(&arm.body, &mut body, target);
+
+        if let Some(exit) = exit {
+            body.push(js::Stmt::Break(Some(exit.clone())));
+        }
+
+        match &arm.guard {
+            Some(guard) => {
+                let guard = self.trans_expr(guard, &mut stmts);
+
+                stmts.push(js::Stmt::If(js::If {
+                    cond: guard.boxed(),
+                    then: js::Block { stmts: body },
+                    r#else: None,
+                }));
+            }
+            None => stmts.extend(body),
+        }
+
+        let cond = conds
+            .into_iter()
+            .reduce(|lhs, rhs| js::Expr::And(lhs.boxed(), rhs.boxed()));
+
+        (cond, js::Block { stmts })
+    }
+
+    /// Translates the body of an arm, assigning its value to `target`, if any.
+    /// Blocks are translated directly into the arm.
+    fn trans_arm_body<'a>(
+        &mut self,
+        body: &'a Expr,
+        stmts: &mut Vec<js::Stmt<'a>>,
+        target: Option<&Cow<'a, str>>,
+    ) {
+        let (body_stmts, value) = match &body.kind {
+            ExprKind::Block(block) => (&block.stmts[..], block.expr.as_deref()),
+            _ => (&[][..], Some(body)),
+        };
+
+        self.scopes.push(Scope::new());
+
+        for stmt in body_stmts {
+            self.trans_stmt(stmt, stmts);
+        }
+
+        match (value, target) {
+            (Some(value), Some(target)) => match self.trans_expr(value, stmts) {
+                js::Expr::Undefined => {}
+                value => stmts.push(js::Stmt::Expr(js::Expr::Assign(
+                    js::Expr::Var(target.clone()).boxed(),
+                    value.boxed(),
+                ))),
+            },
+            (Some(value), None) => self.trans_expr_stmt(value, stmts),
+            (None, _) => {}
+        }
+
+        self.scopes.pop();
+    }
+
+    /// Translates a pattern matching the result of `value` into conditions testing it
+    /// and declarations of its variables.
+    fn trans_pattern<'a>(
+        &mut self,
+        pattern: &'a Pattern,
+        value: &dyn std::ops::Fn() -> js::Expr<'a>,
+        conds: &mut Vec<js::Expr<'a>>,
+        bindings: &mut Vec<js::Stmt<'a>>,
+    ) {
+        match &pattern.kind {
+            PatternKind::Wildcard => {}
+            // `None` matches the variant instead of binding a variable
+            PatternKind::Binding {
+                name,
+                mutable: false,
+            } if prelude::variant_enum(name).is_some() => {
+                conds.push(tag_test(value(), name.as_str()));
+            }
+            PatternKind::Binding { name, mutable } => {
+                let name = self.declare_variable(name, *mutable);
+
+                bindings.push(js::Stmt::Let(js::Let {
+                    pattern: js::Pattern::Var(name),
+                    mutable: *mutable,
+                    rhs: Some(value().boxed()),
+                }));
+            }
+            PatternKind::Int { value: n, .. } => {
+                let literal = match self.types.pattern(pattern) {
+                    Type::I64 if *n < 0 => {
+                        js::Expr::Neg(js::Expr::BigInt(n.unsigned_abs()).boxed())
+                    }
+                    Type::I64 => js::Expr::BigInt(n.unsigned_abs()),
+                    _ => js::Expr::Number(*n as f64),
+                };
+
+                conds.push(js::Expr::StrictEq(value().boxed(), literal.boxed()));
+            }
+            PatternKind::Bool(true) => conds.push(value()),
+            PatternKind::Bool(false) => conds.push(js::Expr::Not(value().boxed())),
+            PatternKind::Path(path) => conds.push(tag_test(value(), path.last().as_str())),
+            PatternKind::Tuple(patterns) => {
+                for (i, pattern) in patterns.iter().enumerate() {
+                    let element =
+                        || js::Expr::Index(value().boxed(), js::Expr::Number(i as f64).boxed());
+                    self.trans_pattern(pattern, &element, conds, bindings);
+                }
+            }
+            PatternKind::TupleVariant(path, patterns) => {
+                conds.push(tag_test(value(), path.last().as_str()));
+
+                for (i, pattern) in patterns.iter().enumerate() {
+                    let field = || js::Expr::Member(value().boxed(), Cow::Owned(tuple_field(i)));
+                    self.trans_pattern(pattern, &field, conds, bindings);
+                }
+            }
+            PatternKind::Struct { path, fields, .. } => {
+                if path.as_ident().is_none() {
+                    conds.push(tag_test(value(), path.last().as_str()));
+                }
+
+                for field in fields {
+                    let name = field_name(&field.name);
+                    let field_value = || js::Expr::Member(value().boxed(), name.clone());
+                    self.trans_pattern(&field.pattern, &field_value, conds, bindings);
+                }
+            }
+        }
+    }
+
+    /// Translates the pattern of a function or closure argument.
+    /// The values of `mut` arguments are copied into their variables by the `prologue`
+    /// of the function, so that changing them doesn't change the value of the caller.
+    fn trans_param<'a>(
+        &mut self,
+        pattern: &'a Pattern,
+        is_fn_arg: bool,
+        prologue: &mut Vec<js::Stmt<'a>>,
+    ) -> js::Pattern<'a> {
+        if !self.copies_value(pattern) {
+            return self.trans_arg_pattern(pattern, is_fn_arg);
+        }
+
+        let arg = self.declare_temporary();
+
+        if let Some(js_pattern) = self.trans_irrefutable_pattern(pattern, false) {
+            let value = js::Expr::Var(arg.clone());
+
+            prologue.push(js::Stmt::Let(js::Let {
+                pattern: js_pattern,
+                mutable: true,
+                rhs: Some(self.call_helper(Helper::Clone, vec![value]).boxed()),
+            }));
+        }
+
+        js::Pattern::Var(arg)
+    }
+
+    /// Copies the value of `expr` if it would otherwise be shared with a variable that can change it:
+    /// if it is stored `into_mut` variables, unless it was just built,
+    /// or if it is read out of a `mut` variable.
+    fn copy_value<'a>(&mut self, expr: &Expr, value: js::Expr<'a>, into_mut: bool) -> js::Expr<'a> {
+        let is_shared = match into_mut {
+            true => !self.is_fresh(expr),
+            false => self.reads_mut_place(expr),
+        };
+
+        match is_shared && !self.types.expr(expr).is_primitive() {
+            true => self.call_helper(Helper::Clone, vec![value]),
+            false => value,
+        }
+    }
+
+    /// Copies the result of a method whose receiver is read out of a `mut` variable,
+    /// since the result may be a part of the receiver, which the method gets without a copy.
+    fn copy_method_result<'a>(
+        &mut self,
+        receiver: &Expr,
+        result_type: &Type,
+        call: js::Expr<'a>,
+    ) -> js::Expr<'a> {
+        match self.reads_mut_place(receiver) && !result_type.is_primitive() {
+            true => self.call_helper(Helper::Clone, vec![call]),
+            false => call,
+        }
+    }
+
+    /// Whether `expr` builds a new value that no variable refers to yet, like `[1, 2]`.
+    fn is_fresh(&self, expr: &Expr) -> bool {
+        if self.types.expr(expr).is_primitive() {
+            return true;
+        }
+
+        match &expr.kind {
+            ExprKind::Array(elements) | ExprKind::Tuple(elements) => {
+                elements.iter().all(|element| self.is_fresh(element))
+            }
+            ExprKind::Struct {
+                fields, base: None, ..
+            } => fields.iter().all(|field| self.is_fresh(&field.value)),
+            // Every element gets its own copy of the value
+            ExprKind::Repeat { .. } => true,
+            ExprKind::Parens(expr) => self.is_fresh(expr),
+            _ => false,
+        }
+    }
+
+    /// Whether `expr` is a place like `a.b[0]` in a `mut` variable.
+    fn reads_mut_place(&self, expr: &Expr) -> bool {
+        match &expr.kind {
+            ExprKind::Var(name) => self
+                .scopes
+                .iter()
+                .rev()
+                .flat_map(|scope| scope.variables.iter().rev())
+                .find(|variable| variable.jester_name == name.as_str())
+                .is_some_and(|variable| variable.mutable),
+            ExprKind::Field(expr, _) | ExprKind::Index(expr, _) | ExprKind::Parens(expr) => {
+                self.reads_mut_place(expr)
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether the value matched by `pattern` is copied into its variables,
+    /// since JS objects are shared and `mut` bindings can change them.
+    fn copies_value(&self, pattern: &Pattern) -> bool {
+        pattern.is_mutable() && !self.types.pattern(pattern).is_primitive()
+    }
+
+    /// Translates the pattern of an argument, which is named by a temporary if it's `_`.
+    /// The arguments of functions are declared with [`Trans::declare_arg`].
+    fn trans_arg_pattern<'a>(&mut self, pattern: &'a Pattern, is_fn_arg: bool) -> js::Pattern<'a> {
+        self.trans_irrefutable_pattern(pattern, is_fn_arg)
+            .unwrap_or_else(|| js::Pattern::Var(self.declare_temporary()))
+    }
+
+    /// Translates a pattern matching every value to a JS destructuring pattern
+    /// and declares its variables, like `let (x, Point { y, .. }) = …` to `[x, { y }]`.
+    /// Returns `None` for `_`, which doesn't bind anything.
+    fn trans_irrefutable_pattern<'a>(
+        &mut self,
+        pattern: &'a Pattern,
+        is_fn_arg: bool,
+    ) -> Option<js::Pattern<'a>> {
+        let mut trans_props = |props: Vec<(Cow<'a, str>, &'a Pattern)>| {
+            let props = props
+                .into_iter()
+                .filter_map(|(key, pattern)| {
+                    Some((key, self.trans_irrefutable_pattern(pattern, is_fn_arg)?))
+                })
+                .collect();
+
+            js::Pattern::Object(props)
+        };
+
+        Some(match &pattern.kind {
+            PatternKind::Wildcard => return None,
+            PatternKind::Binding { name, mutable } if is_fn_arg => {
+                js::Pattern::Var(self.declare_arg(name, *mutable))
+            }
+            PatternKind::Binding { name, mutable } => {
+                js::Pattern::Var(self.declare_variable(name, *mutable))
+            }
+            PatternKind::Tuple(patterns) => {
+                let mut elements = patterns
+                    .iter()
+                    .map(|pattern| self.trans_irrefutable_pattern(pattern, is_fn_arg))
+                    .collect::<Vec<_>>();
+
+                // Trailing elements that are skipped can be left out
+                while elements.last().is_some_and(Option::is_none) {
+                    elements.pop();
+                }
+
+                js::Pattern::Array(elements)
+            }
+            // Variants only match every value if they are the only variant of their enum
+            PatternKind::Path(_) => js::Pattern::Object(Vec::new()),
+            PatternKind::TupleVariant(_, patterns) => trans_props(
+                patterns
+                    .iter()
+                    .enumerate()
+                    .map(|(i, pattern)| (Cow::Owned(tuple_field(i)), pattern))
+                    .collect(),
+            ),
+            PatternKind::Struct { fields, .. } => trans_props(
+                fields
+                    .iter()
+                    .map(|field| (field_name(&field.name), &field.pattern))
+                    .collect(),
+            ),
+            PatternKind::Int { .. } | PatternKind::Bool(_) => {
+                unreachable!("BUG: refutable patterns are rejected by the type checker")
+            }
+        })
+    }
+
+    /// Translates a block used as a value.
+    ///
+    /// The statements of the block are hoisted into a JS block that assigns
+    /// the block's value to a temporary, which is then used in its place:
+    ///
+    /// ```js
+    /// let $tmp0;
+    /// {
+    ///     …
+    ///     $tmp0 = value;
+    /// }
+    /// ```
+    fn trans_block_expr<'a>(
+        &mut self,
+        block: &'a Block,
+        stmts: &mut Vec<js::Stmt<'a>>,
+    ) -> js::Expr<'a> {
+        let Some(expr) = &block.expr else {
+            stmts.push(js::Stmt::Block(self.trans_scoped_block(block)));
+
+            return js::Expr::Undefined;
+        };
+
+        // `{ value }` doesn't need a JS block
+        if block.stmts.is_empty() {
+            self.scopes.push(Scope::new());
+            let value = self.trans_expr(expr, stmts);
+            self.scopes.pop();
+
+            return value;
+        }
+
+        let temporary = self.declare_temporary();
+        let block = self.trans_branch(block, Some(&temporary));
+
+        stmts.push(js::Stmt::Let(js::Let {
+            pattern: js::Pattern::Var(temporary.clone()),
+            mutable: true,
+            rhs: None,
+        }));
+        stmts.push(js::Stmt::Block(block));
+
+        js::Expr::Var(temporary)
+    }
+
+    /// Translates an `if` used as a value.
+    ///
+    /// Branches that are plain expressions are translated to a ternary,
+    /// otherwise the value of the taken branch is assigned to a temporary:
+    ///
+    /// ```js
+    /// let $tmp0;
+    /// if (cond) {
+    ///     …
+    ///     $tmp0 = then;
+    /// }
+    /// else {
+    ///     …
+    ///     $tmp0 = else;
+    /// }
+    /// ```
+    fn trans_if_expr<'a>(
+        &mut self,
+        cond: &'a Expr,
+        then: &'a Block,
+        r#else: Option<&'a Expr>,
+        stmts: &mut Vec<js::Stmt<'a>>,
+    ) -> js::Expr<'a> {
+        // Without an `else` the value is always `()`
+        let Some(r#else) = r#else else {
+            let r#if = self.trans_if(cond, then, None, stmts, None);
+            stmts.push(js::Stmt::If(r#if));
+
+            return js::Expr::Undefined;
+        };
+
+        if !cond.contains_block() && !then.contains_block() && !r#else.contains_block() {
+            let cond = self.trans_expr(cond, stmts);
+            let then = self.trans_block_expr(then, stmts);
+            let r#else = self.trans_expr(r#else, stmts);
+
+            return js::Expr::Ternary(cond.boxed(), then.boxed(), r#else.boxed());
+        }
+
+        let temporary = self.declare_temporary();
+        let r#if = self.trans_if(cond, then, Some(r#else), stmts, Some(&temporary));
+
+        stmts.push(js::Stmt::Let(js::Let {
+            pattern: js::Pattern::Var(temporary.clone()),
+            mutable: true,
+            rhs: None,
+        }));
+        stmts.push(js::Stmt::If(r#if));
+
+        js::Expr::Var(temporary)
+    }
+
+    /// Translates an `if` to a JS `if` statement.
+    /// The value of the taken branch is assigned to `target`, if any.
+    fn trans_if<'a>(
+        &mut self,
+        cond: &'a Expr,
+        then: &'a Block,
+        r#else: Option<&'a Expr>,
+        stmts: &mut Vec<js::Stmt<'a>>,
+        target: Option<&Cow<'a, str>>,
+    ) -> js::If<'a> {
+        let cond = self.trans_expr(cond, stmts);
+        let then = self.trans_branch(then, target);
+        let r#else = match r#else.map(|r#else| &r#else.kind) {
+            Some(ExprKind::If { cond, then, r#else }) => {
+                // Statements hoisted out of the condition must only run if it gets evaluated
+                let mut else_stmts = Vec::new();
+                let r#if = self.trans_if(cond, then, r#else.as_deref(), &mut else_stmts, target);
+
+                if else_stmts.is_empty() {
+                    Some(js::Else::If(r#if.into()))
+                } else {
+                    else_stmts.push(js::Stmt::If(r#if));
+                    Some(js::Else::Block(js::Block { stmts: else_stmts }))
+                }
+            }
+            Some(ExprKind::Block(block)) => Some(js::Else::Block(self.trans_branch(block, target))),
+            Some(_) => unreachable!("BUG: `else` is followed by a block or an `if`"),
+            None => None,
+        };
+
+        js::If {
+            cond: cond.boxed(),
+            then,
+            r#else,
+        }
+    }
+
+    /// Translates a block into a new scope, assigning its value to `target`, if any.
+    fn trans_branch<'a>(
+        &mut self,
+        block: &'a Block,
+        target: Option<&Cow<'a, str>>,
+    ) -> js::Block<'a> {
+        let Some(target) = target else {
+            return self.trans_scoped_block(block);
+        };
+
+        self.scopes.push(Scope::new());
+
+        let mut stmts = Vec::new();
+        for stmt in &block.stmts {
+            self.trans_stmt(stmt, &mut stmts);
+        }
+
+        let value = match &block.expr {
+            Some(expr) => self.trans_expr(expr, &mut stmts),
+            None => js::Expr::Undefined,
+        };
+
+        self.scopes.pop();
+
+        stmts.push(js::Stmt::Expr(js::Expr::Assign(
+            js::Expr::Var(target.clone()).boxed(),
+            value.boxed(),
+        )));
+
+        js::Block { stmts }
+    }
+
+    /// Stores the value of `expr` in a temporary,
+    /// so that statements hoisted afterwards can't observe a different evaluation order.
+    fn hoist<'a>(&mut self, expr: js::Expr<'a>, stmts: &mut Vec<js::Stmt<'a>>) -> js::Expr<'a> {
+        // Immutable variables can't change, so their evaluation can be delayed
+        match &expr {
+            js::Expr::Number(_) | js::Expr::BigInt(_) => return expr,
+            js::Expr::Var(name) if !self.is_mutable(name) => return expr,
+            _ => {}
+        }
+
+        let temporary = self.declare_temporary();
+
+        stmts.push(js::Stmt::Let(js::Let {
+            pattern: js::Pattern::Var(temporary.clone()),
+            mutable: false,
+            rhs: Some(expr.boxed()),
+        }));
+
+        js::Expr::Var(temporary)
+    }
+}
+
+/// Wraps the result of an operation on integers of type `ty` around on overflow,
+/// like `x | 0` for `i32` and `BigInt.asIntN(64, x)` for `i64`.
+fn wrap<'a>(ty: &Type, expr: js::Expr<'a>) -> js::Expr<'a> {
+    match ty {
+        Type::I32 => js::Expr::BitOr(expr.boxed(), js::Expr::Number(0.0).boxed()),
+        Type::I64 => js::Expr::Call(
+            global_member("BigInt", "asIntN").boxed(),
+            vec![js::Expr::Number(64.0), expr],
+        ),
+        _ => expr,
+    }
+}
+
+/// The integer `1` of type `ty`.
+fn one<'a>(ty: &Type) -> js::Expr<'a> {
+    match ty {
+        Type::I64 => js::Expr::BigInt(1),
+        _ => js::Expr::Number(1.0),
+    }
+}
+
+/// A member of a JS global, like `Math.imul`.
+fn global_member<'a>(global: &'static str, name: &'static str) -> js::Expr<'a> {
+    js::Expr::Member(
+        js::Expr::Var(Cow::Borrowed(global)).boxed(),
+        Cow::Borrowed(name),
+    )
+}
+
+/// A field of a struct, like `p.x`, or of a tuple, like `t[0]` for `t.0`.
+fn member<'a>(expr: js::Expr<'a>, name: &'a Ident) -> js::Expr<'a> {
+    match name.parse::<usize>() {
+        Ok(index) => js::Expr::Index(expr.boxed(), js::Expr::Number(index as f64).boxed()),
+        Err(_) => js::Expr::Member(expr.boxed(), field_name(name)),
+    }
+}
+
+/// The JS property of a field, which gets a `$` appended if it would be special to JS:
+/// `__proto__` would set the prototype of the value instead.
+fn field_name<'a>(name: &'a Ident) -> Cow<'a, str> {
+    match name.as_str() {
+        "__proto__" => Cow::Owned(format!("{}$", name.as_str())),
+        name => Cow::Borrowed(name),
+    }
+}
+
+/// The tag of a variant, like `$tag: "Circle"` for `Shape::Circle`.
+fn tag(name: &str) -> js::Prop<'_> {
+    js::Prop::KeyValue(Cow::Borrowed(TAG), js::Expr::String(Cow::Borrowed(name)))
+}
+
+/// Tests whether `value` is of the variant `name`, like `shape.$tag === "Circle"`.
+fn tag_test<'a>(value: js::Expr<'a>, name: &'a str) -> js::Expr<'a> {
+    let tag = js::Expr::Member(value.boxed(), Cow::Borrowed(TAG));
+
+    js::Expr::StrictEq(tag.boxed(), js::Expr::String(Cow::Borrowed(name)).boxed())
+}
+
+/// The JS property holding the `i`th field of a tuple variant.
+fn tuple_field(i: usize) -> String {
+    format!("_{i}")
+}
+
+#[derive(Debug)]
+struct Scope {
+    variables: Vec<Variable>,
+}
+
+impl Scope {
+    fn new() -> Self {
+        Self {
+            variables: Vec::new(),
+        }
+    }
+
+    fn declare_variable<'a>(
+        &mut self,
+        jester_name: &'a str,
+        js_name: &str,
+        mutable: bool,
+    ) -> Cow<'a, str> {
+        self.variables.push(Variable {
+            jester_name: jester_name.into(),
+            js_name: js_name.into(),
+            mutable,
+        });
+
+        if js_name == jester_name {
+            Cow::Borrowed(jester_name)
+        } else {
+            Cow::Owned(js_name.into())
+        }
+    }
+}
+
+/// An arithmetic operator, which wraps around on integer overflow like Rust's release builds
+#[derive(Clone, Copy)]
+enum Arithmetic {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+impl Arithmetic {
+    fn apply<'a>(self, ty: &Type, lhs: Box<js::Expr<'a>>, rhs: Box<js::Expr<'a>>) -> js::Expr<'a> {
+        let expr = match self {
+            Arithmetic::Add => js::Expr::Add(lhs, rhs),
+            Arithmetic::Sub => js::Expr::Sub(lhs, rhs),
+            // `a * b | 0` would lose precision for products above 2^53
+            Arithmetic::Mul if *ty == Type::I32 => {
+                return js::Expr::Call(global_member("Math", "imul").boxed(), vec![*lhs, *rhs]);
+            }
+            Arithmetic::Mul => js::Expr::Mul(lhs, rhs),
+            // Also truncates `i32` division, `BigInt` division already truncates
+            Arithmetic::Div => js::Expr::Div(lhs, rhs),
+        };
+
+        wrap(ty, expr)
+    }
+}
+
+/// How a method or associated function is called in JS.
+#[derive(Debug)]
+struct AssocFn {
+    js_name: String,
+    /// Whether it is a `static` method of the class instead of a method of its instances
+    is_static: bool,
+    /// The number of arguments besides `self`
+    args: usize,
+}
+
+#[derive(Debug)]
+struct Loop {
+    label: Option<String>,
+    js_label: Option<String>,
+    /// The variable that values passed to `break` are assigned to.
+    result: Option<String>,
 }
 
 #[derive(Debug)]
 struct Variable {
     jester_name: String,
     js_name: String,
+    mutable: bool,
 }
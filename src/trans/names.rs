@@ -0,0 +1,186 @@
+use std::collections::HashSet;
+
+use crate::js;
+
+/// Picks the JS names of Jester variables.
+///
+/// A JS name is never reused within a function, even in unrelated blocks.
+/// JS scopes `let` and `const` to their whole block, so an inner `x`
+/// would otherwise hide an outer `x` that is used before its declaration.
+#[derive(Debug, Default)]
+pub struct Names {
+    /// Names of top-level declarations, which are visible in every function
+    globals: HashSet<String>,
+    /// Names declared in the current function, if any
+    locals: Option<HashSet<String>>,
+}
+
+impl Names {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn enter_fn(&mut self) {
+        self.locals = Some(HashSet::new());
+    }
+
+    pub fn exit_fn(&mut self) {
+        self.locals = None;
+    }
+
+    /// Returns a JS name for `name` that no other visible variable uses.
+    pub fn declare(&mut self, name: &str) -> String {
+        let js_name = unique_name(name, |js_name| {
+            self.globals.contains(js_name)
+                || self
+                    .locals
+                    .as_ref()
+                    .is_some_and(|locals| locals.contains(js_name))
+        });
+
+        self.insert(js_name)
+    }
+
+    /// Returns a JS name for a function argument.
+    ///
+    /// Arguments keep their names where possible, since they are in scope
+    /// for the whole function and may shadow top-level declarations.
+    pub fn declare_arg(&mut self, name: &str) -> String {
+        let js_name = unique_name(name, |js_name| {
+            self.locals
+                .as_ref()
+                .is_some_and(|locals| locals.contains(js_name))
+        });
+
+        self.insert(js_name)
+    }
+
+    fn insert(&mut self, js_name: String) -> String {
+        let names = self.locals.as_mut().unwrap_or(&mut self.globals);
+        names.insert(js_name.clone());
+
+        js_name
+    }
+}
+
+/// Appends `__2`, `__3`, … to `name` until it is not used.
+pub fn unique_name(name: &str, is_used: impl Fn(&str) -> bool) -> String {
+    let name = escape(name);
+
+    if !is_used(&name) {
+        return name;
+    }
+
+    (2..)
+        .map(|n| format!("{name}__{n}"))
+        .find(|js_name| !is_used(js_name))
+        .expect("BUG: ran out of names")
+}
+
+/// Turns `name` into a valid JS identifier that doesn't refer to a JS global.
+///
+/// Characters that JS doesn't accept become `$u` and their hex code point, followed by `$`,
+/// which can't clash with Jester names since they never contain `$`.
+pub fn escape(name: &str) -> String {
+    if RESERVED_WORDS.contains(&name) || GLOBALS.contains(&name) {
+        return format!("{name}_");
+    }
+
+    if js::is_identifier(name) {
+        return name.to_owned();
+    }
+
+    name.chars()
+        .enumerate()
+        .map(|(i, c)| {
+            if js::is_identifier_char(c, i == 0) {
+                c.to_string()
+            } else {
+                format!("$u{:x}$", c as u32)
+            }
+        })
+        .collect()
+}
+
+/// See <https://developer.mozilla.org/en-US/docs/Web/JavaScript/Reference/Lexical_grammar#keywords>
+const RESERVED_WORDS: &[&str] = &[
+    "arguments",
+    "await",
+    "break",
+    "case",
+    "catch",
+    "class",
+    "const",
+    "continue",
+    "debugger",
+    "default",
+    "delete",
+    "do",
+    "else",
+    "enum",
+    "eval",
+    "export",
+    "extends",
+    "false",
+    "finally",
+    "for",
+    "function",
+    "if",
+    "implements",
+    "import",
+    "in",
+    "instanceof",
+    "interface",
+    "let",
+    "new",
+    "null",
+    "package",
+    "private",
+    "protected",
+    "public",
+    "return",
+    "static",
+    "super",
+    "switch",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "typeof",
+    "var",
+    "void",
+    "while",
+    "with",
+    "yield",
+];
+
+/// Globals that generated code or the JS environment rely on.
+const GLOBALS: &[&str] = &[
+    "Array",
+    "BigInt",
+    "Boolean",
+    "Error",
+    "Function",
+    "Infinity",
+    "JSON",
+    "Map",
+    "Math",
+    "NaN",
+    "Number",
+    "Object",
+    "Promise",
+    "RangeError",
+    "Reflect",
+    "Set",
+    "String",
+    "Symbol",
+    "console",
+    "document",
+    "globalThis",
+    "isFinite",
+    "isNaN",
+    "parseFloat",
+    "parseInt",
+    "undefined",
+    "window",
+];
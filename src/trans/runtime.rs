@@ -0,0 +1,242 @@
+use std::borrow::Cow;
+
+use crate::js;
+
+/// A JS function that translated code relies on.
+/// Only the helpers that are used get emitted, at the start of the file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Helper {
+    /// `$checkIndex(array, index)` returns `index` if it's in bounds and throws otherwise
+    CheckIndex,
+    /// `$checkDivisor(divisor)` returns `divisor` if it isn't zero and throws otherwise
+    CheckDivisor,
+    /// `$eq(a, b)` compares structs, variants, tuples and arrays by their contents
+    Eq,
+    /// `$clone(value)` copies structs, variants, tuples and arrays with their contents
+    Clone,
+}
+
+impl Helper {
+    /// The name of the JS function.
+    /// `$` can't appear in Jester identifiers, so it never collides with a variable.
+    pub fn name(self) -> &'static str {
+        match self {
+            Helper::CheckIndex => "$checkIndex",
+            Helper::CheckDivisor => "$checkDivisor",
+            Helper::Eq => "$eq",
+            Helper::Clone => "$clone",
+        }
+    }
+
+    pub fn decl<'a>(self) -> js::Stmt<'a> {
+        let var = |name: &'static str| js::Expr::Var(Cow::Borrowed(name));
+
+        match self {
+            Helper::CheckIndex => {
+                let len = || js::Expr::Member(var("array").boxed(), Cow::Borrowed("length"));
+                let out_of_bounds = js::Expr::Or(
+                    js::Expr::Lt(var("index").boxed(), js::Expr::Number(0.0).boxed()).boxed(),
+                    js::Expr::Ge(var("index").boxed(), len().boxed()).boxed(),
+                );
+                let message = js::Expr::Template(vec![
+                    js::TemplatePart::Str(Cow::Borrowed("index out of bounds: the len is ")),
+                    js::TemplatePart::Expr(len()),
+                    js::TemplatePart::Str(Cow::Borrowed(" but the index is ")),
+                    js::TemplatePart::Expr(var("index")),
+                ]);
+                let error = js::Expr::New(var("RangeError").boxed(), vec![message]);
+
+                js::Stmt::Fn(js::Fn {
+                    name: Cow::Borrowed(self.name()),
+                    args: vec![
+                        js::Pattern::Var(Cow::Borrowed("array")),
+                        js::Pattern::Var(Cow::Borrowed("index")),
+                    ],
+                    body: js::Block {
+                        stmts: vec![
+                            js::Stmt::If(js::If {
+                                cond: out_of_bounds.boxed(),
+                                then: js::Block {
+                                    stmts: vec![js::Stmt::Throw(error)],
+                                },
+                                r#else: None,
+                            }),
+                            js::Stmt::Return(Some(var("index"))),
+                        ],
+                    },
+                })
+            }
+            // ```js
+            // function $checkDivisor(divisor) {
+            //     if (!divisor) throw new RangeError("attempt to divide by zero");
+            //     return divisor;
+            // }
+            // ```
+            Helper::CheckDivisor => {
+                // Both `0` and `0n` are falsy
+                let is_zero = js::Expr::Not(var("divisor").boxed());
+                let message = js::Expr::String(Cow::Borrowed("attempt to divide by zero"));
+                let error = js::Expr::New(var("RangeError").boxed(), vec![message]);
+
+                js::Stmt::Fn(js::Fn {
+                    name: Cow::Borrowed(self.name()),
+                    args: vec![js::Pattern::Var(Cow::Borrowed("divisor"))],
+                    body: js::Block {
+                        stmts: vec![
+                            js::Stmt::If(js::If {
+                                cond: is_zero.boxed(),
+                                then: js::Block {
+                                    stmts: vec![js::Stmt::Throw(error)],
+                                },
+                                r#else: None,
+                            }),
+                            js::Stmt::Return(Some(var("divisor"))),
+                        ],
+                    },
+                })
+            }
+            // ```js
+            // function $eq(a, b) {
+            //     if (Object(a) !== a) return a === b;
+            //     const keys = Object.keys(a);
+            //     return keys.length === Object.keys(b).length && keys.every((key) => …);
+            // }
+            // ```
+            Helper::Eq => {
+                let object = |value| js::Expr::Call(var("Object").boxed(), vec![var(value)]);
+                let keys = |value| {
+                    let keys = js::Expr::Member(var("Object").boxed(), Cow::Borrowed("keys"));
+
+                    js::Expr::Call(keys.boxed(), vec![var(value)])
+                };
+                let len =
+                    |keys: js::Expr<'a>| js::Expr::Member(keys.boxed(), Cow::Borrowed("length"));
+                let field = |value| js::Expr::Index(var(value).boxed(), var("key").boxed());
+                let fields_eq = js::Expr::Arrow(
+                    vec![js::Pattern::Var(Cow::Borrowed("key"))],
+                    js::Block {
+                        stmts: vec![js::Stmt::Return(Some(js::Expr::Call(
+                            var(self.name()).boxed(),
+                            vec![field("a"), field("b")],
+                        )))],
+                    },
+                );
+                let every = js::Expr::Call(
+                    js::Expr::Member(var("keys").boxed(), Cow::Borrowed("every")).boxed(),
+                    vec![fields_eq],
+                );
+
+                js::Stmt::Fn(js::Fn {
+                    name: Cow::Borrowed(self.name()),
+                    args: vec![
+                        js::Pattern::Var(Cow::Borrowed("a")),
+                        js::Pattern::Var(Cow::Borrowed("b")),
+                    ],
+                    body: js::Block {
+                        stmts: vec![
+                            // Primitives aren't objects
+                            js::Stmt::If(js::If {
+                                cond: js::Expr::StrictNe(object("a").boxed(), var("a").boxed())
+                                    .boxed(),
+                                then: js::Block {
+                                    stmts: vec![js::Stmt::Return(Some(js::Expr::StrictEq(
+                                        var("a").boxed(),
+                                        var("b").boxed(),
+                                    )))],
+                                },
+                                r#else: None,
+                            }),
+                            js::Stmt::Let(js::Let {
+                                pattern: js::Pattern::Var(Cow::Borrowed("keys")),
+                                mutable: false,
+                                rhs: Some(keys("a").boxed()),
+                            }),
+                            js::Stmt::Return(Some(js::Expr::And(
+                                js::Expr::StrictEq(
+                                    len(var("keys")).boxed(),
+                                    len(keys("b")).boxed(),
+                                )
+                                .boxed(),
+                                every.boxed(),
+                            ))),
+                        ],
+                    },
+                })
+            }
+            // ```js
+            // function $clone(value) {
+            //     if (typeof value !== "object") return value;
+            //     if (Array.isArray(value)) return value.map($clone);
+            //     const copy = Object.create(Object.getPrototypeOf(value));
+            //     for (const key of Object.keys(value)) copy[key] = $clone(value[key]);
+            //     return copy;
+            // }
+            // ```
+            Helper::Clone => {
+                let object_fn = |name| js::Expr::Member(var("Object").boxed(), Cow::Borrowed(name));
+                let is_array = js::Expr::Call(
+                    js::Expr::Member(var("Array").boxed(), Cow::Borrowed("isArray")).boxed(),
+                    vec![var("value")],
+                );
+                let map = js::Expr::Call(
+                    js::Expr::Member(var("value").boxed(), Cow::Borrowed("map")).boxed(),
+                    vec![var(self.name())],
+                );
+                let prototype =
+                    js::Expr::Call(object_fn("getPrototypeOf").boxed(), vec![var("value")]);
+                let keys = js::Expr::Call(object_fn("keys").boxed(), vec![var("value")]);
+                let field = |value| js::Expr::Index(var(value).boxed(), var("key").boxed());
+                let copy_field = js::Expr::Assign(
+                    field("copy").boxed(),
+                    js::Expr::Call(var(self.name()).boxed(), vec![field("value")]).boxed(),
+                );
+
+                js::Stmt::Fn(js::Fn {
+                    name: Cow::Borrowed(self.name()),
+                    args: vec![js::Pattern::Var(Cow::Borrowed("value"))],
+                    body: js::Block {
+                        stmts: vec![
+                            // Primitives and functions are never changed in place
+                            js::Stmt::If(js::If {
+                                cond: js::Expr::StrictNe(
+                                    js::Expr::TypeOf(var("value").boxed()).boxed(),
+                                    js::Expr::String(Cow::Borrowed("object")).boxed(),
+                                )
+                                .boxed(),
+                                then: js::Block {
+                                    stmts: vec![js::Stmt::Return(Some(var("value")))],
+                                },
+                                r#else: None,
+                            }),
+                            js::Stmt::If(js::If {
+                                cond: is_array.boxed(),
+                                then: js::Block {
+                                    stmts: vec![js::Stmt::Return(Some(map))],
+                                },
+                                r#else: None,
+                            }),
+                            // Keeps the class of structs, so that their methods still work
+                            js::Stmt::Let(js::Let {
+                                pattern: js::Pattern::Var(Cow::Borrowed("copy")),
+                                mutable: false,
+                                rhs: Some(
+                                    js::Expr::Call(object_fn("create").boxed(), vec![prototype])
+                                        .boxed(),
+                                ),
+                            }),
+                            js::Stmt::ForOf(js::ForOf {
+                                label: None,
+                                var: Cow::Borrowed("key"),
+                                iter: keys.boxed(),
+                                body: js::Block {
+                                    stmts: vec![js::Stmt::Expr(copy_field)],
+                                },
+                            }),
+                            js::Stmt::Return(Some(var("copy"))),
+                        ],
+                    },
+                })
+            }
+        }
+    }
+}
@@ -0,0 +1,2868 @@
+mod exhaustiveness;
+mod ty;
+
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+
+use ariadne::Report;
+
+use crate::diagnostics::Diagnostics;
+use crate::syntax::format::FormatPart;
+use crate::syntax::pattern::PatternKind;
+use crate::syntax::r#enum::{Variant, VariantFields};
+use crate::syntax::r#match::Arm;
+use crate::syntax::r#struct::{Field, FieldInit};
+use crate::syntax::r#type::TypeKind;
+use crate::syntax::{
+    Block, Closure, Enum, Expr, ExprKind, File, Fn, Ident, Impl, Item, Let, Path, Pattern, Span,
+    Stmt, Struct,
+};
+use crate::{prelude, report, syntax};
+
+pub use ty::Type;
+
+/// Checks the types of a parsed file before it gets translated.
+pub struct Typeck<'a> {
+    scopes: Vec<Scope<'a>>,
+    structs: HashMap<&'a str, &'a Struct<'a>>,
+    enums: HashMap<&'a str, &'a Enum<'a>>,
+    /// Methods and associated functions, by the name of their type
+    impls: HashMap<&'a str, Vec<&'a Fn<'a>>>,
+    /// The `impl` blocks of methods and associated functions, by the span of their name
+    fn_impls: HashMap<Span, &'a Impl<'a>>,
+    types: Types,
+    loops: Vec<Loop<'a>>,
+    /// Number of `break`s checked so far, used to tell whether a loop ever finishes.
+    breaks: usize,
+    /// The return type of the function being checked.
+    /// Closures without a known return type get it from their first `return`.
+    return_type: Option<Expected>,
+    /// The type parameters of the function being checked, which its annotations can refer to.
+    type_params: Vec<Ident<'a>>,
+    diagnostics: Diagnostics,
+    /// Structs and enums whose declarations have errors, so that their uses aren't reported too
+    poisoned_types: HashSet<&'a str>,
+    /// Functions whose signatures have errors, by the span of their name
+    poisoned_fns: HashSet<Span>,
+}
+
+impl<'a> Typeck<'a> {
+    pub fn new() -> Self {
+        Self {
+            scopes: vec![Scope::new()],
+            structs: HashMap::new(),
+            enums: HashMap::new(),
+            impls: HashMap::new(),
+            fn_impls: HashMap::new(),
+            types: Types::default(),
+            loops: Vec::new(),
+            breaks: 0,
+            return_type: None,
+            type_params: Vec::new(),
+            diagnostics: Diagnostics::new(),
+            poisoned_types: HashSet::new(),
+            poisoned_fns: HashSet::new(),
+        }
+    }
+
+    /// Reports an error about the code at `span`, unless it was already reported.
+    fn report(&mut self, span: Span, error: Error) {
+        if let Error::Report(report) = error {
+            self.diagnostics.error(span, report);
+        }
+    }
+
+    fn last_scope_mut(&mut self) -> &mut Scope<'a> {
+        self.scopes
+            .last_mut()
+            .expect("BUG: expected at least one scope")
+    }
+
+    fn declare_variable(
+        &mut self,
+        name: &'a Ident<'a>,
+        ty: Type,
+        mutable: bool,
+        r#fn: Option<&'a Fn<'a>>,
+    ) {
+        self.last_scope_mut().variables.push(Variable {
+            name: name.as_str(),
+            span: name.span(),
+            ty,
+            mutable,
+            r#fn,
+            poisoned: false,
+            pending: None,
+        });
+    }
+
+    /// Declares a variable whose declaration has errors, so that its uses aren't reported too.
+    fn declare_poisoned_variable(&mut self, name: &'a Ident<'a>) {
+        self.last_scope_mut().variables.push(Variable {
+            name: name.as_str(),
+            span: name.span(),
+            ty: Type::Never,
+            mutable: true,
+            r#fn: None,
+            poisoned: true,
+            pending: None,
+        });
+    }
+
+    /// Declares a variable bound to a closure whose parameter types are taken from its first call.
+    fn declare_pending_closure(
+        &mut self,
+        name: &'a Ident<'a>,
+        closure: &'a Closure<'a>,
+        span: Span,
+        pattern_span: Span,
+    ) {
+        let pending = PendingClosure {
+            closure,
+            span,
+            pattern_span,
+            scopes: self.scopes.len(),
+            variables: self.last_scope_mut().variables.len(),
+        };
+
+        self.last_scope_mut().variables.push(Variable {
+            name: name.as_str(),
+            span: name.span(),
+            ty: Type::Never,
+            mutable: false,
+            r#fn: None,
+            poisoned: false,
+            pending: Some(pending),
+        });
+    }
+
+    /// Poisons the variables of a `let` or argument pattern that failed to check.
+    fn poison_bindings(&mut self, pattern: &'a Pattern<'a>) {
+        for name in pattern.bindings() {
+            // Paths like `None` are parsed as bindings but refer to variants
+            if prelude_variant(name).is_none() {
+                self.declare_poisoned_variable(name);
+            }
+        }
+    }
+
+    fn resolve_variable(&self, name: &str) -> Result<Option<&Variable<'a>>, Error> {
+        match self
+            .scopes
+            .iter()
+            .rev()
+            .flat_map(|scope| scope.variables.iter().rev())
+            .find(|variable| variable.name == name)
+        {
+            Some(variable) if variable.poisoned => Err(Error::Reported),
+            variable => Ok(variable),
+        }
+    }
+
+    /// The pending closure that `name` is bound to, if it is.
+    fn resolve_pending_closure(&self, name: &str) -> Option<PendingClosure<'a>> {
+        self.scopes
+            .iter()
+            .rev()
+            .flat_map(|scope| scope.variables.iter().rev())
+            .find(|variable| variable.name == name)
+            .and_then(|variable| variable.pending)
+    }
+
+    /// Infers the type of a pending closure by checking it in the scopes of its declaration,
+    /// and gives the type to its variable.
+    fn infer_pending_closure(
+        &mut self,
+        pending: PendingClosure<'a>,
+        params: Option<&[Type]>,
+    ) -> Result<Type, Error> {
+        let inner_scopes = self.scopes.split_off(pending.scopes);
+        let later_variables = self.last_scope_mut().variables.split_off(pending.variables);
+        let result = self.infer_closure(pending.closure, params, None);
+        self.last_scope_mut().variables.extend(later_variables);
+        self.scopes.extend(inner_scopes);
+
+        let variable = &mut self.scopes[pending.scopes - 1].variables[pending.variables];
+        variable.pending = None;
+
+        match result {
+            Ok(ty) => {
+                variable.ty = ty.clone();
+                self.types.exprs.insert(pending.span, ty.clone());
+                self.types.patterns.insert(pending.pattern_span, ty.clone());
+
+                Ok(ty)
+            }
+            Err(error) => {
+                variable.poisoned = true;
+
+                Err(error)
+            }
+        }
+    }
+
+    /// Poisons the variable of a pending closure whose first call has errors.
+    fn poison_pending_closure(&mut self, pending: PendingClosure<'a>) {
+        let variable = &mut self.scopes[pending.scopes - 1].variables[pending.variables];
+        variable.pending = None;
+        variable.poisoned = true;
+    }
+
+    /// Reports the closures of the last scope that were never called,
+    /// since the types of their parameters can't be inferred.
+    fn report_pending_closures(&mut self) {
+        // Errors can stop the calls from being checked
+        if self.diagnostics.has_errors() {
+            return;
+        }
+
+        let pending = self
+            .last_scope_mut()
+            .variables
+            .iter()
+            .filter_map(|variable| variable.pending)
+            .collect::<Vec<_>>();
+
+        for pending in pending {
+            if let Err(error) = self.infer_pending_closure(pending, None) {
+                self.report(pending.span, error);
+            }
+        }
+    }
+
+    fn resolve_type(&self, ty: &syntax::Type) -> Result<Type, Error> {
+        self.resolve_generic_type(ty, &self.type_params)
+    }
+
+    /// Resolves a type that can refer to `type_params`, like the `T` of `enum Option<T>`.
+    fn resolve_generic_type(
+        &self,
+        ty: &syntax::Type,
+        type_params: &[Ident],
+    ) -> Result<Type, Error> {
+        match &ty.kind {
+            TypeKind::Named(path, args) => {
+                let args = args
+                    .iter()
+                    .map(|arg| self.resolve_generic_type(arg, type_params))
+                    .collect::<Result<Vec<_>, _>>()?;
+                // There are no modules that types could be declared in
+                let Some(name) = path.as_ident() else {
+                    return Err(report::unknown_type(&path.to_string(), &path.span()).into());
+                };
+
+                if type_params
+                    .iter()
+                    .any(|param| param.as_str() == name.as_str())
+                {
+                    if !args.is_empty() {
+                        return Err(report::wrong_number_of_type_args(
+                            name,
+                            0,
+                            args.len(),
+                            &ty.span,
+                        )
+                        .into());
+                    }
+
+                    return Ok(Type::Param(name.to_string()));
+                }
+
+                self.resolve_type_name(name, args, ty.span)
+            }
+            TypeKind::Array(ty) => Ok(Type::Array(
+                self.resolve_generic_type(ty, type_params)?.into(),
+            )),
+            TypeKind::Tuple(types) if types.is_empty() => Ok(Type::Unit),
+            TypeKind::Tuple(types) => Ok(Type::Tuple(
+                types
+                    .iter()
+                    .map(|ty| self.resolve_generic_type(ty, type_params))
+                    .collect::<Result<_, _>>()?,
+            )),
+            TypeKind::Fn(params, return_type) => {
+                let params = params
+                    .iter()
+                    .map(|ty| self.resolve_generic_type(ty, type_params))
+                    .collect::<Result<_, _>>()?;
+                let return_type = match return_type {
+                    Some(return_type) => self.resolve_generic_type(return_type, type_params)?,
+                    None => Type::Unit,
+                };
+
+                Ok(Type::Fn(params, return_type.into()))
+            }
+        }
+    }
+
+    /// Resolves the name of a built-in type, a struct or an enum given the type arguments `args`.
+    /// `span` is the whole type, including its arguments.
+    fn resolve_type_name(&self, name: &Ident, args: Vec<Type>, span: Span) -> Result<Type, Error> {
+        if Type::from_name(name).is_none()
+            && !self.structs.contains_key(name.as_str())
+            && !self.enums.contains_key(name.as_str())
+        {
+            return Err(self.unknown_type(name, &name.span()));
+        }
+
+        let type_params = self.type_params_of(name).len();
+
+        if args.len() != type_params {
+            return Err(
+                report::wrong_number_of_type_args(name, type_params, args.len(), &span).into(),
+            );
+        }
+
+        if let Some(ty) = Type::from_name(name) {
+            return Ok(ty);
+        }
+
+        if self.poisoned_types.contains(name.as_str()) {
+            return Err(Error::Reported);
+        }
+
+        if self.structs.contains_key(name.as_str()) {
+            return Ok(Type::Struct(name.to_string(), args));
+        }
+
+        Ok(Type::Enum(name.to_string(), args))
+    }
+
+    /// Reports a missing type, unless it is an `impl` type that was already reported.
+    fn unknown_type(&self, name: &str, span: &Span) -> Error {
+        if self.poisoned_types.contains(name) {
+            Error::Reported
+        } else {
+            report::unknown_type(name, span).into()
+        }
+    }
+
+    /// Returns the type parameters of the struct or enum `name`.
+    fn type_params_of(&self, name: &str) -> &'a [Ident<'a>] {
+        if let Some(r#struct) = self.structs.get(name) {
+            return &r#struct.type_params;
+        }
+
+        match self.enums.get(name) {
+            Some(r#enum) => &r#enum.type_params,
+            None => &[],
+        }
+    }
+
+    /// Checks a file and returns the types of its expressions, along with its errors.
+    /// The types are only complete if there are no errors.
+    pub fn check_file(&mut self, file: &'a File<'a>) -> (Types, Diagnostics) {
+        // Types can be used before their declaration,
+        // and the file's own types shadow the ones of the prelude
+        for item in &prelude::file().items {
+            self.declare_type(item);
+        }
+
+        let mut type_names = HashMap::new();
+
+        for item in &file.items {
+            let name = match item {
+                Item::Struct(r#struct) => &r#struct.name,
+                Item::Enum(r#enum) => &r#enum.name,
+                Item::Fn(_) | Item::Impl(_) => continue,
+            };
+
+            // Later declarations of a name are checked, but never used
+            if self.declare_item_name(&mut type_names, name) {
+                self.declare_type(item);
+            }
+        }
+
+        // Types are checked first, so that the uses of the ones with errors aren't reported
+        for item in &file.items {
+            let (name, result) = match item {
+                Item::Struct(r#struct) => (
+                    &r#struct.name,
+                    check_type_params(&r#struct.type_params)
+                        .and_then(|()| self.check_fields(&r#struct.fields, &r#struct.type_params)),
+                ),
+                Item::Enum(r#enum) => (&r#enum.name, self.check_enum(r#enum)),
+                Item::Fn(_) | Item::Impl(_) => continue,
+            };
+
+            if let Err(error) = result {
+                self.report(name.span(), error);
+                self.poisoned_types.insert(name);
+            }
+        }
+
+        // Functions can be called before their declaration
+        let mut fn_names = HashMap::new();
+
+        for item in &file.items {
+            match item {
+                Item::Fn(r#fn) => {
+                    if !self.declare_item_name(&mut fn_names, &r#fn.name) {
+                        continue;
+                    }
+
+                    let ty = match &r#fn.receiver {
+                        Some(receiver) => {
+                            Err(report::receiver_outside_impl(&receiver.name.span()).into())
+                        }
+                        None => self.fn_type(r#fn, None),
+                    };
+
+                    match ty {
+                        Ok(ty) => self.declare_variable(&r#fn.name, ty, false, Some(r#fn)),
+                        Err(error) => {
+                            self.report(r#fn.name.span(), error);
+                            self.poisoned_fns.insert(r#fn.name.span());
+                            self.declare_poisoned_variable(&r#fn.name);
+                        }
+                    }
+                }
+                Item::Impl(r#impl) => {
+                    if let Err(error) = self.declare_impl(r#impl) {
+                        self.report(r#impl.self_type.span(), error);
+                        self.poisoned_fns
+                            .extend(r#impl.fns.iter().map(|r#fn| r#fn.name.span()));
+                    }
+                }
+                Item::Struct(_) | Item::Enum(_) => {}
+            }
+        }
+
+        for item in &file.items {
+            match item {
+                Item::Fn(r#fn) => self.check_fn(r#fn, None),
+                Item::Impl(r#impl) => self.check_impl(r#impl),
+                Item::Struct(_) | Item::Enum(_) => {}
+            }
+        }
+
+        (
+            std::mem::take(&mut self.types),
+            std::mem::take(&mut self.diagnostics),
+        )
+    }
+
+    fn declare_type(&mut self, item: &'a Item<'a>) {
+        match item {
+            Item::Struct(r#struct) => {
+                self.structs.insert(&r#struct.name, r#struct);
+                self.enums.remove(r#struct.name.as_str());
+            }
+            Item::Enum(r#enum) => {
+                self.enums.insert(&r#enum.name, r#enum);
+            }
+            Item::Fn(_) | Item::Impl(_) => {}
+        }
+    }
+
+    /// Adds the name of a top-level item to those declared before it,
+    /// or reports it and returns `false` if it is already declared.
+    fn declare_item_name(
+        &mut self,
+        names: &mut HashMap<&'a str, Span>,
+        name: &'a Ident<'a>,
+    ) -> bool {
+        match names.entry(name.as_str()) {
+            Entry::Occupied(previous) => {
+                let error = report::duplicate_item(name, &name.span(), previous.get());
+                self.report(name.span(), error.into());
+
+                false
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(name.span());
+
+                true
+            }
+        }
+    }
+
+    /// Checks the functions of an `impl` block and reports their errors.
+    fn check_impl(&mut self, r#impl: &'a Impl<'a>) {
+        let Ok(ty) = self.impl_type(r#impl) else {
+            return;
+        };
+
+        for r#fn in &r#impl.fns {
+            self.check_fn(r#fn, Some(&ty));
+        }
+    }
+
+    /// Adds the functions of an `impl` block to its type.
+    fn declare_impl(&mut self, r#impl: &'a Impl<'a>) -> Result<(), Error> {
+        check_type_params(&r#impl.type_params)?;
+
+        let name = &r#impl.self_type;
+        let ty = match self.impl_type(r#impl) {
+            Ok(ty) => ty,
+            Err(error) => {
+                // Keeps the uses of a missing type from being reported again
+                if !self.structs.contains_key(name.as_str())
+                    && !self.enums.contains_key(name.as_str())
+                {
+                    self.poisoned_types.insert(name);
+                }
+
+                return Err(error);
+            }
+        };
+        let variants = match &ty {
+            Type::Struct(..) => &[][..],
+            Type::Enum(name, _) => &self.enums[name.as_str()].variants[..],
+            _ => return Err(report::invalid_impl_type(&ty, &r#impl.self_type.span()).into()),
+        };
+
+        for r#fn in &r#impl.fns {
+            // Variants and associated functions are both accessed with paths like `Shape::new`
+            let previous_span = self
+                .impls
+                .get(r#impl.self_type.as_str())
+                .and_then(|fns| {
+                    fns.iter()
+                        .find(|previous| previous.name.as_str() == r#fn.name.as_str())
+                })
+                .map(|previous| previous.name.span())
+                .or_else(|| {
+                    variants
+                        .iter()
+                        .find(|variant| variant.name.as_str() == r#fn.name.as_str())
+                        .map(|variant| variant.name.span())
+                });
+
+            if let Some(previous_span) = previous_span {
+                return Err(report::duplicate_associated_item(
+                    &ty,
+                    &r#fn.name,
+                    &r#fn.name.span(),
+                    &previous_span,
+                )
+                .into());
+            }
+
+            self.impls
+                .entry(r#impl.self_type.as_str())
+                .or_default()
+                .push(r#fn);
+            self.fn_impls.insert(r#fn.name.span(), r#impl);
+
+            if let Err(error) = self.fn_type(r#fn, Some(&ty)) {
+                self.report(r#fn.name.span(), error);
+                self.poisoned_fns.insert(r#fn.name.span());
+            }
+        }
+
+        Ok(())
+    }
+
+    fn check_enum(&mut self, r#enum: &'a Enum<'a>) -> Result<(), Error> {
+        check_type_params(&r#enum.type_params)?;
+
+        for (i, variant) in r#enum.variants.iter().enumerate() {
+            let previous = r#enum.variants[..i]
+                .iter()
+                .find(|previous| previous.name.as_str() == variant.name.as_str());
+
+            if let Some(previous) = previous {
+                return Err(report::duplicate_variant(
+                    &variant.name,
+                    &variant.name.span(),
+                    &previous.name.span(),
+                )
+                .into());
+            }
+
+            match &variant.fields {
+                VariantFields::Unit => {}
+                VariantFields::Tuple(types) => {
+                    for ty in types {
+                        self.resolve_generic_type(ty, &r#enum.type_params)?;
+                    }
+                }
+                VariantFields::Struct(fields) => self.check_fields(fields, &r#enum.type_params)?,
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks the named fields of a struct or variant declaration.
+    fn check_fields(
+        &mut self,
+        fields: &'a [Field<'a>],
+        type_params: &[Ident],
+    ) -> Result<(), Error> {
+        for (i, field) in fields.iter().enumerate() {
+            self.resolve_generic_type(&field.r#type, type_params)?;
+
+            let previous = fields[..i]
+                .iter()
+                .find(|previous| previous.name.as_str() == field.name.as_str());
+
+            if let Some(previous) = previous {
+                return Err(report::duplicate_field(
+                    &field.name,
+                    &field.name.span(),
+                    &previous.name.span(),
+                )
+                .into());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Resolves the type of an `impl` block, like `Wrap<T>` in `impl<T> Wrap<T>`.
+    fn impl_type(&self, r#impl: &Impl) -> Result<Type, Error> {
+        let args = r#impl
+            .type_args
+            .iter()
+            .map(|arg| self.resolve_generic_type(arg, &r#impl.type_params))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        self.resolve_type_name(&r#impl.self_type, args, r#impl.self_type.span())
+    }
+
+    /// Returns the type parameters a function can refer to: those of its `impl` block, then its own.
+    fn fn_type_params(&self, r#fn: &Fn<'a>) -> Vec<Ident<'a>> {
+        let impl_params = match self.fn_impls.get(&r#fn.name.span()) {
+            Some(r#impl) => &r#impl.type_params[..],
+            None => &[],
+        };
+
+        impl_params
+            .iter()
+            .chain(&r#fn.type_params)
+            .cloned()
+            .collect()
+    }
+
+    /// Whether a function has type parameters, including those of its `impl` block.
+    fn is_generic(&self, r#fn: &Fn<'a>) -> bool {
+        !self.fn_type_params(r#fn).is_empty()
+    }
+
+    /// Returns the type of a function.
+    /// Methods referred to by a path like `Point::len` take `self_type` as their first argument.
+    fn fn_type(&self, r#fn: &Fn<'a>, self_type: Option<&Type>) -> Result<Type, Error> {
+        if self.poisoned_fns.contains(&r#fn.name.span()) {
+            return Err(Error::Reported);
+        }
+
+        let type_params = self.fn_type_params(r#fn);
+        check_type_params(&type_params)?;
+
+        let receiver = r#fn.receiver.as_ref().and(self_type).cloned();
+        let args = receiver
+            .into_iter()
+            .map(Ok)
+            .chain(
+                r#fn.args
+                    .iter()
+                    .map(|arg| self.resolve_generic_type(&arg.r#type, &type_params)),
+            )
+            .collect::<Result<Vec<_>, _>>()?;
+        let return_type = match &r#fn.return_type {
+            Some(return_type) => self.resolve_generic_type(return_type, &type_params)?,
+            None => Type::Unit,
+        };
+
+        Ok(Type::Fn(args, return_type.into()))
+    }
+
+    /// Checks a function, which is a method of `self_type` if given, and reports its errors.
+    fn check_fn(&mut self, r#fn: &'a Fn<'a>, self_type: Option<&Type>) {
+        // The errors of the signature were reported when it was declared
+        let Ok(Type::Fn(arg_types, return_type)) = self.fn_type(r#fn, self_type) else {
+            return;
+        };
+
+        self.type_params = self.fn_type_params(r#fn);
+
+        let return_type = Expected {
+            ty: *return_type,
+            span: r#fn.return_type.as_ref().map(|ty| ty.span),
+        };
+
+        self.scopes.push(Scope::new());
+        self.return_type = Some(return_type.clone());
+
+        if let (Some(receiver), Some(ty)) = (&r#fn.receiver, self_type) {
+            self.declare_variable(&receiver.name, ty.clone(), receiver.mutable, None);
+        }
+
+        // The receiver comes first in the type of a method
+        let arg_types = &arg_types[arg_types.len() - r#fn.args.len()..];
+        let patterns = r#fn.args.iter().map(|arg| &arg.pattern).collect::<Vec<_>>();
+
+        if let Err(error) = check_bindings(&patterns) {
+            self.report(r#fn.name.span(), error);
+        }
+
+        for (arg, ty) in r#fn.args.iter().zip(arg_types) {
+            let expected = Expected {
+                ty: ty.clone(),
+                span: Some(arg.r#type.span),
+            };
+
+            if let Err(error) = self.check_irrefutable(&arg.pattern, &expected) {
+                self.report(arg.pattern.span, error);
+                self.poison_bindings(&arg.pattern);
+            }
+        }
+
+        if let Err(error) = self.check_block(&r#fn.body, &return_type) {
+            // A missing value is reported where the body ends
+            let end = r#fn.body.span.end;
+            self.report(Span::new(end, end), error);
+        }
+
+        self.type_params = Vec::new();
+        self.return_type = None;
+        self.scopes.pop();
+    }
+
+    /// Checks that the value of `block` has the expected type.
+    fn check_block(&mut self, block: &'a Block<'a>, expected: &Expected) -> Result<(), Error> {
+        self.scopes.push(Scope::new());
+
+        let diverges = self.check_stmts(&block.stmts);
+        let result = match &block.expr {
+            // Like statements, the value is checked on its own, since its type is known
+            Some(expr) => {
+                if let Err(error) = self.check_expr(expr, expected) {
+                    self.report(expr.span, error);
+                }
+
+                Ok(())
+            }
+            None if diverges => Ok(()),
+            None => expected.check(&Type::Unit, block.span),
+        };
+
+        self.report_pending_closures();
+        self.scopes.pop();
+
+        result
+    }
+
+    fn infer_block(&mut self, block: &'a Block<'a>) -> Result<Type, Error> {
+        self.scopes.push(Scope::new());
+
+        let diverges = self.check_stmts(&block.stmts);
+        let result = match &block.expr {
+            Some(expr) => self.infer_expr(expr).map_err(|error| {
+                self.report(expr.span, error);
+                Error::Reported
+            }),
+            None if diverges => Ok(Type::Never),
+            None => Ok(Type::Unit),
+        };
+
+        self.report_pending_closures();
+        self.scopes.pop();
+
+        result
+    }
+
+    /// Checks statements, reporting their errors, and returns whether any of them diverges.
+    fn check_stmts(&mut self, stmts: &'a [Stmt<'a>]) -> bool {
+        let mut diverges = false;
+
+        for stmt in stmts {
+            match self.check_stmt(stmt) {
+                Ok(ty) => diverges |= ty == Type::Never,
+                // Whether it diverges is unknown, so a missing value isn't reported after it
+                Err(error) => {
+                    diverges = true;
+                    let span = match stmt {
+                        Stmt::Let(r#let) => Span::new(r#let.pattern.span.start, r#let.rhs.span.end),
+                        Stmt::Expr(expr) => expr.span,
+                    };
+                    self.report(span, error);
+                }
+            }
+        }
+
+        diverges
+    }
+
+    /// Checks a statement and returns the type of its expression.
+    fn check_stmt(&mut self, stmt: &'a Stmt<'a>) -> Result<Type, Error> {
+        match stmt {
+            Stmt::Let(r#let) => self.check_let(r#let),
+            Stmt::Expr(expr) => self.infer_expr(expr),
+        }
+    }
+
+    /// Checks a `let` statement, whose variables are declared even if it has errors.
+    fn check_let(&mut self, r#let: &'a Let<'a>) -> Result<Type, Error> {
+        let Let { pattern, ty, rhs } = r#let;
+
+        // The parameter types of `let f = |x| …;` are taken from the first call of `f`
+        if let (
+            PatternKind::Binding {
+                name,
+                mutable: false,
+            },
+            None,
+            ExprKind::Closure(closure),
+        ) = (&pattern.kind, ty, &rhs.kind)
+        {
+            if closure.args.iter().any(|arg| arg.r#type.is_none())
+                && prelude_variant(name).is_none()
+            {
+                self.declare_pending_closure(name, closure, rhs.span, pattern.span);
+
+                return Ok(Type::Unit);
+            }
+        }
+
+        let (expected, value) = match ty {
+            Some(ty) => match self.resolve_type(ty) {
+                Ok(resolved) => {
+                    let expected = Expected {
+                        ty: resolved,
+                        span: Some(ty.span),
+                    };
+                    // The variables get the annotated types even if the value doesn't check
+                    let value = self.check_expr(rhs, &expected);
+
+                    (Ok(expected), value)
+                }
+                Err(error) => (Err(error), Ok(())),
+            },
+            None => {
+                let expected = self.infer_expr(rhs).map(|ty| Expected {
+                    ty,
+                    span: Some(rhs.span),
+                });
+
+                (expected, Ok(()))
+            }
+        };
+        let declared = check_bindings(&[pattern])
+            .and(expected)
+            .and_then(|expected| {
+                self.check_irrefutable(pattern, &expected)?;
+
+                Ok(expected.ty)
+            });
+
+        match declared {
+            Ok(ty) => {
+                value?;
+
+                Ok(if ty == Type::Never {
+                    Type::Never
+                } else {
+                    Type::Unit
+                })
+            }
+            Err(error) => {
+                self.poison_bindings(pattern);
+
+                Err(error)
+            }
+        }
+    }
+
+    /// Checks that `expr` has the expected type.
+    ///
+    /// Unlike inferring the type and comparing it afterwards,
+    /// this reports mismatches at the innermost expression producing the value.
+    fn check_expr(&mut self, expr: &'a Expr<'a>, expected: &Expected) -> Result<(), Error> {
+        self.check_expr_kind(expr, expected)?;
+        self.types
+            .exprs
+            .entry(expr.span)
+            .or_insert_with(|| expected.ty.clone());
+
+        Ok(())
+    }
+
+    fn check_expr_kind(&mut self, expr: &'a Expr<'a>, expected: &Expected) -> Result<(), Error> {
+        match &expr.kind {
+            ExprKind::Parens(expr) => self.check_expr(expr, expected),
+            ExprKind::Block(block) => self.check_block(block, expected),
+            ExprKind::If {
+                cond,
+                then,
+                r#else: Some(r#else),
+            } => {
+                self.check_cond(cond)?;
+                self.check_block(then, expected)?;
+                self.check_expr(r#else, expected)
+            }
+            ExprKind::Loop { label, body } => {
+                let r#loop = Loop {
+                    label: label.as_ref().map(Ident::as_str),
+                    kind: LoopKind::Loop {
+                        break_type: Some(expected.clone()),
+                    },
+                };
+
+                self.check_loop(r#loop, body)?;
+
+                Ok(())
+            }
+            ExprKind::Array(elements) => match expected.element_type() {
+                Some(expected) => {
+                    for element in elements {
+                        self.check_expr(element, &expected)?;
+                    }
+
+                    Ok(())
+                }
+                None => self.check_inferred(expr, expected),
+            },
+            ExprKind::Repeat { value, count } => match expected.element_type() {
+                Some(expected) => {
+                    self.check_expr(value, &expected)?;
+                    self.check_len(count)
+                }
+                None => self.check_inferred(expr, expected),
+            },
+            ExprKind::Tuple(elements) => match &expected.ty {
+                Type::Tuple(types) if types.len() == elements.len() => {
+                    for (element, ty) in elements.iter().zip(types) {
+                        let expected = Expected {
+                            ty: ty.clone(),
+                            span: expected.span,
+                        };
+
+                        self.check_expr(element, &expected)?;
+                    }
+
+                    Ok(())
+                }
+                _ => self.check_inferred(expr, expected),
+            },
+            ExprKind::Closure(closure) => {
+                let ty = match &expected.ty {
+                    Type::Fn(params, return_type) => {
+                        self.infer_closure(closure, Some(params), Some(return_type))?
+                    }
+                    _ => self.infer_closure(closure, None, None)?,
+                };
+
+                expected.check(&ty, expr.span)
+            }
+            ExprKind::Match { scrutinee, arms } => {
+                self.infer_match(scrutinee, arms, Some(expected))?;
+
+                Ok(())
+            }
+            _ => self.check_inferred(expr, expected),
+        }
+    }
+
+    /// Checks `expr` by inferring its type and comparing it with the expected one.
+    fn check_inferred(&mut self, expr: &'a Expr<'a>, expected: &Expected) -> Result<(), Error> {
+        let ty = self.infer_expr_expecting(expr, Some(&expected.ty))?;
+
+        expected.check(&ty, expr.span)
+    }
+
+    fn infer_expr(&mut self, expr: &'a Expr<'a>) -> Result<Type, Error> {
+        self.infer_expr_expecting(expr, None)
+    }
+
+    /// Infers the type of `expr`, taking what can't be inferred from the `expected` type,
+    /// like the type of an integer literal or the type arguments of `None`.
+    fn infer_expr_expecting(
+        &mut self,
+        expr: &'a Expr<'a>,
+        expected: Option<&Type>,
+    ) -> Result<Type, Error> {
+        let ty = self.infer_expr_kind(expr, expected)?;
+        self.types.exprs.insert(expr.span, ty.clone());
+
+        Ok(ty)
+    }
+
+    fn infer_expr_kind(
+        &mut self,
+        expr: &'a Expr<'a>,
+        expected: Option<&Type>,
+    ) -> Result<Type, Error> {
+        Ok(match &expr.kind {
+            ExprKind::Int { .. } => self
+                .infer_int_literal(expr, expected)?
+                .expect("BUG: integer literals have a type"),
+            ExprKind::Float(_) => Type::F64,
+            ExprKind::Bool(_) => Type::Bool,
+            ExprKind::String(_) => Type::String,
+            // Its error was reported by the parser, and its type is unknown,
+            // so that anything using it isn't reported again
+            ExprKind::Error => return Err(Error::Reported),
+            ExprKind::Format(parts) => {
+                for part in parts {
+                    let FormatPart::Expr(expr) = part else {
+                        continue;
+                    };
+                    let ty = self.infer_expr(expr)?;
+
+                    if !matches!(ty, Type::Bool | Type::String) && !ty.is_numeric() {
+                        return Err(report::unformattable(&ty, &expr.span).into());
+                    }
+                }
+
+                Type::String
+            }
+            ExprKind::Parens(expr) => self.infer_expr_expecting(expr, expected)?,
+            ExprKind::Tuple(elements) => Type::Tuple(
+                elements
+                    .iter()
+                    .map(|element| self.infer_expr(element))
+                    .collect::<Result<_, _>>()?,
+            ),
+            ExprKind::Var(name) => match self.resolve_pending_closure(name) {
+                Some(pending) => {
+                    let params = match expected {
+                        Some(Type::Fn(params, _)) => Some(params.as_slice()),
+                        _ => None,
+                    };
+
+                    self.infer_pending_closure(pending, params)?
+                }
+                None => match self.resolve_variable(name)? {
+                    Some(variable) => {
+                        let is_generic = variable.r#fn.is_some_and(|r#fn| self.is_generic(r#fn));
+
+                        instantiate_fn(variable.ty.clone(), is_generic, expected, expr.span)?
+                    }
+                    None => match prelude_variant(name) {
+                        Some(path) => {
+                            let (r#enum, variant) = self.resolve_variant(&path)?;
+
+                            self.infer_variant_value(r#enum, variant, &path, expected)?
+                        }
+                        None => return Err(report::undeclared_variable(name, &name.span()).into()),
+                    },
+                },
+            },
+            ExprKind::Path(path) => {
+                if let Some((ty, r#fn)) = self.resolve_assoc_fn(path)? {
+                    let ty = self.fn_type(r#fn, Some(&ty))?;
+
+                    return instantiate_fn(ty, self.is_generic(r#fn), expected, expr.span);
+                }
+
+                if let [type_name, name] = path.segments.as_slice() {
+                    if self.structs.contains_key(type_name.as_str()) {
+                        let ty = Type::Struct(type_name.to_string(), Vec::new());
+
+                        return Err(report::unknown_method(&ty, name, &name.span(), None).into());
+                    }
+                }
+
+                let (r#enum, variant) = self.resolve_variant(path)?;
+
+                self.infer_variant_value(r#enum, variant, path, expected)?
+            }
+            ExprKind::Neg(operand) => {
+                if let Some(ty) = self.infer_int_literal(expr, expected)? {
+                    return Ok(ty);
+                }
+
+                let ty = self.infer_expr_expecting(operand, expected)?;
+
+                if !ty.is_numeric() {
+                    return Err(report::unsupported_operator("-", &ty, &operand.span).into());
+                }
+
+                ty
+            }
+            ExprKind::Add(lhs, rhs) => self.infer_arithmetic("+", lhs, rhs, expected)?,
+            ExprKind::Sub(lhs, rhs) => self.infer_arithmetic("-", lhs, rhs, expected)?,
+            ExprKind::Mul(lhs, rhs) => self.infer_arithmetic("*", lhs, rhs, expected)?,
+            ExprKind::Div(lhs, rhs) => self.infer_arithmetic("/", lhs, rhs, expected)?,
+            ExprKind::Eq(lhs, rhs) => self.infer_comparison("==", lhs, rhs)?,
+            ExprKind::Ne(lhs, rhs) => self.infer_comparison("!=", lhs, rhs)?,
+            ExprKind::Lt(lhs, rhs) => self.infer_comparison("<", lhs, rhs)?,
+            ExprKind::Le(lhs, rhs) => self.infer_comparison("<=", lhs, rhs)?,
+            ExprKind::Gt(lhs, rhs) => self.infer_comparison(">", lhs, rhs)?,
+            ExprKind::Ge(lhs, rhs) => self.infer_comparison(">=", lhs, rhs)?,
+            ExprKind::Not(operand) => {
+                let ty = self.infer_expr(operand)?;
+
+                if ty != Type::Bool {
+                    return Err(report::unsupported_operator("!", &ty, &operand.span).into());
+                }
+
+                ty
+            }
+            ExprKind::And(lhs, rhs) => self.infer_logical("&&", lhs, rhs)?,
+            ExprKind::Or(lhs, rhs) => self.infer_logical("||", lhs, rhs)?,
+            ExprKind::Assign(lhs, rhs) => self.check_assignment("=", lhs, rhs)?,
+            ExprKind::AddAssign(lhs, rhs) => self.check_assignment("+=", lhs, rhs)?,
+            ExprKind::SubAssign(lhs, rhs) => self.check_assignment("-=", lhs, rhs)?,
+            ExprKind::MulAssign(lhs, rhs) => self.check_assignment("*=", lhs, rhs)?,
+            ExprKind::DivAssign(lhs, rhs) => self.check_assignment("/=", lhs, rhs)?,
+            ExprKind::Call(callee, args) => self.infer_call(callee, args, expr.span, expected)?,
+            ExprKind::MethodCall {
+                receiver,
+                method,
+                args,
+            } => self.infer_method_call(receiver, method, args, expr.span, expected)?,
+            ExprKind::Block(block) => self.infer_block(block)?,
+            ExprKind::If { cond, then, r#else } => {
+                self.check_cond(cond)?;
+
+                match r#else {
+                    Some(r#else) => {
+                        let expected = Expected {
+                            ty: self.infer_block(then)?,
+                            span: Some(then.value_span()),
+                        };
+
+                        if expected.ty == Type::Never {
+                            return self.infer_expr(r#else);
+                        }
+
+                        self.check_expr(r#else, &expected)?;
+
+                        expected.ty
+                    }
+                    // Without an `else` there's no value for a false condition
+                    None => {
+                        let expected = Expected {
+                            ty: Type::Unit,
+                            span: None,
+                        };
+                        self.check_block(then, &expected)?;
+
+                        expected.ty
+                    }
+                }
+            }
+            ExprKind::Loop { label, body } => {
+                let r#loop = Loop {
+                    label: label.as_ref().map(Ident::as_str),
+                    kind: LoopKind::Loop { break_type: None },
+                };
+
+                self.check_loop(r#loop, body)?
+            }
+            ExprKind::While { label, cond, body } => {
+                self.check_cond(cond)?;
+
+                let r#loop = Loop {
+                    label: label.as_ref().map(Ident::as_str),
+                    kind: LoopKind::While,
+                };
+
+                self.check_loop(r#loop, body)?;
+
+                Type::Unit
+            }
+            ExprKind::Break { label, value } => {
+                let index = self.resolve_loop(label.as_ref(), "break", expr.span)?;
+                self.breaks += 1;
+
+                match (&mut self.loops[index].kind, value) {
+                    (LoopKind::While | LoopKind::For, None) => {}
+                    (LoopKind::While, Some(value)) => {
+                        return Err(report::break_with_value("while", &value.span).into());
+                    }
+                    (LoopKind::For, Some(value)) => {
+                        return Err(report::break_with_value("for", &value.span).into());
+                    }
+                    (LoopKind::Loop { break_type }, value) => {
+                        let break_type = break_type.take();
+                        let value_span = value.as_ref().map_or(expr.span, |value| value.span);
+                        let break_type = match (break_type, value) {
+                            (Some(expected), Some(value)) => {
+                                self.check_expr(value, &expected)?;
+                                expected
+                            }
+                            (Some(expected), None) => {
+                                expected.check(&Type::Unit, value_span)?;
+                                expected
+                            }
+                            // The first `break` determines the type of the loop
+                            (None, Some(value)) => Expected {
+                                ty: self.infer_expr(value)?,
+                                span: Some(value_span),
+                            },
+                            (None, None) => Expected {
+                                ty: Type::Unit,
+                                span: Some(value_span),
+                            },
+                        };
+
+                        self.loops[index].kind = LoopKind::Loop {
+                            break_type: Some(break_type),
+                        };
+                    }
+                }
+
+                Type::Never
+            }
+            ExprKind::Continue { label } => {
+                self.resolve_loop(label.as_ref(), "continue", expr.span)?;
+
+                Type::Never
+            }
+            ExprKind::Return(value) => {
+                match (self.return_type.clone(), value) {
+                    (Some(expected), Some(value)) => self.check_expr(value, &expected)?,
+                    (Some(expected), None) => expected.check(&Type::Unit, expr.span)?,
+                    (None, Some(value)) => {
+                        self.return_type = Some(Expected {
+                            ty: self.infer_expr(value)?,
+                            span: Some(value.span),
+                        });
+                    }
+                    (None, None) => {
+                        self.return_type = Some(Expected {
+                            ty: Type::Unit,
+                            span: Some(expr.span),
+                        });
+                    }
+                }
+
+                Type::Never
+            }
+            ExprKind::Closure(closure) => self.infer_closure(closure, None, None)?,
+            ExprKind::Struct { path, fields, base } => {
+                self.infer_struct(path, fields, base.as_deref(), expected)?
+            }
+            ExprKind::Field(expr, field) => {
+                let ty = self.infer_expr(expr)?;
+
+                self.field_type(&ty, field)?.ty
+            }
+            ExprKind::Array(elements) => {
+                let [first, rest @ ..] = elements.as_slice() else {
+                    return Err(report::empty_array_type(&expr.span).into());
+                };
+                let expected = Expected {
+                    ty: self.infer_expr(first)?,
+                    span: Some(first.span),
+                };
+
+                for element in rest {
+                    self.check_expr(element, &expected)?;
+                }
+
+                Type::Array(expected.ty.into())
+            }
+            ExprKind::Repeat { value, count } => {
+                let ty = self.infer_expr(value)?;
+                self.check_len(count)?;
+
+                Type::Array(ty.into())
+            }
+            ExprKind::Index(expr, index) => {
+                let ty = self.infer_expr(expr)?;
+
+                self.index_type(&ty, expr.span, index)?
+            }
+            ExprKind::Range { start, end, .. } => {
+                let (start, end) = literal_last(start, end);
+                let ty = self.infer_expr(start)?;
+
+                if !ty.is_integer() {
+                    return Err(report::unsupported_operator("..", &ty, &start.span).into());
+                }
+
+                let expected = Expected { ty, span: None };
+                self.check_expr(end, &expected)?;
+
+                Type::Range(expected.ty.into())
+            }
+            ExprKind::For {
+                label,
+                var,
+                iter,
+                body,
+            } => {
+                let ty = match self.infer_expr(iter)? {
+                    Type::Array(ty) | Type::Range(ty) => *ty,
+                    ty => return Err(report::not_iterable(&ty, &iter.span).into()),
+                };
+                let r#loop = Loop {
+                    label: label.as_ref().map(Ident::as_str),
+                    kind: LoopKind::For,
+                };
+
+                self.scopes.push(Scope::new());
+                self.declare_variable(var, ty, false, None);
+                let result = self.check_loop(r#loop, body);
+                self.scopes.pop();
+                result?;
+
+                Type::Unit
+            }
+            ExprKind::Match { scrutinee, arms } => self.infer_match(scrutinee, arms, None)?,
+            ExprKind::Try(operand) => {
+                let ty = self.infer_expr(operand)?;
+                let Type::Enum(name, args) = &ty else {
+                    return Err(report::unsupported_operator("?", &ty, &operand.span).into());
+                };
+
+                if prelude::try_residual(name).is_none() {
+                    return Err(report::unsupported_operator("?", &ty, &operand.span).into());
+                }
+
+                self.check_try_return(&ty, expr.span)?;
+
+                args[0].clone()
+            }
+        })
+    }
+
+    /// Checks that the function being checked can return the `None` or `Err`
+    /// of a value of type `ty` early, for a `?` at `span`.
+    fn check_try_return(&self, ty: &Type, span: Span) -> Result<(), Error> {
+        let Some(return_type) = &self.return_type else {
+            return Err(report::try_without_return_type(&span).into());
+        };
+
+        match (ty, &return_type.ty) {
+            (Type::Enum(name, args), Type::Enum(return_name, return_args))
+                if name == return_name =>
+            {
+                match (args.get(1), return_args.get(1)) {
+                    // The error is returned as it is
+                    (Some(error), Some(return_error)) => {
+                        let expected = Expected {
+                            ty: return_error.clone(),
+                            span: return_type.span,
+                        };
+
+                        expected.check(error, span)
+                    }
+                    _ => Ok(()),
+                }
+            }
+            _ => Err(
+                report::invalid_try(ty, &return_type.ty, return_type.span.as_ref(), &span).into(),
+            ),
+        }
+    }
+
+    /// Infers the type of an integer literal, which can be negated, and checks that it's in range.
+    /// Literals without a suffix have the `expected` integer type, or else `i32`.
+    /// Returns `None` if `expr` isn't an integer literal.
+    fn infer_int_literal(
+        &mut self,
+        expr: &Expr,
+        expected: Option<&Type>,
+    ) -> Result<Option<Type>, Error> {
+        let (literal, negative) = match &expr.kind {
+            ExprKind::Neg(operand) => (&**operand, true),
+            _ => (expr, false),
+        };
+        let ExprKind::Int { value, suffix } = literal.kind else {
+            return Ok(None);
+        };
+
+        let ty = int_type(suffix, expected);
+        let value = if negative {
+            -i128::from(value)
+        } else {
+            value.into()
+        };
+        check_int_range(value, &ty, expr.span)?;
+        self.types.exprs.insert(literal.span, ty.clone());
+
+        Ok(Some(ty))
+    }
+
+    /// Infers the type of a `match`.
+    /// The arms need to have the `expected` type if given, or else the type of the first arm.
+    fn infer_match(
+        &mut self,
+        scrutinee: &'a Expr<'a>,
+        arms: &'a [Arm<'a>],
+        expected: Option<&Expected>,
+    ) -> Result<Type, Error> {
+        let scrutinee_type = Expected {
+            ty: self.infer_expr(scrutinee)?,
+            span: Some(scrutinee.span),
+        };
+        let mut result = expected.cloned();
+
+        for arm in arms {
+            self.scopes.push(Scope::new());
+            let ty = self.check_arm(arm, &scrutinee_type, result.as_ref());
+            self.scopes.pop();
+
+            let ty = ty?;
+
+            // Arms that diverge, like `return`, don't determine the type
+            if result.is_none() && ty != Type::Never {
+                result = Some(Expected {
+                    ty,
+                    span: Some(arm.body.span),
+                });
+            }
+        }
+
+        self.check_exhaustiveness(arms, &scrutinee_type.ty, scrutinee.span)?;
+
+        Ok(result.map_or(Type::Never, |result| result.ty))
+    }
+
+    fn check_arm(
+        &mut self,
+        arm: &'a Arm<'a>,
+        scrutinee_type: &Expected,
+        expected: Option<&Expected>,
+    ) -> Result<Type, Error> {
+        check_bindings(&[&arm.pattern])?;
+        self.check_pattern(&arm.pattern, scrutinee_type)?;
+
+        if let Some(guard) = &arm.guard {
+            self.check_cond(guard)?;
+        }
+
+        match expected {
+            Some(expected) => {
+                self.check_expr(&arm.body, expected)?;
+
+                Ok(expected.ty.clone())
+            }
+            None => self.infer_expr(&arm.body),
+        }
+    }
+
+    /// Checks a pattern that has to match every value of the expected type,
+    /// like the pattern of a `let`, and declares its bindings.
+    fn check_irrefutable(
+        &mut self,
+        pattern: &'a Pattern<'a>,
+        expected: &Expected,
+    ) -> Result<(), Error> {
+        self.check_pattern(pattern, expected)?;
+        self.check_covers_all(pattern, &expected.ty)
+    }
+
+    /// Checks that `pattern` matches values of the expected type and declares its bindings.
+    fn check_pattern(
+        &mut self,
+        pattern: &'a Pattern<'a>,
+        expected: &Expected,
+    ) -> Result<(), Error> {
+        self.types
+            .patterns
+            .insert(pattern.span, expected.ty.clone());
+
+        match &pattern.kind {
+            PatternKind::Wildcard => Ok(()),
+            PatternKind::Binding { name, mutable } => match prelude_variant(name) {
+                // `None` matches the variant instead of binding a variable
+                Some(path) if !mutable => self.check_path_pattern(&path, expected, pattern.span),
+                _ => {
+                    self.declare_variable(name, expected.ty.clone(), *mutable, None);
+
+                    Ok(())
+                }
+            },
+            PatternKind::Int { value, suffix } => {
+                let ty = int_type(*suffix, Some(&expected.ty));
+                check_int_range((*value).into(), &ty, pattern.span)?;
+
+                expected.check(&ty, pattern.span)
+            }
+            PatternKind::Bool(_) => expected.check(&Type::Bool, pattern.span),
+            PatternKind::Path(path) => self.check_path_pattern(path, expected, pattern.span),
+            PatternKind::Tuple(patterns) => {
+                let Type::Tuple(types) = &expected.ty else {
+                    return Err(report::mismatched_tuple_pattern(
+                        &expected.ty,
+                        expected.span.as_ref(),
+                        &pattern.span,
+                    )
+                    .into());
+                };
+
+                if types.len() != patterns.len() {
+                    return Err(report::wrong_number_of_fields(
+                        &expected.ty.to_string(),
+                        types.len(),
+                        patterns.len(),
+                        &pattern.span,
+                    )
+                    .into());
+                }
+
+                for (ty, pattern) in types.iter().zip(patterns) {
+                    let expected = Expected {
+                        ty: ty.clone(),
+                        span: expected.span,
+                    };
+
+                    self.check_pattern(pattern, &expected)?;
+                }
+
+                Ok(())
+            }
+            PatternKind::TupleVariant(path, patterns) => {
+                let (r#enum, variant) = self.resolve_variant(path)?;
+
+                let types = match &variant.fields {
+                    VariantFields::Tuple(types) => types,
+                    VariantFields::Unit => {
+                        return Err(report::wrong_variant_syntax(
+                            &path.to_string(),
+                            "unit variant",
+                            &path.to_string(),
+                            &path.span(),
+                        )
+                        .into());
+                    }
+                    VariantFields::Struct(_) => {
+                        return Err(report::wrong_variant_syntax(
+                            &path.to_string(),
+                            "struct variant",
+                            &format!("{path} {{ .. }}"),
+                            &path.span(),
+                        )
+                        .into());
+                    }
+                };
+
+                let ty = pattern_type(generic_type(r#enum), &expected.ty);
+                expected.check(&ty, pattern.span)?;
+
+                if types.len() != patterns.len() {
+                    return Err(report::wrong_number_of_fields(
+                        &path.to_string(),
+                        types.len(),
+                        patterns.len(),
+                        &pattern.span,
+                    )
+                    .into());
+                }
+
+                for (decl, pattern) in types.iter().zip(patterns) {
+                    let expected = self.field_decl_type(decl, &ty, expected.span)?;
+                    self.check_pattern(pattern, &expected)?;
+                }
+
+                Ok(())
+            }
+            PatternKind::Struct { path, fields, rest } => {
+                let (generic, _, decls) = self.resolve_named_fields(path)?;
+                let name = path.to_string();
+                let ty = pattern_type(generic, &expected.ty);
+
+                expected.check(&ty, pattern.span)?;
+
+                for (i, field) in fields.iter().enumerate() {
+                    let previous = fields[..i]
+                        .iter()
+                        .find(|previous| previous.name.as_str() == field.name.as_str());
+
+                    if let Some(previous) = previous {
+                        return Err(report::duplicate_field(
+                            &field.name,
+                            &field.name.span(),
+                            &previous.name.span(),
+                        )
+                        .into());
+                    }
+
+                    let decl = named_field(&name, decls, &field.name)?;
+                    let expected = self.field_decl_type(&decl.r#type, &ty, expected.span)?;
+                    self.check_pattern(&field.pattern, &expected)?;
+                }
+
+                if !rest {
+                    let missing = missing_fields(
+                        decls,
+                        &fields
+                            .iter()
+                            .map(|field| field.name.as_str())
+                            .collect::<Vec<_>>(),
+                    );
+
+                    if !missing.is_empty() {
+                        return Err(report::missing_fields(
+                            &name,
+                            &missing,
+                            &path.span(),
+                            Some("Use `..` to ignore the remaining fields".into()),
+                        )
+                        .into());
+                    }
+                }
+
+                Ok(())
+            }
+        }
+    }
+
+    /// Checks a pattern matching a unit variant, like `Shape::Empty`.
+    fn check_path_pattern(
+        &self,
+        path: &Path,
+        expected: &Expected,
+        span: Span,
+    ) -> Result<(), Error> {
+        let (r#enum, variant) = self.resolve_variant(path)?;
+
+        match &variant.fields {
+            VariantFields::Unit => {
+                expected.check(&pattern_type(generic_type(r#enum), &expected.ty), span)
+            }
+            VariantFields::Tuple(_) => Err(report::wrong_variant_syntax(
+                &path.to_string(),
+                "tuple variant",
+                &format!("{path}(…)"),
+                &path.span(),
+            )
+            .into()),
+            VariantFields::Struct(_) => Err(report::wrong_variant_syntax(
+                &path.to_string(),
+                "struct variant",
+                &format!("{path} {{ .. }}"),
+                &path.span(),
+            )
+            .into()),
+        }
+    }
+
+    /// Resolves a path like `Point::new` to a method or associated function and its type,
+    /// if it refers to one.
+    fn resolve_assoc_fn(&self, path: &Path) -> Result<Option<(Type, &'a Fn<'a>)>, Error> {
+        let [type_name, name] = path.segments.as_slice() else {
+            return Ok(None);
+        };
+        let r#fn = self
+            .impls
+            .get(type_name.as_str())
+            .and_then(|fns| fns.iter().find(|r#fn| r#fn.name.as_str() == name.as_str()));
+
+        match r#fn {
+            Some(r#fn) => Ok(Some((
+                self.impl_type(self.fn_impls[&r#fn.name.span()])?,
+                r#fn,
+            ))),
+            None => Ok(None),
+        }
+    }
+
+    /// Resolves the path of a variant like `Shape::Circle` to its enum and declaration.
+    /// Variants of the prelude can also be referred to by their name alone, like `Some`.
+    fn resolve_variant(&self, path: &Path) -> Result<(&'a Enum<'a>, &'a Variant<'a>), Error> {
+        let (enum_name, variant_name) = match path.segments.as_slice() {
+            [variant_name] => match prelude::variant_enum(variant_name) {
+                Some(enum_name) => (enum_name, variant_name),
+                None => return Err(report::unknown_type(variant_name, &path.span()).into()),
+            },
+            [enum_name, variant_name] => (enum_name.as_str(), variant_name),
+            _ => return Err(report::unknown_type(&path.to_string(), &path.span()).into()),
+        };
+        let r#enum = *self
+            .enums
+            .get(enum_name)
+            .ok_or_else(|| self.unknown_type(enum_name, &path.segments[0].span()))?;
+        let variant = r#enum
+            .variants
+            .iter()
+            .find(|variant| variant.name.as_str() == variant_name.as_str())
+            .ok_or_else(|| {
+                let names = r#enum
+                    .variants
+                    .iter()
+                    .map(|variant| variant.name.as_str())
+                    .collect::<Vec<_>>();
+
+                report::unknown_variant(
+                    &generic_type(r#enum),
+                    variant_name,
+                    &variant_name.span(),
+                    &names,
+                )
+            })?;
+
+        Ok((r#enum, variant))
+    }
+
+    /// Resolves the path of a struct or struct variant to its fields,
+    /// and the type of the struct or enum with its type parameters.
+    fn resolve_named_fields(
+        &self,
+        path: &Path,
+    ) -> Result<(Type, &'a [Ident<'a>], &'a [Field<'a>]), Error> {
+        if let Some(name) = path.as_ident() {
+            let r#struct = *self
+                .structs
+                .get(name.as_str())
+                .ok_or_else(|| self.unknown_type(name, &name.span()))?;
+            let ty = Type::Struct(name.to_string(), param_types(&r#struct.type_params));
+
+            return Ok((ty, &r#struct.type_params, &r#struct.fields));
+        }
+
+        let (r#enum, variant) = self.resolve_variant(path)?;
+
+        match &variant.fields {
+            VariantFields::Struct(fields) => {
+                Ok((generic_type(r#enum), &r#enum.type_params, fields))
+            }
+            VariantFields::Tuple(_) => Err(report::wrong_variant_syntax(
+                &path.to_string(),
+                "tuple variant",
+                &format!("{path}(…)"),
+                &path.span(),
+            )
+            .into()),
+            VariantFields::Unit => Err(report::wrong_variant_syntax(
+                &path.to_string(),
+                "unit variant",
+                &path.to_string(),
+                &path.span(),
+            )
+            .into()),
+        }
+    }
+
+    /// Infers the type of a struct literal or struct variant,
+    /// whose type arguments can be taken from the `expected` type.
+    fn infer_struct(
+        &mut self,
+        path: &Path<'a>,
+        fields: &'a [FieldInit<'a>],
+        base: Option<&'a Expr<'a>>,
+        expected: Option<&Type>,
+    ) -> Result<Type, Error> {
+        let (generic, type_params, decls) = self.resolve_named_fields(path)?;
+        let name = path.to_string();
+        let mut values = Vec::with_capacity(fields.len());
+
+        for (i, field) in fields.iter().enumerate() {
+            let previous = fields[..i]
+                .iter()
+                .find(|previous| previous.name.as_str() == field.name.as_str());
+
+            if let Some(previous) = previous {
+                return Err(report::duplicate_field(
+                    &field.name,
+                    &field.name.span(),
+                    &previous.name.span(),
+                )
+                .into());
+            }
+
+            let decl = named_field(&name, decls, &field.name)?;
+            values.push((&decl.r#type, &field.value));
+        }
+
+        let ty = self.check_field_values(type_params, &generic, &values, expected, path.span())?;
+
+        match base {
+            // Variants can't be copied, since their fields depend on the variant
+            Some(base) if path.as_ident().is_none() => {
+                return Err(report::update_of_variant(&base.span).into());
+            }
+            Some(base) => {
+                let expected = Expected {
+                    ty: ty.clone(),
+                    span: Some(path.span()),
+                };
+                self.check_expr(base, &expected)?;
+            }
+            None => {
+                let missing = missing_fields(
+                    decls,
+                    &fields
+                        .iter()
+                        .map(|field| field.name.as_str())
+                        .collect::<Vec<_>>(),
+                );
+                let help = path.as_ident().map(|_| {
+                    format!("Use `..other` to copy the remaining fields from another `{name}`")
+                });
+
+                if !missing.is_empty() {
+                    return Err(report::missing_fields(&name, &missing, &path.span(), help).into());
+                }
+            }
+        }
+
+        Ok(ty)
+    }
+
+    /// Returns the type of a field and the annotation declaring it.
+    fn field_type(&self, ty: &Type, field: &Ident) -> Result<Expected, Error> {
+        if let Type::Tuple(types) = ty {
+            return match field
+                .parse::<usize>()
+                .ok()
+                .and_then(|index| types.get(index))
+            {
+                Some(ty) => Ok(Expected {
+                    ty: ty.clone(),
+                    span: None,
+                }),
+                None => {
+                    let names = (0..types.len()).map(|i| i.to_string()).collect::<Vec<_>>();
+                    let names = names.iter().map(String::as_str).collect::<Vec<_>>();
+
+                    Err(report::unknown_field(&ty.to_string(), field, &field.span(), &names).into())
+                }
+            };
+        }
+
+        let fields = match ty {
+            Type::Struct(name, _) => &self.structs[name.as_str()].fields[..],
+            _ => &[],
+        };
+        let decl = named_field(&ty.to_string(), fields, field)?;
+
+        self.field_decl_type(&decl.r#type, ty, None)
+    }
+
+    /// Returns the type of a field declared as `decl` in a value of the struct or enum type `ty`.
+    ///
+    /// The type parameters of a generic type are replaced by the type arguments of `ty`,
+    /// in which case the field's type is required by whatever required `ty`, at `span`.
+    fn field_decl_type(
+        &self,
+        decl: &syntax::Type,
+        ty: &Type,
+        span: Option<Span>,
+    ) -> Result<Expected, Error> {
+        let (Type::Struct(name, args) | Type::Enum(name, args)) = ty else {
+            unreachable!("BUG: only structs and enums have fields");
+        };
+        let type_params = self.type_params_of(name);
+        let generic = self.resolve_generic_type(decl, type_params)?;
+
+        if !generic.has_params() {
+            return Ok(Expected {
+                ty: generic,
+                span: Some(decl.span),
+            });
+        }
+
+        Ok(Expected {
+            ty: generic
+                .substitute(&type_args(type_params, args))
+                .expect("BUG: types have every type argument"),
+            span,
+        })
+    }
+
+    /// Infers the type of a variant used as a value, like `Shape::Empty`,
+    /// whose type arguments can be taken from the `expected` type.
+    fn infer_variant_value(
+        &self,
+        r#enum: &Enum,
+        variant: &Variant,
+        path: &Path,
+        expected: Option<&Type>,
+    ) -> Result<Type, Error> {
+        let ty = match &variant.fields {
+            VariantFields::Unit => generic_type(r#enum),
+            // Tuple variants can be used as functions constructing them
+            VariantFields::Tuple(types) => {
+                let params = types
+                    .iter()
+                    .map(|decl| self.resolve_generic_type(decl, &r#enum.type_params))
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                Type::Fn(params, generic_type(r#enum).into())
+            }
+            VariantFields::Struct(_) => {
+                return Err(report::wrong_variant_syntax(
+                    &path.to_string(),
+                    "struct variant",
+                    &format!("{path} {{ … }}"),
+                    &path.span(),
+                )
+                .into());
+            }
+        };
+
+        instantiate(&ty, &expected_type_args(&ty, expected), path.span())
+    }
+
+    /// Checks the values of the fields of a struct or variant, paired with their declarations,
+    /// and returns the `generic` type of the struct or enum with the inferred type arguments.
+    ///
+    /// The type arguments are taken from the `expected` type, or else inferred from the values.
+    fn check_field_values(
+        &mut self,
+        type_params: &[Ident],
+        generic: &Type,
+        values: &[(&syntax::Type, &'a Expr<'a>)],
+        expected: Option<&Type>,
+        span: Span,
+    ) -> Result<Type, Error> {
+        let decls = values
+            .iter()
+            .map(|(decl, _)| {
+                let ty = self.resolve_generic_type(decl, type_params)?;
+
+                Ok((ty, Some(decl.span)))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        let values = values.iter().map(|(_, value)| *value).collect::<Vec<_>>();
+        let mut type_args = expected_type_args(generic, expected);
+
+        self.check_generic_values(&decls, &values, &mut type_args)?;
+
+        instantiate(generic, &type_args, span)
+    }
+
+    /// Checks values against the generic types declaring them, paired with their annotations,
+    /// and adds the type arguments inferred from the values to `type_args`.
+    ///
+    /// Closures are checked last, so that they can take the types of their arguments
+    /// from the other values, like in `map(xs, |x| x + 1)`.
+    fn check_generic_values(
+        &mut self,
+        decls: &[(Type, Option<Span>)],
+        values: &[&'a Expr<'a>],
+        type_args: &mut HashMap<String, Type>,
+    ) -> Result<(), Error> {
+        let (closures, others): (Vec<_>, Vec<_>) = decls
+            .iter()
+            .zip(values)
+            .partition(|(_, value)| matches!(value.kind, ExprKind::Closure(_)));
+
+        for ((generic, span), value) in others.into_iter().chain(closures) {
+            if let Some(ty) = generic.substitute(type_args) {
+                // Generic types are required by the values they were inferred from instead
+                let span = if generic.has_params() { None } else { *span };
+                self.check_expr(value, &Expected { ty, span })?;
+
+                continue;
+            }
+
+            let found = match (&value.kind, generic) {
+                (ExprKind::Closure(closure), Type::Fn(params, _)) => {
+                    let params = params
+                        .iter()
+                        .map(|param| param.substitute(type_args))
+                        .collect::<Option<Vec<_>>>();
+                    let ty = self.infer_closure(closure, params.as_deref(), None)?;
+                    self.types.exprs.insert(value.span, ty.clone());
+
+                    ty
+                }
+                _ => self.infer_expr(value)?,
+            };
+
+            if !generic.bind_params(&found, type_args) {
+                return Err(report::mismatched_types(generic, None, &found, &value.span).into());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Infers the type of a closure.
+    /// Types that aren't annotated are taken from the expected types, if any.
+    fn infer_closure(
+        &mut self,
+        closure: &'a Closure<'a>,
+        expected_params: Option<&[Type]>,
+        expected_return_type: Option<&Type>,
+    ) -> Result<Type, Error> {
+        check_bindings(
+            &closure
+                .args
+                .iter()
+                .map(|arg| &arg.pattern)
+                .collect::<Vec<_>>(),
+        )?;
+
+        let (expected_params, expected_return_type) = match expected_params {
+            Some(params) if params.len() == closure.args.len() => {
+                (Some(params), expected_return_type)
+            }
+            _ => (None, None),
+        };
+
+        let mut params = Vec::with_capacity(closure.args.len());
+
+        for (i, arg) in closure.args.iter().enumerate() {
+            let ty = match (&arg.r#type, expected_params) {
+                (Some(ty), _) => self.resolve_type(ty)?,
+                (None, Some(expected_params)) => expected_params[i].clone(),
+                (None, None) => {
+                    let name = match &arg.pattern.kind {
+                        PatternKind::Binding { name, .. } => Some(name.as_str()),
+                        _ => None,
+                    };
+
+                    return Err(report::type_annotation_needed(name, &arg.pattern.span).into());
+                }
+            };
+
+            params.push(ty);
+        }
+
+        let return_type = match (&closure.return_type, expected_return_type) {
+            (Some(return_type), _) => Some(Expected {
+                ty: self.resolve_type(return_type)?,
+                span: Some(return_type.span),
+            }),
+            (None, Some(expected_return_type)) => Some(Expected {
+                ty: expected_return_type.clone(),
+                span: None,
+            }),
+            (None, None) => None,
+        };
+
+        // `break`, `continue` and `return` can't leave the closure
+        let loops = std::mem::take(&mut self.loops);
+        let breaks = self.breaks;
+        let outer_return_type = std::mem::replace(&mut self.return_type, return_type);
+
+        self.scopes.push(Scope::new());
+
+        let result = closure
+            .args
+            .iter()
+            .zip(&params)
+            .try_for_each(|(arg, ty)| {
+                let expected = Expected {
+                    ty: ty.clone(),
+                    span: arg.r#type.as_ref().map(|ty| ty.span),
+                };
+
+                self.check_irrefutable(&arg.pattern, &expected)
+            })
+            .and_then(|()| self.infer_closure_body(&closure.body));
+
+        self.scopes.pop();
+        self.return_type = outer_return_type;
+        self.breaks = breaks;
+        self.loops = loops;
+
+        Ok(Type::Fn(params, Box::new(result?)))
+    }
+
+    fn infer_closure_body(&mut self, body: &'a Expr<'a>) -> Result<Type, Error> {
+        if let Some(expected) = self.return_type.clone() {
+            self.check_expr(body, &expected)?;
+
+            return Ok(expected.ty);
+        }
+
+        let ty = self.infer_expr(body)?;
+
+        // A `return` in the body determined the return type
+        match &self.return_type {
+            Some(expected) => {
+                expected.check(&ty, body.span)?;
+
+                Ok(expected.ty.clone())
+            }
+            None => Ok(ty),
+        }
+    }
+
+    /// Checks the body of a loop and returns the type of the loop.
+    fn check_loop(&mut self, r#loop: Loop<'a>, body: &'a Block<'a>) -> Result<Type, Error> {
+        let breaks_before = self.breaks;
+        self.loops.push(r#loop);
+
+        let expected = Expected {
+            ty: Type::Unit,
+            span: None,
+        };
+        let result = self.check_block(body, &expected);
+
+        let r#loop = self.loops.pop().expect("BUG: loop was pushed before");
+        let breaks = std::mem::replace(&mut self.breaks, breaks_before);
+        result?;
+
+        Ok(match r#loop.kind {
+            LoopKind::While | LoopKind::For => Type::Unit,
+            // A `loop` without a `break` never finishes
+            LoopKind::Loop { .. } if breaks == breaks_before => Type::Never,
+            LoopKind::Loop { break_type } => break_type.map_or(Type::Unit, |expected| expected.ty),
+        })
+    }
+
+    /// Finds the loop that a `break` or `continue` refers to.
+    fn resolve_loop(
+        &self,
+        label: Option<&Ident>,
+        keyword: &str,
+        span: Span,
+    ) -> Result<usize, Error> {
+        match label {
+            Some(label) => self
+                .loops
+                .iter()
+                .rposition(|r#loop| r#loop.label == Some(label.as_str()))
+                .ok_or_else(|| report::undeclared_label(label, &label.span()).into()),
+            None => self
+                .loops
+                .len()
+                .checked_sub(1)
+                .ok_or_else(|| report::outside_of_loop(keyword, &span).into()),
+        }
+    }
+
+    /// Checks the length of an array, like the `n` in `[0; n]`.
+    fn check_len(&mut self, len: &'a Expr<'a>) -> Result<(), Error> {
+        let expected = Expected {
+            ty: Type::I32,
+            span: None,
+        };
+
+        self.check_expr(len, &expected)
+    }
+
+    /// Checks the index of `expr[index]`, where `expr` has the type `ty`,
+    /// and returns the type of the element.
+    fn index_type(&mut self, ty: &Type, span: Span, index: &'a Expr<'a>) -> Result<Type, Error> {
+        let Type::Array(element_type) = ty else {
+            return Err(report::not_indexable(ty, &span).into());
+        };
+
+        self.check_len(index)?;
+
+        Ok((**element_type).clone())
+    }
+
+    fn check_cond(&mut self, cond: &'a Expr<'a>) -> Result<(), Error> {
+        let expected = Expected {
+            ty: Type::Bool,
+            span: None,
+        };
+
+        self.check_expr(cond, &expected)
+    }
+
+    fn infer_arithmetic(
+        &mut self,
+        op: &str,
+        lhs: &'a Expr<'a>,
+        rhs: &'a Expr<'a>,
+        expected: Option<&Type>,
+    ) -> Result<Type, Error> {
+        let (lhs, rhs) = literal_last(lhs, rhs);
+        // The operands have the type of the result, which literals can take from the context
+        let ty = self.infer_expr_expecting(lhs, expected)?;
+        // `+` also concatenates strings
+        let is_supported = ty.is_numeric() || (op == "+" && ty == Type::String);
+
+        if !is_supported {
+            return Err(report::unsupported_operator(op, &ty, &lhs.span).into());
+        }
+
+        let expected = Expected { ty, span: None };
+        self.check_expr(rhs, &expected)?;
+
+        Ok(expected.ty)
+    }
+
+    /// Checks an assignment with the operator `op`, which has the type `()`.
+    fn check_assignment(
+        &mut self,
+        op: &str,
+        lhs: &'a Expr<'a>,
+        rhs: &'a Expr<'a>,
+    ) -> Result<Type, Error> {
+        let expected = self.infer_place(lhs, lhs.span)?;
+        self.types.exprs.insert(lhs.span, expected.ty.clone());
+
+        let is_supported = match op {
+            "=" => true,
+            "+=" => expected.ty.is_numeric() || expected.ty == Type::String,
+            _ => expected.ty.is_numeric(),
+        };
+
+        if !is_supported {
+            return Err(report::unsupported_operator(op, &expected.ty, &lhs.span).into());
+        }
+
+        self.check_expr(rhs, &expected)?;
+
+        Ok(Type::Unit)
+    }
+
+    /// Infers the type of a place that gets assigned to, like `p.x`,
+    /// and the declaration giving it that type.
+    /// Places are only mutable if their variable is, `lhs_span` is the whole place.
+    fn infer_place(&mut self, place: &'a Expr<'a>, lhs_span: Span) -> Result<Expected, Error> {
+        match &place.kind {
+            ExprKind::Parens(place) => self.infer_place(place, lhs_span),
+            ExprKind::Var(name) => {
+                let variable = self
+                    .resolve_variable(name)?
+                    .ok_or_else(|| report::undeclared_variable(name, &name.span()))?;
+
+                if !variable.mutable {
+                    return Err(
+                        report::assignment_to_immutable(name, &lhs_span, &variable.span).into(),
+                    );
+                }
+
+                Ok(Expected {
+                    ty: variable.ty.clone(),
+                    span: Some(variable.span),
+                })
+            }
+            ExprKind::Field(expr, field) => {
+                let expected = self.infer_place(expr, lhs_span)?;
+
+                self.field_type(&expected.ty, field)
+            }
+            ExprKind::Index(expr, index) => {
+                let expected = self.infer_place(expr, lhs_span)?;
+
+                Ok(Expected {
+                    ty: self.index_type(&expected.ty, expr.span, index)?,
+                    span: None,
+                })
+            }
+            _ => Err(report::invalid_assignment_target(&place.span).into()),
+        }
+    }
+
+    fn infer_comparison(
+        &mut self,
+        op: &str,
+        lhs: &'a Expr<'a>,
+        rhs: &'a Expr<'a>,
+    ) -> Result<Type, Error> {
+        let (lhs, rhs) = literal_last(lhs, rhs);
+        let ty = self.infer_expr(lhs)?;
+        let is_comparable = match op {
+            "==" | "!=" => !matches!(ty, Type::Fn(..)),
+            _ => ty.is_numeric(),
+        };
+
+        if !is_comparable {
+            return Err(report::unsupported_operator(op, &ty, &lhs.span).into());
+        }
+
+        self.check_expr(rhs, &Expected { ty, span: None })?;
+
+        Ok(Type::Bool)
+    }
+
+    fn infer_logical(
+        &mut self,
+        op: &str,
+        lhs: &'a Expr<'a>,
+        rhs: &'a Expr<'a>,
+    ) -> Result<Type, Error> {
+        let ty = self.infer_expr(lhs)?;
+
+        if ty != Type::Bool {
+            return Err(report::unsupported_operator(op, &ty, &lhs.span).into());
+        }
+
+        self.check_expr(rhs, &Expected { ty, span: None })?;
+
+        Ok(Type::Bool)
+    }
+
+    fn infer_call(
+        &mut self,
+        callee: &'a Expr<'a>,
+        args: &'a [Expr<'a>],
+        span: Span,
+        expected: Option<&Type>,
+    ) -> Result<Type, Error> {
+        let (ty, r#fn) = match &callee.kind {
+            ExprKind::Var(name) => match self.resolve_pending_closure(name) {
+                Some(pending) => return self.infer_pending_call(pending, args, span),
+                None => match self.resolve_variable(name)? {
+                    Some(variable) => (variable.ty.clone(), variable.r#fn),
+                    None => match prelude_variant(name) {
+                        Some(path) => return self.infer_variant_call(&path, args, span, expected),
+                        None => return Err(report::undeclared_function(name, &name.span()).into()),
+                    },
+                },
+            },
+            ExprKind::Path(path) => match self.resolve_assoc_fn(path)? {
+                Some((self_type, r#fn)) => (self.fn_type(r#fn, Some(&self_type))?, Some(r#fn)),
+                None if !self.is_struct_path(path) => {
+                    return self.infer_variant_call(path, args, span, expected);
+                }
+                None => (self.infer_expr(callee)?, None),
+            },
+            _ => (self.infer_expr(callee)?, None),
+        };
+
+        let Type::Fn(params, return_type) = ty else {
+            return Err(report::not_callable(&ty, &callee.span).into());
+        };
+
+        let param_spans = r#fn.map(param_spans).unwrap_or_default();
+
+        self.check_args(
+            params,
+            *return_type,
+            &param_spans,
+            args,
+            span,
+            r#fn,
+            HashMap::new(),
+            expected,
+        )
+    }
+
+    /// Infers the type of the first call of a pending closure,
+    /// whose parameters get the types of the arguments.
+    fn infer_pending_call(
+        &mut self,
+        pending: PendingClosure<'a>,
+        args: &'a [Expr<'a>],
+        span: Span,
+    ) -> Result<Type, Error> {
+        let closure_args = &pending.closure.args;
+
+        if closure_args.len() != args.len() {
+            self.poison_pending_closure(pending);
+
+            return Err(
+                report::wrong_number_of_args(closure_args.len(), args.len(), &span, None).into(),
+            );
+        }
+
+        let mut arg_types = Vec::with_capacity(args.len());
+
+        for (arg, closure_arg) in args.iter().zip(closure_args) {
+            // Annotated parameters are checked like those of other functions
+            let ty = match &closure_arg.r#type {
+                Some(annotation) => self.resolve_type(annotation).and_then(|ty| {
+                    let expected = Expected {
+                        ty,
+                        span: Some(annotation.span),
+                    };
+                    self.check_expr(arg, &expected)?;
+
+                    Ok(expected.ty)
+                }),
+                None => self.infer_expr(arg),
+            };
+
+            match ty {
+                Ok(ty) => arg_types.push(ty),
+                Err(error) => {
+                    self.poison_pending_closure(pending);
+
+                    return Err(error);
+                }
+            }
+        }
+
+        match self.infer_pending_closure(pending, Some(&arg_types))? {
+            Type::Fn(_, return_type) => Ok(*return_type),
+            _ => unreachable!("BUG: closures have function types"),
+        }
+    }
+
+    /// Infers the type of a call constructing a tuple variant, like `Some(1)`,
+    /// whose type arguments can be taken from the `expected` type.
+    fn infer_variant_call(
+        &mut self,
+        path: &Path,
+        args: &'a [Expr<'a>],
+        span: Span,
+        expected: Option<&Type>,
+    ) -> Result<Type, Error> {
+        let (r#enum, variant) = self.resolve_variant(path)?;
+        let types = match &variant.fields {
+            VariantFields::Tuple(types) => types,
+            VariantFields::Unit => {
+                return Err(report::not_callable(&generic_type(r#enum), &path.span()).into());
+            }
+            VariantFields::Struct(_) => {
+                return Err(report::wrong_variant_syntax(
+                    &path.to_string(),
+                    "struct variant",
+                    &format!("{path} {{ … }}"),
+                    &path.span(),
+                )
+                .into());
+            }
+        };
+
+        if types.len() != args.len() {
+            return Err(report::wrong_number_of_args(types.len(), args.len(), &span, None).into());
+        }
+
+        let values = types.iter().zip(args).collect::<Vec<_>>();
+
+        self.check_field_values(
+            &r#enum.type_params,
+            &generic_type(r#enum),
+            &values,
+            expected,
+            span,
+        )
+    }
+
+    /// Whether `path` refers to something of a struct, like `Point::new`.
+    fn is_struct_path(&self, path: &Path) -> bool {
+        matches!(
+            path.segments.as_slice(),
+            [type_name, _] if self.structs.contains_key(type_name.as_str())
+        )
+    }
+
+    fn infer_method_call(
+        &mut self,
+        receiver: &'a Expr<'a>,
+        method: &Ident,
+        args: &'a [Expr<'a>],
+        span: Span,
+        expected: Option<&Type>,
+    ) -> Result<Type, Error> {
+        let ty = self.infer_expr(receiver)?;
+
+        if matches!(ty, Type::Array(_)) && method.as_str() == "len" {
+            return self.check_args(
+                Vec::new(),
+                Type::I32,
+                &[],
+                args,
+                span,
+                None,
+                HashMap::new(),
+                None,
+            );
+        }
+
+        let r#fn = match &ty {
+            Type::Struct(name, _) | Type::Enum(name, _) => {
+                self.impls.get(name.as_str()).and_then(|fns| {
+                    fns.iter()
+                        .find(|r#fn| r#fn.name.as_str() == method.as_str())
+                })
+            }
+            _ => None,
+        };
+
+        let Some(r#fn) = r#fn.filter(|r#fn| r#fn.receiver.is_some()) else {
+            let help = match (r#fn, &ty) {
+                (Some(_), _) => Some(format!(
+                    "`{ty}::{}` doesn't take `self`, call it with `{ty}::{}(…)`",
+                    method.as_str(),
+                    method.as_str(),
+                )),
+                (None, Type::Struct(name, _))
+                    if self.structs[name.as_str()]
+                        .fields
+                        .iter()
+                        .any(|field| field.name.as_str() == method.as_str()) =>
+                {
+                    Some(format!("`{}` is a field, not a method", method.as_str()))
+                }
+                (None, _) => None,
+            };
+
+            return Err(report::unknown_method(&ty, method, &method.span(), help).into());
+        };
+
+        let Type::Fn(params, return_type) = self.fn_type(r#fn, None)? else {
+            unreachable!("BUG: functions have function types");
+        };
+        // The type arguments of a generic `impl` block are those of the receiver
+        let mut type_args = HashMap::new();
+        self.impl_type(self.fn_impls[&r#fn.name.span()])?
+            .bind_params(&ty, &mut type_args);
+
+        // `self` is given by the receiver
+        self.check_args(
+            params,
+            *return_type,
+            &param_spans(r#fn)[1..],
+            args,
+            span,
+            Some(r#fn),
+            type_args,
+            expected,
+        )
+    }
+
+    /// Checks that `args` have the types of the parameters of a function
+    /// and returns the type of the call.
+    /// `param_spans` are the annotations of the parameters, if the declaration `r#fn` is known.
+    ///
+    /// The type arguments of a generic function are those already known in `type_args`,
+    /// like the ones of the receiver of a method, or else are inferred from the arguments,
+    /// or else from the `expected` type of the call.
+    #[allow(clippy::too_many_arguments)]
+    fn check_args(
+        &mut self,
+        params: Vec<Type>,
+        return_type: Type,
+        param_spans: &[Span],
+        args: &'a [Expr<'a>],
+        span: Span,
+        r#fn: Option<&Fn<'a>>,
+        mut type_args: HashMap<String, Type>,
+        expected: Option<&Type>,
+    ) -> Result<Type, Error> {
+        if params.len() != args.len() {
+            return Err(report::wrong_number_of_args(
+                params.len(),
+                args.len(),
+                &span,
+                r#fn.map(|r#fn| r#fn.name.span()),
+            )
+            .into());
+        }
+
+        if r#fn.is_some_and(|r#fn| self.is_generic(r#fn)) {
+            let decls = params
+                .into_iter()
+                .enumerate()
+                .map(|(i, param)| (param, param_spans.get(i).copied()))
+                .collect::<Vec<_>>();
+            for (param, arg) in expected_type_args(&return_type, expected) {
+                type_args.entry(param).or_insert(arg);
+            }
+
+            self.check_generic_values(&decls, &args.iter().collect::<Vec<_>>(), &mut type_args)?;
+
+            return instantiate(&return_type, &type_args, span);
+        }
+
+        for (i, (param, arg)) in params.into_iter().zip(args).enumerate() {
+            let expected = Expected {
+                ty: param,
+                span: param_spans.get(i).copied(),
+            };
+
+            self.check_expr(arg, &expected)?;
+        }
+
+        Ok(return_type)
+    }
+}
+
+/// Returns the annotations of the parameters of a function, starting with `self` for methods.
+fn param_spans(r#fn: &Fn) -> Vec<Span> {
+    r#fn.receiver
+        .iter()
+        .map(|receiver| receiver.name.span())
+        .chain(r#fn.args.iter().map(|arg| arg.r#type.span))
+        .collect()
+}
+
+/// Returns the type of an integer literal with the given suffix.
+/// Without a suffix, it's the `expected` integer type or else `i32`.
+fn int_type(suffix: Option<&str>, expected: Option<&Type>) -> Type {
+    match (suffix, expected) {
+        (Some(suffix), _) => {
+            Type::from_name(suffix).expect("BUG: literal suffixes are checked by the parser")
+        }
+        (None, Some(expected)) if expected.is_integer() => expected.clone(),
+        (None, _) => Type::I32,
+    }
+}
+
+fn check_int_range(value: i128, ty: &Type, span: Span) -> Result<(), Error> {
+    let (min, max) = match ty {
+        Type::I32 => (i32::MIN.into(), i32::MAX.into()),
+        _ => (i64::MIN.into(), i64::MAX.into()),
+    };
+
+    if !(min..=max).contains(&value) {
+        return Err(report::int_out_of_range(ty, min, max, &span).into());
+    }
+
+    Ok(())
+}
+
+/// Orders the operands of a binary operator so that an integer literal without a suffix
+/// comes last, so that it gets the type of the other operand, like in `1 + x` with `x: i64`.
+fn literal_last<'a>(lhs: &'a Expr<'a>, rhs: &'a Expr<'a>) -> (&'a Expr<'a>, &'a Expr<'a>) {
+    let is_unsuffixed = |expr: &Expr| {
+        let literal = match &expr.kind {
+            ExprKind::Neg(operand) => operand,
+            _ => expr,
+        };
+
+        matches!(literal.kind, ExprKind::Int { suffix: None, .. })
+    };
+
+    if is_unsuffixed(lhs) && !is_unsuffixed(rhs) {
+        (rhs, lhs)
+    } else {
+        (lhs, rhs)
+    }
+}
+
+/// Returns the path of the variant of the prelude named `name`, like `None`,
+/// which can be used without the name of its enum.
+fn prelude_variant<'p>(name: &Ident<'p>) -> Option<Path<'p>> {
+    prelude::variant_enum(name)?;
+
+    Some(Path {
+        segments: vec![name.clone()],
+    })
+}
+
+/// Returns the type of `r#enum` with its type parameters as arguments, like `Option<T>`.
+fn generic_type(r#enum: &Enum) -> Type {
+    Type::Enum(r#enum.name.to_string(), param_types(&r#enum.type_params))
+}
+
+fn param_types(type_params: &[Ident]) -> Vec<Type> {
+    type_params
+        .iter()
+        .map(|param| Type::Param(param.to_string()))
+        .collect()
+}
+
+/// Returns the type of the values matched by a struct or variant pattern,
+/// which is the `expected` type if it's an instance of the `generic` type.
+fn pattern_type(generic: Type, expected: &Type) -> Type {
+    match expected {
+        Type::Never => generic,
+        _ if generic.bind_params(expected, &mut HashMap::new()) => expected.clone(),
+        _ => generic,
+    }
+}
+
+/// Returns the type arguments that make the `generic` type the `expected` one, if it can be.
+fn expected_type_args(generic: &Type, expected: Option<&Type>) -> HashMap<String, Type> {
+    let mut args = HashMap::new();
+
+    match expected {
+        Some(expected) if generic.bind_params(expected, &mut args) => args,
+        _ => HashMap::new(),
+    }
+}
+
+/// Replaces the type parameters of a `generic` type by their type arguments in `args`,
+/// or reports that the type of the value at `span` can't be inferred.
+fn instantiate(generic: &Type, args: &HashMap<String, Type>, span: Span) -> Result<Type, Error> {
+    generic.substitute(args).ok_or_else(|| {
+        let unknown = generic.substitute_or(args, &Type::Param("_".into()));
+        let example = generic.substitute_or(args, &Type::I32);
+
+        report::type_args_needed(&unknown.to_string(), &example.to_string(), &span).into()
+    })
+}
+
+/// Returns the type of a function used as a value at `span`.
+/// The type arguments of a generic function are taken from the `expected` type.
+fn instantiate_fn(
+    ty: Type,
+    is_generic: bool,
+    expected: Option<&Type>,
+    span: Span,
+) -> Result<Type, Error> {
+    match is_generic {
+        true => instantiate(&ty, &expected_type_args(&ty, expected), span),
+        false => Ok(ty),
+    }
+}
+
+/// Checks that the type parameters of a declaration have different names.
+fn check_type_params(type_params: &[Ident]) -> Result<(), Error> {
+    for (i, param) in type_params.iter().enumerate() {
+        let previous = type_params[..i]
+            .iter()
+            .find(|previous| previous.as_str() == param.as_str());
+
+        if let Some(previous) = previous {
+            return Err(
+                report::duplicate_type_param(param, &param.span(), &previous.span()).into(),
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks that the patterns of a `let`, an arm or the arguments of a function
+/// don't bind a name more than once.
+fn check_bindings(patterns: &[&Pattern]) -> Result<(), Error> {
+    let bindings = patterns
+        .iter()
+        .flat_map(|pattern| pattern.bindings())
+        // Paths like `None` are parsed as bindings but refer to variants
+        .filter(|name| prelude_variant(name).is_none())
+        .collect::<Vec<_>>();
+
+    for (i, name) in bindings.iter().enumerate() {
+        let previous = bindings[..i]
+            .iter()
+            .find(|previous| previous.as_str() == name.as_str());
+
+        if let Some(previous) = previous {
+            return Err(report::duplicate_binding(name, &name.span(), &previous.span()).into());
+        }
+    }
+
+    Ok(())
+}
+
+/// Pairs type parameters with their arguments.
+fn type_args(type_params: &[Ident], args: &[Type]) -> HashMap<String, Type> {
+    type_params
+        .iter()
+        .map(|param| param.to_string())
+        .zip(args.iter().cloned())
+        .collect()
+}
+
+/// Finds the declaration of a field of the struct or variant `name`, which has the given `fields`.
+fn named_field<'f, 'a>(
+    name: &str,
+    fields: &'f [Field<'a>],
+    field: &Ident,
+) -> Result<&'f Field<'a>, Error> {
+    fields
+        .iter()
+        .find(|decl| decl.name.as_str() == field.as_str())
+        .ok_or_else(|| {
+            let names = fields
+                .iter()
+                .map(|decl| decl.name.as_str())
+                .collect::<Vec<_>>();
+
+            report::unknown_field(name, field, &field.span(), &names).into()
+        })
+}
+
+/// Returns the names of the declared fields that aren't given.
+fn missing_fields<'a>(decls: &'a [Field<'a>], given: &[&str]) -> Vec<&'a str> {
+    decls
+        .iter()
+        .map(|decl| decl.name.as_str())
+        .filter(|name| !given.contains(name))
+        .collect()
+}
+
+impl Default for Typeck<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The types of the expressions of a checked file.
+#[derive(Debug, Default)]
+pub struct Types {
+    /// Expressions are identified by their span
+    exprs: HashMap<Span, Type>,
+    /// The types of the values matched by patterns, which are identified by their span
+    patterns: HashMap<Span, Type>,
+}
+
+impl Types {
+    pub fn expr(&self, expr: &Expr) -> &Type {
+        self.exprs
+            .get(&expr.span)
+            .expect("BUG: expressions are checked before their translation")
+    }
+
+    pub fn pattern(&self, pattern: &Pattern) -> &Type {
+        self.patterns
+            .get(&pattern.span)
+            .expect("BUG: patterns are checked before their translation")
+    }
+}
+
+/// Why checking an item, statement or expression stopped.
+#[derive(Debug)]
+enum Error {
+    /// A new error, which gets reported
+    Report(Report<'static>),
+    /// An error that was already reported, like the use of a variable whose declaration failed
+    Reported,
+}
+
+impl From<Report<'static>> for Error {
+    fn from(report: Report<'static>) -> Self {
+        Self::Report(report)
+    }
+}
+
+/// A type that a value needs to have, and the annotation requiring it.
+#[derive(Clone)]
+struct Expected {
+    ty: Type,
+    span: Option<Span>,
+}
+
+impl Expected {
+    fn check(&self, found: &Type, found_span: Span) -> Result<(), Error> {
+        if *found != self.ty && *found != Type::Never {
+            return Err(
+                report::mismatched_types(&self.ty, self.span.as_ref(), found, &found_span).into(),
+            );
+        }
+
+        Ok(())
+    }
+
+    /// The type of the elements of an expected array, which is required by the same annotation.
+    fn element_type(&self) -> Option<Expected> {
+        match &self.ty {
+            Type::Array(ty) => Some(Expected {
+                ty: (**ty).clone(),
+                span: self.span,
+            }),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Scope<'a> {
+    variables: Vec<Variable<'a>>,
+}
+
+impl Scope<'_> {
+    fn new() -> Self {
+        Self {
+            variables: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Variable<'a> {
+    name: &'a str,
+    /// The span of the name in the declaration
+    span: Span,
+    ty: Type,
+    mutable: bool,
+    /// The declaration of the function, if the variable refers to one
+    r#fn: Option<&'a Fn<'a>>,
+    /// Whether the declaration has errors, so that uses of the variable are errors already reported
+    poisoned: bool,
+    /// The closure the variable is bound to, if its parameter types are still unknown
+    pending: Option<PendingClosure<'a>>,
+}
+
+/// A closure bound by `let` without annotated parameters,
+/// whose parameter types are taken from its first call.
+#[derive(Debug, Clone, Copy)]
+struct PendingClosure<'a> {
+    closure: &'a Closure<'a>,
+    span: Span,
+    /// The span of the `let` pattern
+    pattern_span: Span,
+    /// The number of scopes, and of variables in the last one, where the closure is declared
+    scopes: usize,
+    variables: usize,
+}
+
+struct Loop<'a> {
+    label: Option<&'a str>,
+    kind: LoopKind,
+}
+
+enum LoopKind {
+    While,
+    For,
+    Loop {
+        /// The type of the values passed to `break`, known after the first `break`
+        /// unless the loop itself is expected to have a certain type.
+        break_type: Option<Expected>,
+    },
+}
@@ -0,0 +1,404 @@
+//! Checks that the arms of a `match` cover every possible value.
+//!
+//! Patterns are simplified to constructors applied to sub-patterns and wildcards.
+//! The missing patterns are found by splitting the matrix of patterns column by column,
+//! following "Warnings for pattern matching" by Luc Maranget.
+
+use std::{iter, slice};
+
+use crate::syntax::pattern::PatternKind;
+use crate::syntax::r#enum::VariantFields;
+use crate::syntax::r#match::Arm;
+use crate::syntax::{Path, Pattern, Span};
+use crate::typeck::{prelude_variant, Error, Type, Typeck};
+use crate::{prelude, report};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Constructor {
+    Bool(bool),
+    Int(i64),
+    /// The index of an enum variant
+    Variant(usize),
+    /// The only constructor of a struct
+    Struct,
+    /// The only constructor of a tuple
+    Tuple,
+}
+
+#[derive(Debug, Clone)]
+enum Pat {
+    Wildcard,
+    Constructor(Constructor, Vec<Pat>),
+}
+
+impl Typeck<'_> {
+    /// Checks that the arms of a `match` cover every value of type `ty`,
+    /// and warns about the arms after those that already do.
+    pub(super) fn check_exhaustiveness(
+        &mut self,
+        arms: &[Arm],
+        ty: &Type,
+        span: Span,
+    ) -> Result<(), Error> {
+        let mut rows = Vec::new();
+        let mut covering_arm = None;
+
+        for arm in arms {
+            if let Some(covering_span) = covering_arm {
+                let report = report::unreachable_arm(&arm.pattern.span, &covering_span);
+                self.diagnostics.warning(arm.pattern.span, report);
+                continue;
+            }
+
+            // Arms with a guard might not match, so they don't cover any values
+            if arm.guard.is_none() {
+                rows.push(vec![self.lower(&arm.pattern, ty)?]);
+
+                if self.missing(&rows, slice::from_ref(ty))?.is_empty() {
+                    covering_arm = Some(arm.pattern.span);
+                }
+            }
+        }
+
+        if covering_arm.is_some() {
+            return Ok(());
+        }
+
+        let missing = self
+            .missing(&rows, slice::from_ref(ty))?
+            .iter()
+            .map(|row| self.format(&row[0], ty))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let missing = missing.iter().map(String::as_str).collect::<Vec<_>>();
+
+        Err(report::non_exhaustive_match(&missing, &span).into())
+    }
+
+    /// Checks that `pattern` matches every value of type `ty`, like the pattern of a `let`.
+    pub(super) fn check_covers_all(&self, pattern: &Pattern, ty: &Type) -> Result<(), Error> {
+        let missing = self.missing_patterns(iter::once(pattern), ty)?;
+
+        if missing.is_empty() {
+            return Ok(());
+        }
+
+        let missing = missing.iter().map(String::as_str).collect::<Vec<_>>();
+
+        Err(report::refutable_pattern(&missing, &pattern.span).into())
+    }
+
+    /// Formats the patterns matching the values of type `ty` that none of `patterns` match.
+    fn missing_patterns<'p>(
+        &self,
+        patterns: impl Iterator<Item = &'p Pattern<'p>>,
+        ty: &Type,
+    ) -> Result<Vec<String>, Error> {
+        let rows = patterns
+            .map(|pattern| self.lower(pattern, ty).map(|pat| vec![pat]))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        self.missing(&rows, slice::from_ref(ty))?
+            .iter()
+            .map(|row| self.format(&row[0], ty))
+            .collect()
+    }
+
+    /// Simplifies a pattern that was checked to match values of type `ty`.
+    fn lower(&self, pattern: &Pattern, ty: &Type) -> Result<Pat, Error> {
+        Ok(match &pattern.kind {
+            PatternKind::Binding {
+                name,
+                mutable: false,
+            } if prelude_variant(name).is_some() => {
+                let path = prelude_variant(name).expect("BUG: checked by the guard");
+
+                Pat::Constructor(self.variant(&path), Vec::new())
+            }
+            PatternKind::Wildcard | PatternKind::Binding { .. } => Pat::Wildcard,
+            PatternKind::Int { value, .. } => {
+                Pat::Constructor(Constructor::Int(*value), Vec::new())
+            }
+            PatternKind::Bool(b) => Pat::Constructor(Constructor::Bool(*b), Vec::new()),
+            PatternKind::Path(path) => Pat::Constructor(self.variant(path), Vec::new()),
+            PatternKind::Tuple(patterns) => {
+                let Type::Tuple(types) = ty else {
+                    unreachable!("BUG: tuple patterns are checked before exhaustiveness");
+                };
+                let fields = types
+                    .iter()
+                    .zip(patterns)
+                    .map(|(ty, pattern)| self.lower(pattern, ty))
+                    .collect::<Result<_, _>>()?;
+
+                Pat::Constructor(Constructor::Tuple, fields)
+            }
+            PatternKind::TupleVariant(path, patterns) => {
+                let constructor = self.variant(path);
+                let fields = self
+                    .field_types(ty, &constructor)?
+                    .iter()
+                    .zip(patterns)
+                    .map(|((_, ty), pattern)| self.lower(pattern, ty))
+                    .collect::<Result<_, _>>()?;
+
+                Pat::Constructor(constructor, fields)
+            }
+            PatternKind::Struct { path, fields, .. } => {
+                let constructor = match path.as_ident() {
+                    Some(_) => Constructor::Struct,
+                    None => self.variant(path),
+                };
+                // Fields are put in the order of their declaration, omitted ones match anything
+                let fields = self
+                    .field_types(ty, &constructor)?
+                    .iter()
+                    .map(|(name, ty)| {
+                        match fields.iter().find(|field| field.name.as_str() == *name) {
+                            Some(field) => self.lower(&field.pattern, ty),
+                            None => Ok(Pat::Wildcard),
+                        }
+                    })
+                    .collect::<Result<_, _>>()?;
+
+                Pat::Constructor(constructor, fields)
+            }
+        })
+    }
+
+    fn variant(&self, path: &Path) -> Constructor {
+        let (r#enum, variant) = self
+            .resolve_variant(path)
+            .expect("BUG: variants are checked before exhaustiveness");
+        let index = r#enum
+            .variants
+            .iter()
+            .position(|other| other.name.as_str() == variant.name.as_str())
+            .expect("BUG: variants belong to their enum");
+
+        Constructor::Variant(index)
+    }
+
+    /// Returns every constructor of `ty`, or `None` if there are too many to list.
+    fn constructors(&self, ty: &Type) -> Option<Vec<Constructor>> {
+        Some(match ty {
+            Type::Bool => vec![Constructor::Bool(false), Constructor::Bool(true)],
+            Type::Struct(..) => vec![Constructor::Struct],
+            Type::Tuple(_) => vec![Constructor::Tuple],
+            Type::Enum(name, _) => (0..self.enums[name.as_str()].variants.len())
+                .map(Constructor::Variant)
+                .collect(),
+            _ => return None,
+        })
+    }
+
+    /// Returns the names and types of the fields of a constructor of `ty`.
+    /// Tuple fields are named by their index.
+    fn field_types(
+        &self,
+        ty: &Type,
+        constructor: &Constructor,
+    ) -> Result<Vec<(String, Type)>, Error> {
+        let fields = match (ty, constructor) {
+            (Type::Struct(name, _), Constructor::Struct) => &self.structs[name.as_str()].fields,
+            (Type::Tuple(types), Constructor::Tuple) => {
+                return Ok(types
+                    .iter()
+                    .enumerate()
+                    .map(|(i, ty)| (i.to_string(), ty.clone()))
+                    .collect());
+            }
+            (Type::Enum(name, _), Constructor::Variant(index)) => {
+                match &self.enums[name.as_str()].variants[*index].fields {
+                    VariantFields::Unit => return Ok(Vec::new()),
+                    VariantFields::Tuple(types) => {
+                        return types
+                            .iter()
+                            .enumerate()
+                            .map(|(i, decl)| {
+                                Ok((i.to_string(), self.field_decl_type(decl, ty, None)?.ty))
+                            })
+                            .collect();
+                    }
+                    VariantFields::Struct(fields) => fields,
+                }
+            }
+            _ => return Ok(Vec::new()),
+        };
+
+        fields
+            .iter()
+            .map(|field| {
+                let ty = self.field_decl_type(&field.r#type, ty, None)?.ty;
+
+                Ok((field.name.to_string(), ty))
+            })
+            .collect()
+    }
+
+    /// Returns rows of patterns matching values of the types `tys` that no row of `rows` matches.
+    fn missing(&self, rows: &[Vec<Pat>], tys: &[Type]) -> Result<Vec<Vec<Pat>>, Error> {
+        let Some((ty, tys)) = tys.split_first() else {
+            // Without columns, any row matches everything
+            return Ok(if rows.is_empty() {
+                vec![Vec::new()]
+            } else {
+                Vec::new()
+            });
+        };
+
+        // The constructors that the patterns of the first column start with
+        let used = rows
+            .iter()
+            .filter_map(|row| match &row[0] {
+                Pat::Constructor(constructor, _) => Some(constructor),
+                Pat::Wildcard => None,
+            })
+            .collect::<Vec<_>>();
+        let constructors = self.constructors(ty);
+        let mut missing = Vec::new();
+
+        // Values built by different constructors are covered separately,
+        // so that every missing variant gets reported
+        if let Some(constructors) = constructors
+            .as_ref()
+            .filter(|constructors| constructors.iter().all(|c| used.contains(&c)))
+        {
+            for constructor in constructors {
+                let field_types = self.field_types(ty, constructor)?;
+                let arity = field_types.len();
+                let tys = field_types
+                    .into_iter()
+                    .map(|(_, ty)| ty)
+                    .chain(tys.iter().cloned())
+                    .collect::<Vec<_>>();
+                let rows = specialize(rows, constructor, arity);
+
+                for row in self.missing(&rows, &tys)? {
+                    let (fields, rest) = row.split_at(arity);
+                    let pat = Pat::Constructor(constructor.clone(), fields.to_vec());
+
+                    missing.push(iter::once(pat).chain(rest.iter().cloned()).collect());
+                }
+            }
+
+            return Ok(missing);
+        }
+
+        // The other constructors are only covered by the rows starting with a wildcard,
+        // which also keeps the fields of recursive types from being expanded forever
+        let default = rows
+            .iter()
+            .filter(|row| matches!(row[0], Pat::Wildcard))
+            .map(|row| row[1..].to_vec())
+            .collect::<Vec<_>>();
+        let rest = self.missing(&default, tys)?;
+
+        if rest.is_empty() {
+            return Ok(missing);
+        }
+
+        // Values that aren't covered at all are reported with wildcards
+        // instead of listing every combination of their fields
+        let firsts = match constructors {
+            Some(constructors) => constructors
+                .into_iter()
+                .filter(|constructor| !used.contains(&constructor))
+                .map(|constructor| {
+                    let arity = self.field_types(ty, &constructor)?.len();
+
+                    Ok(Pat::Constructor(constructor, vec![Pat::Wildcard; arity]))
+                })
+                .collect::<Result<Vec<_>, Error>>()?,
+            // Constructors like integers can't all be listed
+            None => vec![Pat::Wildcard],
+        };
+
+        for first in firsts {
+            for row in &rest {
+                missing.push(
+                    iter::once(first.clone())
+                        .chain(row.iter().cloned())
+                        .collect(),
+                );
+            }
+        }
+
+        Ok(missing)
+    }
+
+    /// Formats a pattern matching values of type `ty` like `Shape::Circle(_)`.
+    fn format(&self, pat: &Pat, ty: &Type) -> Result<String, Error> {
+        let Pat::Constructor(constructor, fields) = pat else {
+            return Ok("_".into());
+        };
+
+        let (name, kind) = match (ty, constructor) {
+            (_, Constructor::Bool(b)) => return Ok(b.to_string()),
+            (_, Constructor::Int(n)) => return Ok(n.to_string()),
+            (Type::Struct(name, _), Constructor::Struct) => (name.clone(), None),
+            (Type::Tuple(_), Constructor::Tuple) => {
+                let fields = fields
+                    .iter()
+                    .zip(&self.field_types(ty, constructor)?)
+                    .map(|(field, (_, ty))| self.format(field, ty))
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                return Ok(match fields.as_slice() {
+                    [field] => format!("({field},)"),
+                    fields => format!("({})", fields.join(", ")),
+                });
+            }
+            (Type::Enum(name, _), Constructor::Variant(index)) => {
+                let variant = &self.enums[name.as_str()].variants[*index];
+                // Variants of the prelude are written without their enum, like `None`
+                let name = if prelude::variant_enum(&variant.name) == Some(name.as_str()) {
+                    variant.name.to_string()
+                } else {
+                    format!("{name}::{}", variant.name.as_str())
+                };
+
+                (name, Some(&variant.fields))
+            }
+            _ => unreachable!("BUG: constructor {constructor:?} doesn't belong to `{ty}`"),
+        };
+
+        let field_types = self.field_types(ty, constructor)?;
+        let fields = fields
+            .iter()
+            .zip(&field_types)
+            .map(|(field, (_, ty))| self.format(field, ty))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(match kind {
+            Some(VariantFields::Unit) => name,
+            Some(VariantFields::Tuple(_)) => format!("{name}({})", fields.join(", ")),
+            _ if fields.iter().all(|field| field == "_") => format!("{name} {{ .. }}"),
+            _ => {
+                let fields = field_types
+                    .iter()
+                    .zip(fields)
+                    .map(|((name, _), field)| format!("{name}: {field}"))
+                    .collect::<Vec<_>>();
+
+                format!("{name} {{ {} }}", fields.join(", "))
+            }
+        })
+    }
+}
+
+/// Keeps the rows that match values built by `constructor`,
+/// replacing their first pattern by the patterns of the constructor's fields.
+fn specialize(rows: &[Vec<Pat>], constructor: &Constructor, arity: usize) -> Vec<Vec<Pat>> {
+    rows.iter()
+        .filter_map(|row| {
+            let fields = match &row[0] {
+                Pat::Wildcard => vec![Pat::Wildcard; arity],
+                Pat::Constructor(other, fields) if other == constructor => fields.clone(),
+                Pat::Constructor(..) => return None,
+            };
+
+            Some(fields.into_iter().chain(row[1..].iter().cloned()).collect())
+        })
+        .collect()
+}
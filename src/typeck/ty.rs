@@ -0,0 +1,200 @@
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Type {
+    I32,
+    I64,
+    F64,
+    Bool,
+    String,
+    Unit,
+    /// The type of expressions that never produce a value, like `break`.
+    /// It can be used in place of any other type.
+    Never,
+    Fn(Vec<Type>, Box<Type>),
+    /// An array like `[i32]`
+    Array(Box<Type>),
+    /// A tuple with at least one element, like `(i32, bool)`
+    Tuple(Vec<Type>),
+    /// A range of integers like `0..n`
+    Range(Box<Type>),
+    /// A struct, identified by its name, with its type arguments like `Wrapper<i32>`
+    Struct(String, Vec<Type>),
+    /// An enum, identified by its name, with its type arguments like `Option<i32>`
+    Enum(String, Vec<Type>),
+    /// A type parameter of a generic declaration, like `T` in `fn id<T>(x: T) -> T`
+    Param(String),
+}
+
+impl Type {
+    /// Resolves the name of a built-in type.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "i32" => Type::I32,
+            "i64" => Type::I64,
+            "f64" => Type::F64,
+            "bool" => Type::Bool,
+            "string" => Type::String,
+            _ => return None,
+        })
+    }
+
+    pub fn is_numeric(&self) -> bool {
+        matches!(self, Type::I32 | Type::I64 | Type::F64)
+    }
+
+    pub fn is_integer(&self) -> bool {
+        matches!(self, Type::I32 | Type::I64)
+    }
+
+    /// Whether values of the type are JS primitives or functions, which are never changed in place,
+    /// so that copies of them can be shared.
+    pub fn is_primitive(&self) -> bool {
+        matches!(
+            self,
+            Type::I32
+                | Type::I64
+                | Type::F64
+                | Type::Bool
+                | Type::String
+                | Type::Unit
+                | Type::Never
+                | Type::Fn(_, _)
+        )
+    }
+
+    /// Whether the type refers to a type parameter.
+    pub fn has_params(&self) -> bool {
+        match self {
+            Type::Param(_) => true,
+            Type::Array(ty) | Type::Range(ty) => ty.has_params(),
+            Type::Tuple(tys) | Type::Struct(_, tys) | Type::Enum(_, tys) => {
+                tys.iter().any(Type::has_params)
+            }
+            Type::Fn(params, ret) => params.iter().any(Type::has_params) || ret.has_params(),
+            _ => false,
+        }
+    }
+
+    /// Replaces the type parameters by their type arguments in `args`.
+    /// Returns `None` if a type parameter has no type argument.
+    pub fn substitute(&self, args: &HashMap<String, Type>) -> Option<Type> {
+        self.substitute_with(&|name| args.get(name).cloned())
+    }
+
+    /// Replaces the type parameters by their type arguments in `args`, or else by `unknown`.
+    pub fn substitute_or(&self, args: &HashMap<String, Type>, unknown: &Type) -> Type {
+        self.substitute_with(&|name| Some(args.get(name).unwrap_or(unknown).clone()))
+            .expect("BUG: every type parameter has a replacement")
+    }
+
+    fn substitute_with(&self, arg: &dyn Fn(&str) -> Option<Type>) -> Option<Type> {
+        let substitute_all = |tys: &[Type]| {
+            tys.iter()
+                .map(|ty| ty.substitute_with(arg))
+                .collect::<Option<_>>()
+        };
+
+        Some(match self {
+            Type::Param(name) => arg(name)?,
+            Type::Array(ty) => Type::Array(ty.substitute_with(arg)?.into()),
+            Type::Range(ty) => Type::Range(ty.substitute_with(arg)?.into()),
+            Type::Tuple(tys) => Type::Tuple(substitute_all(tys)?),
+            Type::Struct(name, tys) => Type::Struct(name.clone(), substitute_all(tys)?),
+            Type::Enum(name, tys) => Type::Enum(name.clone(), substitute_all(tys)?),
+            Type::Fn(params, ret) => {
+                Type::Fn(substitute_all(params)?, ret.substitute_with(arg)?.into())
+            }
+            ty => ty.clone(),
+        })
+    }
+
+    /// Infers the type arguments of the type parameters in this type from the `found` type,
+    /// adding them to `args`.
+    /// Returns `false` if the types don't match.
+    pub fn bind_params(&self, found: &Type, args: &mut HashMap<String, Type>) -> bool {
+        let bind_all = |tys: &[Type], found: &[Type], args: &mut HashMap<String, Type>| {
+            tys.len() == found.len()
+                && tys
+                    .iter()
+                    .zip(found)
+                    .all(|(ty, found)| ty.bind_params(found, args))
+        };
+
+        match (self, found) {
+            // `!` fits any type, so it doesn't tell anything about the parameter
+            (_, Type::Never) => true,
+            (Type::Param(name), found) => match args.get(name) {
+                Some(arg) => arg == found,
+                None => {
+                    args.insert(name.clone(), found.clone());
+                    true
+                }
+            },
+            (Type::Array(ty), Type::Array(found)) | (Type::Range(ty), Type::Range(found)) => {
+                ty.bind_params(found, args)
+            }
+            (Type::Tuple(tys), Type::Tuple(found)) => bind_all(tys, found, args),
+            (Type::Struct(name, tys), Type::Struct(found_name, found))
+            | (Type::Enum(name, tys), Type::Enum(found_name, found)) => {
+                name == found_name && bind_all(tys, found, args)
+            }
+            (Type::Fn(params, ret), Type::Fn(found_params, found_ret)) => {
+                bind_all(params, found_params, args) && ret.bind_params(found_ret, args)
+            }
+            (ty, found) => ty == found,
+        }
+    }
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Type::I32 => write!(f, "i32"),
+            Type::I64 => write!(f, "i64"),
+            Type::F64 => write!(f, "f64"),
+            Type::Bool => write!(f, "bool"),
+            Type::String => write!(f, "string"),
+            Type::Unit => write!(f, "()"),
+            Type::Never => write!(f, "!"),
+            Type::Param(name) => write!(f, "{name}"),
+            Type::Struct(name, args) | Type::Enum(name, args) if args.is_empty() => {
+                write!(f, "{name}")
+            }
+            Type::Struct(name, args) | Type::Enum(name, args) => {
+                let args = args.iter().map(Type::to_string).collect::<Vec<_>>();
+
+                write!(f, "{name}<{}>", args.join(", "))
+            }
+            Type::Array(ty) => write!(f, "[{ty}]"),
+            Type::Tuple(tys) => match tys.as_slice() {
+                [ty] => write!(f, "({ty},)"),
+                tys => {
+                    let tys = tys.iter().map(Type::to_string).collect::<Vec<_>>();
+
+                    write!(f, "({})", tys.join(", "))
+                }
+            },
+            Type::Range(ty) => write!(f, "Range<{ty}>"),
+            Type::Fn(args, ret) => {
+                write!(f, "fn(")?;
+
+                for (i, arg) in args.iter().enumerate() {
+                    if i + 1 == args.len() {
+                        write!(f, "{arg}")?;
+                    } else {
+                        write!(f, "{arg}, ")?;
+                    }
+                }
+
+                write!(f, ")")?;
+
+                match **ret {
+                    Type::Unit => Ok(()),
+                    ref ret => write!(f, " -> {ret}"),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,41 @@
+mod common;
+
+use common::run;
+
+#[test]
+fn repeated_arrays_are_separate() {
+    let output = run(r#"
+        fn main() -> string {
+            let mut g = [[0; 2]; 2];
+            g[0][0] = 1;
+            let a = g[0][0];
+            let b = g[1][0];
+            f"{a} {b}"
+        }
+    "#);
+
+    assert_eq!(output, "1 0");
+}
+
+#[test]
+fn repeated_structs_are_separate() {
+    let output = run(r#"
+        struct P { x: i32 }
+
+        impl P {
+            fn get(self) -> i32 { self.x }
+        }
+
+        fn main() -> string {
+            let n = 3;
+            let mut ps = [P { x: 1 }; n];
+            ps[1].x = 5;
+            let a = ps[0].get();
+            let b = ps[1].get();
+            let c = ps[2].x;
+            f"{a} {b} {c}"
+        }
+    "#);
+
+    assert_eq!(output, "1 5 1");
+}
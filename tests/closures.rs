@@ -0,0 +1,57 @@
+mod common;
+
+use common::{errors, run};
+
+#[test]
+fn parameter_types_are_inferred_from_the_first_call() {
+    let output = run(r#"
+        fn main() -> i32 {
+            let x = 10;
+            let g = |a, b| {
+                a * b + x
+            };
+            let x = 1000;
+            let not = |b| !b;
+            let twice = |f: fn(i32) -> i32, v| f(f(v));
+            let inc = |n| n + 1;
+
+            let n = if not(false) { twice(inc, 5) } else { 0 };
+            g(2, 3) + g(4, 5) + n + x
+        }
+    "#);
+
+    assert_eq!(output, "1053");
+}
+
+#[test]
+fn parameter_types_need_a_call_or_an_annotation() {
+    let errors = errors(
+        r#"
+        fn main() -> i32 {
+            let id = |a| a;
+            1
+        }
+        "#,
+    );
+
+    assert_eq!(errors.len(), 1, "{errors:#?}");
+    assert!(errors[0].contains("The type of `a` can't be inferred"));
+}
+
+#[test]
+fn later_calls_are_checked_against_the_first() {
+    let errors = errors(
+        r#"
+        fn main() -> i32 {
+            let g = |a, b| a + b;
+            g(1, 2) + g("a", 2)
+        }
+        "#,
+    );
+
+    assert_eq!(errors.len(), 1, "{errors:#?}");
+    assert!(
+        errors[0].contains("Expected `i32`, found `string`"),
+        "{errors:#?}"
+    );
+}
@@ -0,0 +1,99 @@
+mod common;
+
+use chumsky::Parser;
+use common::{errors, run};
+use jester_script::parser;
+use jester_script::syntax::Item;
+
+#[test]
+fn doc_comments_in_blocks_are_comments() {
+    let output = run(r#"
+        fn main() -> i32 {
+            /// The answer
+            let x = 1;
+            let f = |y: i32| {
+                //! Adds one
+                y + 1
+                /// Nothing comes after it
+            };
+            /// Trailing
+            f(x)
+            /// Ends the block
+        }
+    "#);
+
+    assert_eq!(output, "2");
+}
+
+#[test]
+fn block_comments_nest() {
+    let output = run(r#"
+        /* outer /* inner */ still a comment */
+        fn main() -> string {
+            let x = 1 /* a /* b */ c */ + /**/ 2;
+            // `/*` in a line comment doesn't start a block comment
+            f"{x} // not a comment /* either */"
+        }
+    "#);
+
+    assert_eq!(output, "3 // not a comment /* either */");
+}
+
+#[test]
+fn unterminated_block_comments_are_reported() {
+    let errors = errors(
+        r#"
+        fn main() -> i32 { 1 }
+        /* never /* closed */
+        "#,
+    );
+
+    assert_eq!(errors.len(), 1, "{errors:#?}");
+    assert!(errors[0].contains("unterminated block comment"));
+}
+
+#[test]
+fn doc_comments_attach_to_items() {
+    let input = r#"//! The file
+//! of items
+
+/// A point
+/// in the plane.
+struct Point {
+    /// Horizontal
+    x: i32,
+}
+
+//// Not a doc comment
+/// Shapes
+enum Shape {
+    /// Nothing
+    Empty,
+}
+
+/// Methods
+impl Point {
+    /// Gets `x`
+    fn get(self) -> i32 { self.x }
+}
+
+fn main() -> i32 { 1 }
+"#;
+    let file = parser::file().parse(input).into_output().unwrap();
+
+    assert_eq!(file.doc, [" The file", " of items"]);
+
+    let [Item::Struct(point), Item::Enum(shape), Item::Impl(r#impl), Item::Fn(main)] =
+        file.items.as_slice()
+    else {
+        panic!("{:#?}", file.items);
+    };
+
+    assert_eq!(point.doc, [" A point", " in the plane."]);
+    assert_eq!(point.fields[0].doc, [" Horizontal"]);
+    assert_eq!(shape.doc, [" Shapes"]);
+    assert_eq!(shape.variants[0].doc, [" Nothing"]);
+    assert_eq!(r#impl.doc, [" Methods"]);
+    assert_eq!(r#impl.fns[0].doc, [" Gets `x`"]);
+    assert!(main.doc.is_empty());
+}
@@ -0,0 +1,119 @@
+// Each test crate uses only some of the helpers
+#![allow(dead_code)]
+
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+use ariadne::Source;
+use chumsky::Parser;
+
+use jester_script::diagnostics::Diagnostics;
+use jester_script::js::Display;
+use jester_script::parser;
+use jester_script::report;
+use jester_script::trans::Trans;
+use jester_script::typeck::Typeck;
+
+/// Compiles `input` like the CLI does, returning the JS and the printed reports without colors.
+pub fn compile(input: &str) -> (Option<String>, Vec<String>) {
+    let mut diagnostics = Diagnostics::new();
+    let (file, errs) = parser::file().parse(input).into_output_errors();
+
+    for err in errs {
+        diagnostics.error(*err.span(), report::parse_err(err));
+    }
+
+    let js = file.and_then(|file| {
+        let (types, typeck_diagnostics) = Typeck::new().check_file(&file);
+        diagnostics.extend(typeck_diagnostics);
+
+        (!diagnostics.has_errors()).then(|| {
+            let js = Trans::new(types).trans_file(&file);
+
+            Display::new(&js).to_string()
+        })
+    });
+    let reports = diagnostics
+        .into_reports()
+        .into_iter()
+        .map(|report| {
+            let mut out = Vec::new();
+            report.write(Source::from(input), &mut out).unwrap();
+
+            strip_colors(&String::from_utf8(out).unwrap())
+        })
+        .collect();
+
+    (js, reports)
+}
+
+/// Compiles `input` and runs it with node, returning what `main` returns.
+pub fn run(input: &str) -> String {
+    let (js, output) = run_node(input);
+    assert!(
+        output.status.success(),
+        "node failed:\n{}\n{js}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    String::from_utf8(output.stdout)
+        .unwrap()
+        .trim_end()
+        .to_owned()
+}
+
+/// Compiles `input` and runs it with node, which must fail, returning the error it printed.
+pub fn panics(input: &str) -> String {
+    let (js, output) = run_node(input);
+    assert!(
+        !output.status.success(),
+        "node succeeded:\n{}\n{js}",
+        String::from_utf8_lossy(&output.stdout)
+    );
+
+    String::from_utf8(output.stderr).unwrap()
+}
+
+fn run_node(input: &str) -> (String, Output) {
+    let (js, reports) = compile(input);
+    let Some(js) = js else {
+        panic!("compilation failed:\n{}", reports.join("\n"));
+    };
+
+    let mut node = Command::new("node")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("node is needed to run the compiled code");
+    let mut stdin = node.stdin.take().unwrap();
+    write!(stdin, "{js}\nconsole.log(main());\n").unwrap();
+    drop(stdin);
+
+    let output = node.wait_with_output().unwrap();
+
+    (js, output)
+}
+
+/// Compiles `input`, which must fail, returning the printed reports.
+pub fn errors(input: &str) -> Vec<String> {
+    let (js, reports) = compile(input);
+    assert!(js.is_none(), "compilation succeeded:\n{}", js.unwrap());
+
+    reports
+}
+
+fn strip_colors(s: &str) -> String {
+    let mut stripped = String::with_capacity(s.len());
+    let mut chars = s.chars();
+
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            chars.by_ref().find(|c| *c == 'm');
+        } else {
+            stripped.push(c);
+        }
+    }
+
+    stripped
+}
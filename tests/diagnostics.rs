@@ -0,0 +1,201 @@
+mod common;
+
+use common::errors;
+
+#[test]
+fn parse_error_in_let_is_not_reported_again() {
+    let errors = errors(
+        r#"
+        fn f() -> i32 { return 1 +; }
+
+        fn main() -> string {
+            let x = 1 +;
+            let y = x * 2;
+            let s = f"{x}";
+            let l = x.len();
+            let r = x[0];
+            let t: i32 = x;
+            let b = x && true;
+            let n = -x;
+            s
+        }
+        "#,
+    );
+
+    assert_eq!(errors.len(), 2, "{errors:#?}");
+    assert!(errors.iter().all(|error| error.contains("found ;")));
+}
+
+#[test]
+fn duplicate_items_are_reported() {
+    let errors = errors(
+        r#"
+        fn f() -> i32 { 1 }
+        fn f() -> i32 { 2 }
+        struct S { x: i32 }
+        struct S { y: i32 }
+        enum S { A }
+
+        fn main() -> i32 { f() }
+        "#,
+    );
+
+    assert_eq!(errors.len(), 3, "{errors:#?}");
+
+    for (error, name) in errors.iter().zip(["f", "S", "S"]) {
+        assert!(error.contains(&format!("`{name}` is declared more than once")));
+        assert!(error.contains("First declared here"));
+    }
+}
+
+#[test]
+fn numbers_dont_expect_their_optional_parts() {
+    let errors = errors(
+        r#"
+        fn f(a: i32) -> i32 { a }
+
+        fn main() -> i32 {
+            let a = f(1;
+            let b = 12 13;
+            let c = 1.5 2;
+            a
+        }
+        "#,
+    );
+
+    assert_eq!(errors.len(), 3, "{errors:#?}");
+    assert!(errors[0].contains("found ; expected"), "{}", errors[0]);
+    assert!(errors[0].contains("',', or ')'"), "{}", errors[0]);
+    assert!(errors[1].contains("found 1 expected"), "{}", errors[1]);
+    assert!(errors[2].contains("found 2 expected"), "{}", errors[2]);
+
+    for error in &errors {
+        assert!(!error.contains("'e'"), "{error}");
+    }
+}
+
+#[test]
+fn generic_impl_blocks_take_the_type_of_the_receiver() {
+    let errors = errors(
+        r#"
+        struct Wrap<T> { value: T }
+
+        impl<T> Wrap<T> {
+            fn set(mut self, value: T) { self.value = 1; }
+        }
+
+        fn main() {
+            let w = Wrap { value: "a" };
+            w.set(2);
+        }
+        "#,
+    );
+
+    assert_eq!(errors.len(), 2, "{errors:#?}");
+    assert!(errors[0].contains("Expected `T`, found `i32`"));
+    assert!(errors[1].contains("Expected `string`, found `i32`"));
+}
+
+#[test]
+fn self_is_not_suggested_as_a_raw_identifier() {
+    let errors = errors(
+        r#"
+        fn self() {}
+
+        fn main() {
+            let fn = 1;
+        }
+        "#,
+    );
+
+    assert_eq!(errors.len(), 2, "{errors:#?}");
+    assert!(errors[0].contains("`self` is a keyword and can't be an identifier"));
+    assert!(errors[1].contains("`fn` is a keyword, use `r#fn` for an identifier"));
+}
+
+#[test]
+fn missing_semicolon_resumes_at_the_next_statement() {
+    let errors = errors(
+        r#"
+        fn a() -> i32 { let x = 1 let y = 2; y }
+
+        fn main() -> i32 {
+            let s = 1 + a()
+            let t = s * 2;
+            t
+        }
+        "#,
+    );
+
+    assert_eq!(errors.len(), 2, "{errors:#?}");
+    assert!(errors
+        .iter()
+        .all(|error| error.contains("found l expected")));
+}
+
+#[test]
+fn items_on_the_line_of_an_error_are_kept() {
+    let errors = errors("fn a() -> i32 { ) } fn b() -> i32 { 1 } fn main() -> i32 { b() }");
+
+    assert_eq!(errors.len(), 1, "{errors:#?}");
+    assert!(errors[0].contains("found )"));
+}
+
+#[test]
+fn unknown_types_with_type_arguments() {
+    let errors = errors("fn f(x: Vec<i32>) {} fn main() {}");
+
+    assert_eq!(errors.len(), 1, "{errors:#?}");
+    assert!(
+        errors[0].contains("Type `Vec` does not exist"),
+        "{errors:#?}"
+    );
+}
+
+#[test]
+fn duplicate_type_params_are_reported() {
+    let errors = errors(
+        r#"
+        fn f<T, T>(x: T) {}
+
+        struct S<A, A> { a: A }
+
+        struct W<T> { t: T }
+
+        impl<T> W<T> {
+            fn m<T>(self) {}
+        }
+
+        fn main() { f(1); }
+        "#,
+    );
+
+    assert_eq!(errors.len(), 3, "{errors:#?}");
+    assert!(errors
+        .iter()
+        .all(|error| error.contains("is declared more than once")));
+}
+
+#[test]
+fn names_bound_twice_in_a_pattern_are_reported() {
+    let errors = errors(
+        r#"
+        fn f((a, a): (i32, i32)) -> i32 { a }
+
+        fn main() -> i32 {
+            let (b, b) = (1, 2);
+            let g = |(c, c): (i32, i32)| c;
+            let n = match (1, 2) { (d, d) => d };
+            let (x, y) = (1, 2);
+            let t = (1, 2);
+            t.2
+        }
+        "#,
+    );
+
+    assert_eq!(errors.len(), 5, "{errors:#?}");
+    assert!(errors[..4]
+        .iter()
+        .all(|error| error.contains("is bound more than once")));
+    assert!(errors[4].contains("`(i32, i32)` has no field `2`"));
+}
@@ -0,0 +1,92 @@
+mod common;
+
+use common::run;
+
+#[test]
+fn functions_and_types_can_share_a_name() {
+    let output = run(r#"
+        struct Point { x: i32 }
+
+        fn Point(x: i32) -> Point { Point { x: x * 2 } }
+
+        fn main() -> i32 { Point(3).x }
+    "#);
+
+    assert_eq!(output, "6");
+}
+
+#[test]
+fn types_shadow_the_prelude() {
+    let output = run(r#"
+        struct Option { x: i32 }
+
+        fn main() -> i32 {
+            let o = Option { x: 2 };
+            o.x
+        }
+    "#);
+
+    assert_eq!(output, "2");
+}
+
+#[test]
+fn generic_impl_blocks() {
+    let output = run(r#"
+        struct Wrap<T> { value: T }
+
+        impl<T> Wrap<T> {
+            fn new(value: T) -> Wrap<T> { Wrap { value } }
+
+            fn get(self) -> T { self.value }
+
+            fn map<U>(self, f: fn(T) -> U) -> Wrap<U> { Wrap::new(f(self.get())) }
+        }
+
+        enum Tree<T> { Leaf, Node(T) }
+
+        impl<T> Tree<T> {
+            fn or(self, default: T) -> T {
+                match self {
+                    Tree::Leaf => default,
+                    Tree::Node(value) => value,
+                }
+            }
+        }
+
+        fn unwrap<T>(wrap: Wrap<T>) -> T { wrap.get() }
+
+        fn main() -> string {
+            let n = Wrap::new(2).map(|x| x * 5).get();
+            let leaf: Tree<string> = Tree::Leaf;
+            f"{n} {unwrap(Wrap::new(true))} {leaf.or("leaf")} {Tree::Node(4).or(0)}"
+        }
+    "#);
+
+    assert_eq!(output, "10 true leaf 4");
+}
+
+#[test]
+fn fields_can_be_named_like_js_specials() {
+    let output = run(r#"
+        struct P { __proto__: i32, constructor: i32 }
+
+        enum E { V { __proto__: i32 } }
+
+        impl P {
+            fn get(self) -> i32 { self.__proto__ + self.constructor }
+        }
+
+        fn main() -> string {
+            let mut p = P { __proto__: 3, constructor: 1 };
+            let q = P { constructor: 2, ..p };
+            p.__proto__ = 4;
+            let e = E::V { __proto__: 5 };
+            let n = match e { E::V { __proto__ } => __proto__ };
+            let P { __proto__: m, .. } = q;
+
+            f"{p.get()} {q.get()} {n} {m}"
+        }
+    "#);
+
+    assert_eq!(output, "5 5 5 3");
+}
@@ -0,0 +1,144 @@
+mod common;
+
+use common::{compile, run};
+
+#[test]
+fn guarded_catch_all_arm_falls_through() {
+    let output = run(r#"
+        fn classify(i: i32) -> i32 {
+            match i {
+                0 => 1,
+                x if x > 5 => 2,
+                _ => 3,
+            }
+        }
+
+        fn main() -> string {
+            f"{classify(0)} {classify(3)} {classify(8)}"
+        }
+    "#);
+
+    assert_eq!(output, "1 3 2");
+}
+
+#[test]
+fn guarded_arms_in_loop() {
+    let output = run(r#"
+        fn main() -> string {
+            let mut i = 0;
+            let mut hits = 0;
+            let result = loop {
+                i += 1;
+                match i {
+                    n if n > 5 => break n * 10,
+                    n if n == 2 => continue,
+                    _ => hits += 1,
+                }
+            };
+            f"{result} {hits}"
+        }
+    "#);
+
+    assert_eq!(output, "60 4");
+}
+
+#[test]
+fn arms_after_matching_every_value_are_unreachable() {
+    let input = r#"
+        fn describe(b: bool, i: i32) -> i32 {
+            match (b, i) {
+                (true, 0) => 0,
+                (true, _) => 1,
+                (false, _) => 2,
+                (false, 3) => 3,
+                _ => 4,
+            }
+        }
+
+        fn main() -> i32 {
+            describe(true, 0) + describe(false, 3) * 10
+        }
+    "#;
+    let (js, reports) = compile(input);
+
+    assert!(js.is_some(), "{reports:#?}");
+    assert_eq!(reports.len(), 2, "{reports:#?}");
+    assert!(reports
+        .iter()
+        .all(|report| report.starts_with("Warning: Unreachable `match` arm")));
+    assert_eq!(run(input), "20");
+}
+
+#[test]
+fn recursive_types() {
+    let output = run(r#"
+        enum List { Nil, Cons(i32, List) }
+
+        struct Node { value: i32, next: Option<Node> }
+
+        fn ignore(list: List) -> i32 { 0 }
+
+        fn sum(list: List) -> i32 {
+            match list {
+                List::Nil => 0,
+                List::Cons(value, rest) => value + sum(rest),
+            }
+        }
+
+        fn len(node: Node) -> i32 {
+            match node.next {
+                Some(next) => 1 + len(next),
+                None => 1,
+            }
+        }
+
+        fn main() -> string {
+            let list = List::Cons(1, List::Cons(2, List::Nil));
+            let node = Node { value: 1, next: Some(Node { value: 2, next: None }) };
+            f"{ignore(List::Nil)} {sum(list)} {len(node)}"
+        }
+    "#);
+
+    assert_eq!(output, "0 3 2");
+}
+
+#[test]
+fn missing_patterns_of_recursive_types() {
+    let (_, reports) = compile(
+        r#"
+        enum List { Nil, Cons(i32, List) }
+
+        fn f(list: List) -> i32 {
+            match list {
+                List::Cons(1, List::Nil) => 0,
+                List::Nil => 1,
+            }
+        }
+
+        fn main() {}
+        "#,
+    );
+
+    assert_eq!(reports.len(), 1, "{reports:#?}");
+    assert!(
+        reports[0].contains("`List::Cons(_, List::Nil)` and `List::Cons(_, List::Cons(_, _))`"),
+        "{reports:#?}"
+    );
+}
+
+#[test]
+fn wide_tuple_binding() {
+    let output = run(r#"
+        fn main() -> bool {
+            let t = (
+                true, true, true, true, true, true, true, true, true, true,
+                true, true, true, true, true, true, true, true, true, true,
+                true, true, true, true, true, true, true, true, true, false,
+            );
+            let (a, _, _, _, _, _, _, _, _, _, _, _, _, _, _, _, _, _, _, _, _, _, _, _, _, _, _, _, _, b) = t;
+            a && !b
+        }
+    "#);
+
+    assert_eq!(output, "true");
+}
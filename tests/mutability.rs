@@ -0,0 +1,96 @@
+mod common;
+
+use common::{compile, run};
+
+#[test]
+fn mut_bindings_get_their_own_copy() {
+    let output = run(r#"
+        fn main() -> string {
+            let a = [[1], [2]];
+            let mut b = a;
+            b[0][0] = 9;
+            let mut c = [0];
+            c = a[1];
+            c[0] = 8;
+            f"{a[0][0]} {a[1][0]} {b[0][0]} {c[0]}"
+        }
+    "#);
+
+    assert_eq!(output, "1 2 9 8");
+}
+
+#[test]
+fn mut_arguments_get_their_own_copy() {
+    let output = run(r#"
+        struct P { x: i32 }
+
+        impl P {
+            fn bumped(mut self) -> P {
+                self.x += 1;
+                self
+            }
+        }
+
+        fn bump(mut p: P) -> i32 {
+            p.x += 1;
+            p.x
+        }
+
+        fn main() -> string {
+            let p = P { x: 1 };
+            let set = |mut xs: [i32]| { xs[0] = 5; xs[0] };
+            let xs = [0];
+            f"{bump(p)} {p.bumped().x} {P::bumped(p).x} {p.x} {set(xs)} {xs[0]}"
+        }
+    "#);
+
+    assert_eq!(output, "2 2 2 1 5 0");
+}
+
+#[test]
+fn values_read_out_of_mut_bindings_are_copied() {
+    let output = run(r#"
+        struct P { x: i32, xs: [i32] }
+
+        struct Wrap { p: P }
+
+        impl P {
+            fn xs(self) -> [i32] { self.xs }
+        }
+
+        fn wrap(p: P) -> Wrap { Wrap { p } }
+
+        fn main() -> string {
+            let mut a = P { x: 1, xs: [1] };
+            let b = a;
+            let w = wrap(a);
+            let ps = [a];
+            let xs = a.xs();
+            let c = P { x: 0, ..a };
+            a.x = 5;
+            a.xs[0] = 9;
+            f"{b.x} {w.p.x} {ps[0].x} {xs[0]} {c.xs[0]} {a.x}"
+        }
+    "#);
+
+    assert_eq!(output, "1 1 1 1 1 5");
+}
+
+#[test]
+fn new_values_are_not_copied() {
+    let (js, reports) = compile(
+        r#"
+        struct P { x: i32 }
+
+        fn main() -> i32 {
+            let mut xs = [1, 2, 3];
+            let mut p = P { x: 1 };
+            xs[0] = p.x;
+            xs[0]
+        }
+        "#,
+    );
+    let js = js.unwrap_or_else(|| panic!("{reports:#?}"));
+
+    assert!(!js.contains("$clone"), "{js}");
+}
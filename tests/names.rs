@@ -0,0 +1,86 @@
+mod common;
+
+use common::{compile, run};
+use jester_script::trans::names::{escape, unique_name};
+
+#[test]
+fn reserved_words_and_globals_are_escaped() {
+    assert_eq!(escape("x"), "x");
+    assert_eq!(escape("var"), "var_");
+    assert_eq!(escape("class"), "class_");
+    assert_eq!(escape("Math"), "Math_");
+    assert_eq!(escape("undefined"), "undefined_");
+    assert_eq!(escape("var_"), "var_");
+}
+
+#[test]
+fn used_names_get_a_number() {
+    let used = ["x", "x__2", "class_"];
+    let is_used = |name: &str| used.contains(&name);
+
+    assert_eq!(unique_name("y", is_used), "y");
+    assert_eq!(unique_name("x", is_used), "x__3");
+    assert_eq!(unique_name("class", is_used), "class___2");
+}
+
+#[test]
+fn shadowed_and_reserved_names() {
+    let output = run(r#"
+        fn var(new: i32) -> i32 { new + 1 }
+
+        fn main() -> string {
+            let x = 1;
+            let x = x + 1;
+            let x = x + 1;
+            let var_ = 100;
+            let Math = 10;
+            let doubled = { let x = x * Math; x };
+            let f = |x: i32| { let x = x * 3; x };
+
+            f"{x} {var(x)} {var_} {Math} {doubled} {f(x)}"
+        }
+    "#);
+
+    assert_eq!(output, "3 4 100 10 30 9");
+}
+
+#[test]
+fn characters_js_does_not_accept_are_escaped() {
+    assert_eq!(escape("größe"), "größe");
+    assert_eq!(escape("名前"), "名前");
+    assert_eq!(escape("a-b"), "a$u2d$b");
+    assert_eq!(escape("1a"), "$u31$a");
+}
+
+#[test]
+fn unicode_identifiers() {
+    let output = run(r#"
+        struct Größe { höhe: i32 }
+
+        fn 名前() -> string { "ok" }
+
+        fn main() -> string {
+            let größe = Größe { höhe: 2 };
+            f"{größe.höhe} {名前()}"
+        }
+    "#);
+
+    assert_eq!(output, "2 ok");
+}
+
+#[test]
+fn identifiers_are_compared_in_nfc() {
+    // `café` with a precomposed `é`, then with `e` and a combining acute accent
+    let input = "
+        fn main() -> i32 {
+            let caf\u{e9} = 1;
+            cafe\u{301} + 1
+        }
+    ";
+    let (js, reports) = compile(input);
+    let js = js.unwrap_or_else(|| panic!("{reports:#?}"));
+
+    assert!(js.contains("const caf\u{e9} = 1;"), "{js}");
+    assert!(!js.contains('\u{301}'), "{js}");
+    assert_eq!(run(input), "2");
+}
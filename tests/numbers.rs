@@ -0,0 +1,102 @@
+mod common;
+
+use common::{errors, panics, run};
+
+#[test]
+fn literals_in_arithmetic_take_the_expected_type() {
+    let output = run(r#"
+        fn f() -> i64 { 1 + 2 }
+
+        fn g() -> i64 { -(4 * 5) + (2 - 1) * 3 }
+
+        fn main() -> string {
+            let t: i64 = 3000000000 * 2;
+            let a = f() + g() + t;
+            f"{a}"
+        }
+    "#);
+
+    assert_eq!(output, "5999999986");
+}
+
+#[test]
+fn expected_type_does_not_change_variables() {
+    let errors = errors(
+        r#"
+        fn main() -> i64 {
+            let x = 1;
+            x + 1
+        }
+        "#,
+    );
+
+    assert_eq!(errors.len(), 1, "{errors:#?}");
+    assert!(errors[0].contains("Expected `i64`, found `i32`"));
+}
+
+#[test]
+fn malformed_literals_are_reported_once() {
+    let errors = errors(
+        r#"
+        fn main() -> i32 {
+            let x: i32 = 0xfffffffffffffffffff;
+            let y = 1.5_i32 + 1;
+            let z = 2_u8;
+            x + y + z
+        }
+        "#,
+    );
+
+    assert_eq!(errors.len(), 3, "{errors:#?}");
+    assert!(errors[0].contains("integer literal is out of range for `i64`"));
+    assert!(errors[1].contains("`i32` literals can't have a fraction"));
+    assert!(errors[2].contains("invalid suffix `u8` for number literal"));
+}
+
+#[test]
+fn number_literals() {
+    let output = run(r#"
+        fn main() -> string {
+            let a = 1.5 + 1e3 + 2.5E-2 + 1e+2_f64;
+            let b = 1_000 + 0xff_ff + 0b101 + 0o17;
+            let mut c = 0;
+            for i in 1..3 {
+                c += i;
+            }
+            f"{a} {b} {c}"
+        }
+    "#);
+
+    assert_eq!(output, "1101.525 66555 3");
+}
+
+#[test]
+fn integer_division_by_zero_panics() {
+    for ty in ["i32", "i64"] {
+        let error = panics(&format!(
+            r#"
+            fn divide(x: {ty}, y: {ty}) -> {ty} {{ x / y }}
+
+            fn main() -> {ty} {{
+                let mut z: {ty} = 7;
+                z /= 2;
+                divide(z, 0)
+            }}
+            "#
+        ));
+
+        assert!(
+            error.contains("RangeError: attempt to divide by zero"),
+            "{error}"
+        );
+    }
+}
+
+#[test]
+fn float_division_by_zero_is_infinite() {
+    let output = run(r#"
+        fn main() -> f64 { 1.0 / 0.0 }
+    "#);
+
+    assert_eq!(output, "Infinity");
+}
@@ -0,0 +1,42 @@
+mod common;
+
+use common::{compile, run};
+
+#[test]
+fn escapes() {
+    let output = run(r##"
+        fn main() -> string {
+            let s = "a\tb \"q\" \\ \u{1F600} \
+                continued";
+            let raw = r#"raw "\n" \u{1F600}"#;
+
+            s + "|" + raw + "|" + r"\t"
+        }
+    "##);
+
+    assert_eq!(
+        output,
+        "a\tb \"q\" \\ 😀 continued|raw \"\\n\" \\u{1F600}|\\t"
+    );
+}
+
+#[test]
+fn template_literals_escape_backticks_and_substitutions() {
+    let input = r#"
+        fn main() -> string {
+            let name = "Ann";
+            let s = "` ${x}";
+
+            f"{name}: `tick` ${name} {{braces}} \u{7} {s} {f"in {1 + 2}"}"
+        }
+    "#;
+    let (js, reports) = compile(input);
+    let js = js.unwrap_or_else(|| panic!("{reports:#?}"));
+
+    assert!(js.contains(r#"const s = "` ${x}";"#), "{js}");
+    assert!(
+        js.contains(r"`${name}: \`tick\` $${name} {braces} \u{7} ${s} ${`in ${1 + 2 | 0}`}`"),
+        "{js}"
+    );
+    assert_eq!(run(input), "Ann: `tick` $Ann {braces} \u{7} ` ${x} in 3");
+}
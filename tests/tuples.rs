@@ -0,0 +1,19 @@
+mod common;
+
+use common::run;
+
+#[test]
+fn fields_are_numbered() {
+    let output = run(r#"
+        fn main() -> string {
+            let t = (1, ("two", 3), true);
+            let mut u = (5, 6);
+            u.1 = u.0 + 10;
+            let nested = ((1, 2), (3, (4, 5)));
+
+            f"{t.0} {t.1.0} {t.1.1} {t.2} {u.1} {nested.1.1.0}"
+        }
+    "#);
+
+    assert_eq!(output, "1 two 3 true 15 4");
+}